@@ -1,16 +1,19 @@
 mod error;
+mod spin;
 
 use canberra_engine::{
-  Application, Entity, Scene, Shader, ShaderHandle,
+  Application, Entity, Prefab, RenderSettings, Scene, Schedule, Shader, ShaderHandle, Stage,
   components::{Camera, Material, Mesh, Transform},
   register_shaders,
 };
 use glam::Vec3;
 
 pub use self::error::{Error, Result};
+use self::spin::Spin;
 
 fn try_main() -> Result<()> {
-  Application::run(|| {
+  let schedule = Schedule::new().with_system(Stage::Update, spin::spin);
+  Application::run_with_schedule(RenderSettings::default(), schedule, || {
     let mut wobble_shader = ShaderHandle::default();
     let mut bloom_shader = ShaderHandle::default();
     register_shaders(|registry| {
@@ -46,30 +49,36 @@ fn try_main() -> Result<()> {
     }
     scene.add(colored_group);
 
-    // Group: 3 cubes of the same color (gold)
+    // Group: 3 instances of a gold cube prefab
+    let mut gold_cube = Entity::new("GoldCube");
+    gold_cube.add_component(Transform::default());
+    gold_cube.add_component(Mesh::cube());
+    gold_cube.add_component(Material::with_color([1.0, 0.75, 0.0, 1.0]));
+    let gold_cube = Prefab::new("GoldCube", &gold_cube).expect("prefab serializes");
     let mut same_group = Entity::new("SameCubes");
     same_group.add_component(Transform::default());
-    let gold = [1.0f32, 0.75, 0.0, 1.0];
     for i in 0..3usize {
-      let mut e = Entity::new(&format!("CubeSame_{i}"));
+      let mut e = gold_cube.instantiate().expect("prefab deserializes");
+      e.name = format!("CubeSame_{i}");
       e.add_component(Transform::from_translation(Vec3::new(
         (i as f32 - 1.0) * 3.0,
         1.5,
         0.0,
       )));
-      e.add_component(Mesh::cube());
-      e.add_component(Material::with_color(gold));
       same_group.add_child(e);
     }
+    scene.add_prefab(gold_cube);
     scene.add(same_group);
 
     // Wobbly cube (center, front)
     let mut wobbly = Entity::new("WobblyCube");
     wobbly.add_component(Transform::from_translation(Vec3::new(0.0, 4.0, 0.0)));
     wobbly.add_component(Mesh::cube());
+    wobbly.add_component(Spin::default());
     wobbly.add_component(Material {
       color: [0.9, 0.5, 0.1, 1.0],
       shader: wobble_shader,
+      ..Default::default()
     });
     scene.add(wobbly);
 
@@ -80,6 +89,7 @@ fn try_main() -> Result<()> {
     bloomy.add_component(Material {
       color: [1.0, 1.0, 0.3, 1.0],
       shader: bloom_shader,
+      ..Default::default()
     });
     scene.add(bloomy);
 
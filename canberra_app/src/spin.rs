@@ -0,0 +1,25 @@
+use canberra_engine::{Component, Inspect, Scene, SystemContext, components::Transform};
+use glam::Quat;
+
+/// Turns its entity about the Y axis.
+#[derive(Debug, Clone, Component, Inspect, serde::Serialize, serde::Deserialize)]
+#[component(category = "Demo")]
+pub struct Spin {
+  /// Radians per second.
+  #[inspect(degrees, speed = 1.0, suffix = "°/s")]
+  pub speed: f32,
+}
+
+impl Default for Spin {
+  fn default() -> Self {
+    Self {
+      speed: 45_f32.to_radians(),
+    }
+  }
+}
+
+pub fn spin(scene: &mut Scene, ctx: &SystemContext) {
+  for (mut transform, spin) in scene.query_mut::<(&mut Transform, &Spin)>() {
+    transform.rotation = Quat::from_rotation_y(spin.speed * ctx.delta) * transform.rotation;
+  }
+}
@@ -0,0 +1,181 @@
+use proc_macro2::TokenStream;
+use quote::{quote, quote_spanned};
+use syn::{DeriveInput, LitStr, Path};
+
+/// Category of registered components that do not name one.
+const DEFAULT_CATEGORY: &str = "General";
+
+#[derive(Default)]
+struct Options {
+  name: Option<LitStr>,
+  multiple: bool,
+  hidden: bool,
+  category: Option<LitStr>,
+  create: Option<Path>,
+}
+
+fn parse_options(input: &DeriveInput) -> syn::Result<Options> {
+  let mut options = Options::default();
+  for attr in input
+    .attrs
+    .iter()
+    .filter(|a| a.path().is_ident("component"))
+  {
+    attr.parse_nested_meta(|meta| {
+      if meta.path.is_ident("name") {
+        options.name = Some(meta.value()?.parse()?);
+      } else if meta.path.is_ident("multiple") {
+        options.multiple = true;
+      } else if meta.path.is_ident("hidden") {
+        options.hidden = true;
+      } else if meta.path.is_ident("category") {
+        options.category = Some(meta.value()?.parse()?);
+      } else if meta.path.is_ident("create") {
+        options.create = Some(meta.value()?.parse()?);
+      } else {
+        return Err(meta.error("expected `name`, `multiple`, `hidden`, `category` or `create`"));
+      }
+      Ok(())
+    })?;
+  }
+  if options.hidden && (options.create.is_some() || options.category.is_some()) {
+    return Err(syn::Error::new_spanned(
+      &input.ident,
+      "`category` and `create` only apply to registered components; remove `hidden`",
+    ));
+  }
+  Ok(options)
+}
+
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+  if !input.generics.params.is_empty() {
+    return Err(syn::Error::new_spanned(
+      &input.generics,
+      "components cannot be generic: each needs a single serialized type name",
+    ));
+  }
+  let options = parse_options(input)?;
+  let ident = &input.ident;
+  let name = options
+    .name
+    .unwrap_or_else(|| LitStr::new(&ident.to_string(), ident.span()));
+
+  let multiple = options.multiple;
+  let register = (!options.hidden).then(|| {
+    let category = options
+      .category
+      .unwrap_or_else(|| LitStr::new(DEFAULT_CATEGORY, ident.span()));
+    let create = match options.create {
+      Some(create) => quote! { #create },
+      None => quote_spanned! { ident.span()=> <#ident as ::std::default::Default>::default },
+    };
+    quote! {
+      ::canberra_engine::__inventory::submit! {
+        ::canberra_engine::ComponentRegistration::new::<#ident>(NAME, #category, MULTIPLE, || {
+          ::std::boxed::Box::new((#create)())
+        })
+      }
+    }
+  });
+
+  Ok(quote! {
+    const _: () = {
+      use ::canberra_engine::__typetag as typetag;
+
+      const NAME: &str = #name;
+      const MULTIPLE: bool = #multiple;
+
+      #[typetag::serde]
+      impl ::canberra_engine::Component for #ident {
+        fn name(&self) -> &'static str {
+          NAME
+        }
+
+        fn as_any(&self) -> &dyn ::std::any::Any {
+          self
+        }
+
+        fn as_any_mut(&mut self) -> &mut dyn ::std::any::Any {
+          self
+        }
+
+        fn multiple(&self) -> bool {
+          MULTIPLE
+        }
+
+        fn inspect(&mut self, ui: &mut ::canberra_engine::__egui::Ui) -> bool {
+          ::canberra_engine::Inspect::inspect(self, ui, &::std::default::Default::default())
+        }
+      }
+
+      #register
+    };
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use syn::parse_quote;
+
+  use super::*;
+
+  fn expanded(input: DeriveInput) -> String {
+    expand(&input).unwrap().to_string()
+  }
+
+  fn error(input: DeriveInput) -> String {
+    expand(&input).unwrap_err().to_string()
+  }
+
+  #[test]
+  fn registers_in_the_default_category() {
+    let output = expanded(parse_quote! { struct Spin { speed: f32 } });
+    assert!(output.contains("const NAME : & str = \"Spin\""));
+    assert!(output.contains("const MULTIPLE : bool = false"));
+    assert!(output.contains("submit !"));
+    assert!(output.contains("\"General\""));
+    assert!(output.contains("< Spin as :: std :: default :: Default > :: default"));
+  }
+
+  #[test]
+  fn options_override_the_defaults() {
+    let output = expanded(parse_quote! {
+      #[component(name = "Model", category = "Rendering", create = Mesh::cube, multiple)]
+      struct Mesh;
+    });
+    assert!(output.contains("const NAME : & str = \"Model\""));
+    assert!(output.contains("const MULTIPLE : bool = true"));
+    assert!(output.contains("\"Rendering\""));
+    assert!(output.contains("Mesh :: cube"));
+    assert!(!output.contains("Mesh as :: std :: default :: Default"));
+  }
+
+  #[test]
+  fn hidden_components_are_not_submitted() {
+    let output = expanded(parse_quote! {
+      #[component(hidden)]
+      struct Parent;
+    });
+    assert!(output.contains("impl :: canberra_engine :: Component for Parent"));
+    assert!(!output.contains("submit !"));
+  }
+
+  #[test]
+  fn rejects_invalid_options() {
+    assert!(error(parse_quote! { struct Wrapper<T>(T); }).contains("generic"));
+    assert!(
+      error(parse_quote! {
+        #[component(hidden, category = "General")]
+        struct Parent;
+      })
+      .contains("remove `hidden`")
+    );
+    assert!(
+      error(parse_quote! {
+        #[component(unknown)]
+        struct Spin;
+      })
+      .contains("expected `name`")
+    );
+  }
+}
@@ -0,0 +1,261 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Expr, ExprRange, Fields, LitInt, LitStr, RangeLimits};
+
+#[derive(Default)]
+struct FieldOptions {
+  skip: bool,
+  label: Option<LitStr>,
+  range: Option<(Expr, Expr)>,
+  speed: Option<Expr>,
+  decimals: Option<LitInt>,
+  suffix: Option<LitStr>,
+  degrees: bool,
+  color: bool,
+}
+
+fn parse_options(attrs: &[syn::Attribute]) -> syn::Result<FieldOptions> {
+  let mut options = FieldOptions::default();
+  for attr in attrs.iter().filter(|a| a.path().is_ident("inspect")) {
+    attr.parse_nested_meta(|meta| {
+      if meta.path.is_ident("skip") {
+        options.skip = true;
+      } else if meta.path.is_ident("label") {
+        options.label = Some(meta.value()?.parse()?);
+      } else if meta.path.is_ident("range") {
+        let range: ExprRange = meta.value()?.parse()?;
+        let (Some(start), RangeLimits::Closed(_), Some(end)) =
+          (range.start, range.limits, range.end)
+        else {
+          return Err(meta.error("expected an inclusive range like `0.0..=1.0`"));
+        };
+        options.range = Some((*start, *end));
+      } else if meta.path.is_ident("speed") {
+        options.speed = Some(meta.value()?.parse()?);
+      } else if meta.path.is_ident("decimals") {
+        options.decimals = Some(meta.value()?.parse()?);
+      } else if meta.path.is_ident("suffix") {
+        options.suffix = Some(meta.value()?.parse()?);
+      } else if meta.path.is_ident("degrees") {
+        options.degrees = true;
+      } else if meta.path.is_ident("color") {
+        options.color = true;
+      } else {
+        return Err(meta.error(
+          "expected `skip`, `label`, `range`, `speed`, `decimals`, `suffix`, `degrees` or `color`",
+        ));
+      }
+      Ok(())
+    })?;
+  }
+  Ok(options)
+}
+
+/// `InspectOptions` literal for a field.
+fn options_expr(options: &FieldOptions) -> TokenStream {
+  let option = |value: Option<TokenStream>| match value {
+    Some(value) => quote! { ::std::option::Option::Some(#value) },
+    None => quote! { ::std::option::Option::None },
+  };
+  let range = option(options.range.as_ref().map(|(start, end)| {
+    quote! { ::std::ops::RangeInclusive::new((#start) as f64, (#end) as f64) }
+  }));
+  let speed = option(
+    options
+      .speed
+      .as_ref()
+      .map(|speed| quote! { (#speed) as f64 }),
+  );
+  let decimals = option(options.decimals.as_ref().map(|d| quote! { #d }));
+  let suffix = option(options.suffix.as_ref().map(|s| quote! { #s }));
+  let degrees = options.degrees;
+  let color = options.color;
+  quote! {
+    ::canberra_engine::InspectOptions {
+      range: #range,
+      speed: #speed,
+      decimals: #decimals,
+      suffix: #suffix,
+      degrees: #degrees,
+      color: #color,
+    }
+  }
+}
+
+/// `cluster_radius` -> "Cluster radius".
+fn sentence_case(name: &str) -> String {
+  let spaced = name.trim_start_matches("r#").replace('_', " ");
+  let mut chars = spaced.chars();
+  match chars.next() {
+    Some(first) => first.to_uppercase().chain(chars).collect(),
+    None => spaced,
+  }
+}
+
+pub fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+  let ident = &input.ident;
+  let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+  // Salted with the widget's position too, so two values of the type in one parent (say,
+  // two fields of the same enum) keep apart.
+  let id = ident.to_string();
+
+  let body = match &input.data {
+    Data::Struct(data) => {
+      let rows = data
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+          let options = parse_options(&field.attrs)?;
+          if options.skip {
+            return Ok(TokenStream::new());
+          }
+          let (member, name) = match &field.ident {
+            Some(name) => (quote! { #name }, name.to_string()),
+            None => {
+              let index = syn::Index::from(i);
+              (quote! { #index }, i.to_string())
+            }
+          };
+          let label = options
+            .label
+            .as_ref()
+            .map(LitStr::value)
+            .unwrap_or_else(|| sentence_case(&name));
+          let field_options = options_expr(&options);
+          Ok(quote! {
+            ui.label(#label);
+            changed |= ::canberra_engine::Inspect::inspect(&mut self.#member, ui, &#field_options);
+            ui.end_row();
+          })
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+      quote! {
+        let mut changed = false;
+        ::canberra_engine::__egui::Grid::new(ui.next_auto_id().with(#id))
+          .num_columns(2)
+          .spacing([8.0, 4.0])
+          .show(ui, |ui| {
+            #(#rows)*
+          });
+        changed
+      }
+    }
+    Data::Enum(data) => {
+      let variants = data
+        .variants
+        .iter()
+        .map(|variant| {
+          if !matches!(variant.fields, Fields::Unit) {
+            return Err(syn::Error::new_spanned(
+              variant,
+              "Inspect can only be derived for enums of unit variants",
+            ));
+          }
+          Ok((&variant.ident, variant.ident.to_string()))
+        })
+        .collect::<syn::Result<Vec<_>>>()?;
+      let selected = variants
+        .iter()
+        .map(|(v, name)| quote! { Self::#v => #name });
+      let choices = variants.iter().map(|(v, name)| {
+        quote! {
+          if ui.selectable_label(matches!(self, Self::#v), #name).clicked()
+            && !matches!(self, Self::#v)
+          {
+            *self = Self::#v;
+            changed = true;
+          }
+        }
+      });
+      quote! {
+        let selected = match self {
+          #(#selected,)*
+        };
+        let mut changed = false;
+        ::canberra_engine::__egui::ComboBox::from_id_salt(ui.next_auto_id().with(#id))
+          .selected_text(selected)
+          .show_ui(ui, |ui| {
+            #(#choices)*
+          });
+        changed
+      }
+    }
+    Data::Union(_) => {
+      return Err(syn::Error::new_spanned(
+        ident,
+        "Inspect cannot be derived for unions",
+      ));
+    }
+  };
+
+  Ok(quote! {
+    impl #impl_generics ::canberra_engine::Inspect for #ident #ty_generics #where_clause {
+      fn inspect(
+        &mut self,
+        ui: &mut ::canberra_engine::__egui::Ui,
+        _options: &::canberra_engine::InspectOptions,
+      ) -> bool {
+        #body
+      }
+    }
+  })
+}
+
+#[cfg(test)]
+mod tests {
+  use syn::parse_quote;
+
+  use super::*;
+
+  fn expanded(input: DeriveInput) -> String {
+    expand(&input).unwrap().to_string()
+  }
+
+  fn error(input: DeriveInput) -> String {
+    expand(&input).unwrap_err().to_string()
+  }
+
+  #[test]
+  fn struct_rows_follow_field_options() {
+    let output = expanded(parse_quote! {
+      struct Marker {
+        #[inspect(range = 1.0..=256.0, suffix = " px")]
+        pub cluster_radius: f32,
+        #[inspect(label = "Tint", color)]
+        pub color: [f32; 4],
+        #[inspect(skip)]
+        pub cache: u32,
+      }
+    });
+    assert!(output.contains("\"Cluster radius\""));
+    assert!(output.contains("\"Tint\""));
+    assert!(output.contains("color : true"));
+    assert!(!output.contains("cache"));
+  }
+
+  #[test]
+  fn widget_ids_are_salted_by_position() {
+    let grid = expanded(parse_quote! { struct Point { x: f32 } });
+    assert!(grid.contains("Grid :: new (ui . next_auto_id () . with (\"Point\"))"));
+    let combo = expanded(parse_quote! { enum Shape { Circle, Square } });
+    assert!(combo.contains("from_id_salt (ui . next_auto_id () . with (\"Shape\"))"));
+  }
+
+  #[test]
+  fn rejects_unsupported_shapes() {
+    assert!(error(parse_quote! { enum Value { Number(f32) } }).contains("unit variants"));
+    assert!(error(parse_quote! { union Bits { a: u32 } }).contains("unions"));
+    assert!(
+      error(parse_quote! { struct Range { #[inspect(range = 0.0..1.0)] value: f32 } })
+        .contains("inclusive range")
+    );
+  }
+
+  #[test]
+  fn sentence_case_fields() {
+    assert_eq!(sentence_case("cluster_radius"), "Cluster radius");
+    assert_eq!(sentence_case("r#type"), "Type");
+    assert_eq!(sentence_case("0"), "0");
+  }
+}
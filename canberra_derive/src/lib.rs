@@ -0,0 +1,44 @@
+//! Derive macros for `canberra_engine`; use them through its re-exports.
+
+mod component;
+mod inspect;
+
+use proc_macro::TokenStream;
+use syn::{DeriveInput, parse_macro_input};
+
+/// Implements `Component`, registers the type for deserialization and adds it to the
+/// component registry, created with `Default`. The editor UI comes from the type's
+/// `Inspect` impl, derived or hand-written.
+///
+/// Container attributes, all optional:
+/// - `#[component(name = "...")]`: display name, the type's name by default.
+/// - `#[component(multiple)]`: allow several per entity.
+/// - `#[component(category = "...")]`: registry category, "General" by default.
+/// - `#[component(create = path::to::constructor)]`: create the type with this instead
+///   of `Default`.
+/// - `#[component(hidden)]`: leave the type out of the registry, e.g. for components the
+///   engine manages or that have no sensible default.
+#[proc_macro_derive(Component, attributes(component))]
+pub fn derive_component(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  component::expand(&input)
+    .unwrap_or_else(syn::Error::into_compile_error)
+    .into()
+}
+
+/// Implements `Inspect`: a labelled grid row per field for structs, a combo box for enums
+/// of unit variants.
+///
+/// Field attributes, all optional:
+/// - `#[inspect(skip)]`
+/// - `#[inspect(label = "...")]`: the field name in sentence case by default.
+/// - `#[inspect(range = 0.0..=1.0, speed = 0.1, decimals = 2, suffix = " px")]`
+/// - `#[inspect(degrees)]`: show a radian value in degrees; `range` is in degrees too.
+/// - `#[inspect(color)]`: edit an `[f32; 3]` or `[f32; 4]` with a color picker.
+#[proc_macro_derive(Inspect, attributes(inspect))]
+pub fn derive_inspect(input: TokenStream) -> TokenStream {
+  let input = parse_macro_input!(input as DeriveInput);
+  inspect::expand(&input)
+    .unwrap_or_else(syn::Error::into_compile_error)
+    .into()
+}
@@ -1,20 +1,48 @@
+mod input;
+mod profiler;
+mod schedule;
 mod state;
 use std::sync::Arc;
 
-pub use self::state::ApplicationState;
-use crate::{Result, Scene};
+pub use self::{
+  input::Input,
+  profiler::{FrameSample, PROFILER_HISTORY, Profiler},
+  schedule::{Schedule, Stage, System, SystemContext},
+  state::ApplicationState,
+};
+use crate::{RenderSettings, Result, Scene};
 
 pub struct Application {
   pub state: Option<ApplicationState>,
   scene_builder: Option<Box<dyn FnOnce() -> Scene>>,
+  settings: RenderSettings,
+  schedule: Option<Schedule>,
 }
 
 impl Application {
   pub fn run<F: FnOnce() -> Scene + 'static>(scene_builder: F) -> Result<()> {
+    Self::run_with_settings(RenderSettings::default(), scene_builder)
+  }
+
+  pub fn run_with_settings<F: FnOnce() -> Scene + 'static>(
+    settings: RenderSettings,
+    scene_builder: F,
+  ) -> Result<()> {
+    Self::run_with_schedule(settings, Schedule::new(), scene_builder)
+  }
+
+  /// Runs `schedule`'s systems on the scene every frame.
+  pub fn run_with_schedule<F: FnOnce() -> Scene + 'static>(
+    settings: RenderSettings,
+    schedule: Schedule,
+    scene_builder: F,
+  ) -> Result<()> {
     let event_loop = crate::window::event_loop()?;
     let mut app = Self {
       state: None,
       scene_builder: Some(Box::new(scene_builder)),
+      settings,
+      schedule: Some(schedule),
     };
     event_loop.run_app(&mut app)?;
     Ok(())
@@ -26,7 +54,15 @@ impl winit::application::ApplicationHandler for Application {
     let window_attributes = winit::window::Window::default_attributes();
     let window = Arc::new(event_loop.create_window(window_attributes).unwrap());
     let builder = self.scene_builder.take().unwrap();
-    self.state = Some(pollster::block_on(ApplicationState::new(window, builder)).unwrap());
+    self.state = Some(
+      pollster::block_on(ApplicationState::new(
+        window,
+        self.settings.clone(),
+        self.schedule.take().unwrap_or_default(),
+        builder,
+      ))
+      .unwrap(),
+    );
   }
 
   fn window_event(
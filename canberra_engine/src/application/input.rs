@@ -0,0 +1,82 @@
+use std::collections::HashSet;
+
+use glam::Vec2;
+use winit::{
+  event::{ElementState, MouseButton, MouseScrollDelta, WindowEvent},
+  keyboard::{KeyCode, PhysicalKey},
+};
+
+/// Pixels of trackpad scrolling treated as one wheel notch.
+const PIXELS_PER_LINE: f32 = 40.0;
+
+/// Mouse and keyboard state accumulated from window events over one frame.
+#[derive(Debug, Default, Clone)]
+pub struct Input {
+  /// Cursor position in physical pixels, `None` while outside the window.
+  pub cursor: Option<Vec2>,
+  /// Cursor movement since the previous frame, in physical pixels.
+  pub cursor_delta: Vec2,
+  /// Wheel notches scrolled since the previous frame; positive scrolls away from the user.
+  pub scroll: f32,
+  /// Set while egui is hovering or dragging, so scene controls should ignore the pointer.
+  pub pointer_captured: bool,
+  buttons: HashSet<MouseButton>,
+  keys: HashSet<KeyCode>,
+}
+
+impl Input {
+  pub fn mouse_down(&self, button: MouseButton) -> bool {
+    self.buttons.contains(&button)
+  }
+
+  pub fn key_down(&self, key: KeyCode) -> bool {
+    self.keys.contains(&key)
+  }
+
+  pub(crate) fn on_window_event(&mut self, event: &WindowEvent) {
+    match event {
+      WindowEvent::CursorMoved { position, .. } => {
+        let position = Vec2::new(position.x as f32, position.y as f32);
+        if let Some(previous) = self.cursor {
+          self.cursor_delta += position - previous;
+        }
+        self.cursor = Some(position);
+      }
+      WindowEvent::CursorLeft { .. } => self.cursor = None,
+      WindowEvent::MouseWheel { delta, .. } => {
+        self.scroll += match delta {
+          MouseScrollDelta::LineDelta(_, y) => *y,
+          MouseScrollDelta::PixelDelta(p) => p.y as f32 / PIXELS_PER_LINE,
+        };
+      }
+      WindowEvent::MouseInput { state, button, .. } => match state {
+        ElementState::Pressed => {
+          self.buttons.insert(*button);
+        }
+        ElementState::Released => {
+          self.buttons.remove(button);
+        }
+      },
+      WindowEvent::KeyboardInput { event, .. } => {
+        if let PhysicalKey::Code(code) = event.physical_key {
+          if event.state.is_pressed() {
+            self.keys.insert(code);
+          } else {
+            self.keys.remove(&code);
+          }
+        }
+      }
+      WindowEvent::Focused(false) => {
+        self.buttons.clear();
+        self.keys.clear();
+      }
+      _ => {}
+    }
+  }
+
+  /// Clears per-frame deltas; held buttons and keys carry over.
+  pub(crate) fn end_frame(&mut self) {
+    self.cursor_delta = Vec2::ZERO;
+    self.scroll = 0.0;
+  }
+}
@@ -0,0 +1,80 @@
+use std::collections::VecDeque;
+
+use crate::RenderStats;
+
+/// Frames kept for the frame-time graphs.
+pub const PROFILER_HISTORY: usize = 240;
+
+/// Timings and counts of one frame.
+#[derive(Debug, Clone, Default)]
+pub struct FrameSample {
+  /// Time since the previous frame started, in milliseconds.
+  pub frame_ms: f32,
+  /// CPU time in [`crate::ApplicationState`]'s update: input, systems and camera controllers.
+  pub update_ms: f32,
+  /// CPU time building, recording and submitting the frame, excluding the wait to acquire
+  /// the surface texture.
+  pub render_ms: f32,
+  /// GPU milliseconds per render graph pass of a frame a few frames back; empty when
+  /// the adapter lacks timestamp queries.
+  pub gpu_passes: Vec<(&'static str, f32)>,
+  pub stats: RenderStats,
+  /// Meshes resident on the GPU.
+  pub meshes: usize,
+  /// Bytes of mesh and polyline buffers on the GPU.
+  pub mesh_bytes: u64,
+  /// Bytes allocated by the backend's memory allocator, on backends that report it and
+  /// only while the statistics window is open.
+  pub gpu_allocated: Option<u64>,
+}
+
+impl FrameSample {
+  /// Total GPU time of the timed passes.
+  pub fn gpu_ms(&self) -> Option<f32> {
+    (!self.gpu_passes.is_empty()).then(|| self.gpu_passes.iter().map(|(_, ms)| ms).sum())
+  }
+}
+
+/// Rolling history of [`FrameSample`]s.
+#[derive(Debug, Clone)]
+pub struct Profiler {
+  history: VecDeque<FrameSample>,
+  gpu_timing: bool,
+}
+
+impl Profiler {
+  /// `gpu_timing` tells whether samples will carry GPU pass timings.
+  pub fn new(gpu_timing: bool) -> Self {
+    Self {
+      history: VecDeque::with_capacity(PROFILER_HISTORY),
+      gpu_timing,
+    }
+  }
+
+  pub fn record(&mut self, sample: FrameSample) {
+    if self.history.len() == PROFILER_HISTORY {
+      self.history.pop_front();
+    }
+    self.history.push_back(sample);
+  }
+
+  /// Whether the adapter supports the timestamp queries GPU timings need.
+  pub fn gpu_timing(&self) -> bool {
+    self.gpu_timing
+  }
+
+  pub fn latest(&self) -> Option<&FrameSample> {
+    self.history.back()
+  }
+
+  /// Samples from oldest to newest.
+  pub fn history(&self) -> impl ExactSizeIterator<Item = &FrameSample> + Clone {
+    self.history.iter()
+  }
+
+  /// Mean frame time over the history, in milliseconds.
+  pub fn average_frame_ms(&self) -> f32 {
+    let total: f32 = self.history.iter().map(|s| s.frame_ms).sum();
+    total / self.history.len().max(1) as f32
+  }
+}
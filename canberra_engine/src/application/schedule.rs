@@ -0,0 +1,248 @@
+use crate::{Input, Scene};
+
+/// Point in the frame at which a [`System`] runs; stages run in the order listed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stage {
+  PreUpdate,
+  /// Runs zero or more times per frame, every [`Schedule::fixed_timestep`] of elapsed
+  /// time, for simulation that must not depend on the frame rate.
+  FixedUpdate,
+  Update,
+  /// After the built-in camera controllers have moved the cameras.
+  PostUpdate,
+  /// After the editor UI, right before the scene is drawn.
+  PreRender,
+}
+
+impl Stage {
+  pub const ALL: [Self; 5] = [
+    Self::PreUpdate,
+    Self::FixedUpdate,
+    Self::Update,
+    Self::PostUpdate,
+    Self::PreRender,
+  ];
+}
+
+/// What a [`System`] sees besides the scene.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemContext<'a> {
+  /// This frame's input; its deltas are already cleared in [`Stage::PreRender`].
+  pub input: &'a Input,
+  /// Seconds since the previous frame, or the fixed timestep in [`Stage::FixedUpdate`].
+  pub delta: f32,
+  /// Seconds since the application started.
+  pub elapsed: f32,
+  /// Frames since the application started, from 0.
+  pub frame: u64,
+  /// Fraction of a fixed step of time not yet simulated, for blending fixed-rate state
+  /// between steps when drawing.
+  pub fixed_alpha: f32,
+}
+
+/// Per-frame logic run by the [`Schedule`]. Closures taking the scene and the context
+/// are systems too.
+pub trait System: 'static {
+  fn run(&mut self, scene: &mut Scene, ctx: &SystemContext);
+}
+
+impl<F: FnMut(&mut Scene, &SystemContext) + 'static> System for F {
+  fn run(&mut self, scene: &mut Scene, ctx: &SystemContext) {
+    self(scene, ctx)
+  }
+}
+
+/// A system and the [`Scene::change_tick`] it last ran at, so its [`crate::Added`] and
+/// [`crate::Changed`] filters see every change made since, by anyone.
+struct Scheduled {
+  system: Box<dyn System>,
+  last_run: u64,
+}
+
+impl Scheduled {
+  fn run(&mut self, scene: &mut Scene, ctx: &SystemContext) {
+    let tick = scene.change_tick();
+    let since = scene.set_change_since(self.last_run);
+    self.system.run(scene, ctx);
+    scene.set_change_since(since);
+    self.last_run = tick;
+    scene.advance_change_tick();
+  }
+}
+
+/// Systems by [`Stage`], run in the order they were added within each stage.
+pub struct Schedule {
+  stages: [Vec<Scheduled>; Stage::ALL.len()],
+  fixed_timestep: f32,
+  max_fixed_steps: u32,
+  /// Elapsed time not yet consumed by fixed steps.
+  accumulator: f32,
+}
+
+impl Schedule {
+  pub fn new() -> Self {
+    Self {
+      stages: Default::default(),
+      fixed_timestep: 1.0 / 60.0,
+      max_fixed_steps: 8,
+      accumulator: 0.0,
+    }
+  }
+
+  pub fn with_system(mut self, stage: Stage, system: impl System) -> Self {
+    self.add_system(stage, system);
+    self
+  }
+
+  /// Seconds per [`Stage::FixedUpdate`] step; 1/60 by default.
+  pub fn with_fixed_timestep(mut self, seconds: f32) -> Self {
+    self.fixed_timestep = seconds.max(f32::EPSILON);
+    self
+  }
+
+  /// Caps the fixed steps per frame so a slow frame cannot snowball into slower ones;
+  /// time beyond the cap is dropped. 8 by default.
+  pub fn with_max_fixed_steps(mut self, steps: u32) -> Self {
+    self.max_fixed_steps = steps.max(1);
+    self
+  }
+
+  pub fn add_system(&mut self, stage: Stage, system: impl System) {
+    self.stages[stage as usize].push(Scheduled {
+      system: Box::new(system),
+      last_run: 0,
+    });
+  }
+
+  pub fn fixed_timestep(&self) -> f32 {
+    self.fixed_timestep
+  }
+
+  /// Runs the systems of `stage`; [`Stage::FixedUpdate`] runs once per fixed step due.
+  pub(crate) fn run(&mut self, stage: Stage, scene: &mut Scene, ctx: &SystemContext) {
+    if stage != Stage::FixedUpdate {
+      for system in &mut self.stages[stage as usize] {
+        system.run(scene, ctx);
+      }
+      return;
+    }
+
+    self.accumulator += ctx.delta;
+    let mut steps = 0;
+    while self.accumulator >= self.fixed_timestep {
+      if steps == self.max_fixed_steps {
+        self.accumulator %= self.fixed_timestep;
+        break;
+      }
+      let fixed = SystemContext {
+        delta: self.fixed_timestep,
+        fixed_alpha: 0.0,
+        ..*ctx
+      };
+      for system in &mut self.stages[stage as usize] {
+        system.run(scene, &fixed);
+      }
+      self.accumulator -= self.fixed_timestep;
+      steps += 1;
+    }
+  }
+
+  /// Share of a fixed step accumulated since the last one ran.
+  pub(crate) fn fixed_alpha(&self) -> f32 {
+    self.accumulator / self.fixed_timestep
+  }
+}
+
+impl Default for Schedule {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use std::{cell::RefCell, rc::Rc};
+
+  use super::*;
+
+  /// Schedule with 0.25 s fixed steps recording the delta of each step it runs.
+  fn recording(max_steps: u32) -> (Schedule, Rc<RefCell<Vec<f32>>>) {
+    let steps = Rc::new(RefCell::new(Vec::new()));
+    let log = steps.clone();
+    let schedule = Schedule::new()
+      .with_fixed_timestep(0.25)
+      .with_max_fixed_steps(max_steps)
+      .with_system(
+        Stage::FixedUpdate,
+        move |_: &mut Scene, ctx: &SystemContext| log.borrow_mut().push(ctx.delta),
+      );
+    (schedule, steps)
+  }
+
+  fn frame(schedule: &mut Schedule, scene: &mut Scene, delta: f32) {
+    let input = Input::default();
+    let ctx = SystemContext {
+      input: &input,
+      delta,
+      elapsed: 0.0,
+      frame: 0,
+      fixed_alpha: 0.0,
+    };
+    schedule.run(Stage::FixedUpdate, scene, &ctx);
+  }
+
+  #[test]
+  fn short_frames_accumulate_into_a_step() {
+    let (mut schedule, steps) = recording(8);
+    let mut scene = Scene::new();
+    frame(&mut schedule, &mut scene, 0.125);
+    assert!(steps.borrow().is_empty());
+    assert_eq!(schedule.fixed_alpha(), 0.5);
+    frame(&mut schedule, &mut scene, 0.125);
+    assert_eq!(*steps.borrow(), [0.25]);
+    assert_eq!(schedule.fixed_alpha(), 0.0);
+  }
+
+  #[test]
+  fn long_frames_catch_up_with_fixed_deltas() {
+    let (mut schedule, steps) = recording(8);
+    let mut scene = Scene::new();
+    frame(&mut schedule, &mut scene, 0.625);
+    assert_eq!(*steps.borrow(), [0.25, 0.25]);
+    assert_eq!(schedule.fixed_alpha(), 0.5);
+  }
+
+  #[test]
+  fn steps_past_the_cap_are_dropped() {
+    let (mut schedule, steps) = recording(2);
+    let mut scene = Scene::new();
+    frame(&mut schedule, &mut scene, 1.125);
+    assert_eq!(steps.borrow().len(), 2);
+    // Only the partial step survives; the two skipped steps are not made up later.
+    assert_eq!(schedule.fixed_alpha(), 0.5);
+    frame(&mut schedule, &mut scene, 0.125);
+    assert_eq!(steps.borrow().len(), 3);
+  }
+
+  #[test]
+  fn other_stages_run_once_in_order() {
+    let order = Rc::new(RefCell::new(Vec::new()));
+    let mut schedule = Schedule::new();
+    for name in ["a", "b"] {
+      let order = order.clone();
+      schedule.add_system(Stage::Update, move |_: &mut Scene, _: &SystemContext| {
+        order.borrow_mut().push(name)
+      });
+    }
+    let input = Input::default();
+    let ctx = SystemContext {
+      input: &input,
+      delta: 10.0,
+      elapsed: 0.0,
+      frame: 0,
+      fixed_alpha: 0.0,
+    };
+    schedule.run(Stage::Update, &mut Scene::new(), &ctx);
+    assert_eq!(*order.borrow(), ["a", "b"]);
+  }
+}
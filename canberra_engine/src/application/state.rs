@@ -1,8 +1,11 @@
 use std::{sync::Arc, time::Instant};
 
+use glam::Vec2;
+
 use crate::{
-  Error, Result, Scene,
-  editor::{Hierarchy, Inspector},
+  Error, FrameSample, Input, Profiler, RenderSettings, Result, Scene, Schedule, Stage,
+  SystemContext,
+  editor::{Compass, CoordinateReadout, Hierarchy, Inspector, ScaleBar, Settings, Statistics},
   renderer::Renderer,
 };
 
@@ -12,8 +15,24 @@ pub struct ApplicationState {
   // scene/renderer hold GPU resources → drop before device/surface.
   // surface holds an internal Arc<Window> → drop before window.
   start_time: Instant,
+  last_update: Instant,
+  last_frame: Instant,
+  /// Frames updated so far; the one being updated or drawn is `frames - 1`.
+  frames: u64,
+  /// Seconds between the last two updates.
+  delta: f32,
+  schedule: Schedule,
+  /// CPU time of the last [`ApplicationState::update`], for the profiler.
+  update_ms: f32,
+  profiler: Profiler,
+  input: Input,
   hierarchy: Hierarchy,
   inspector: Inspector,
+  scale_bar: ScaleBar,
+  compass: Compass,
+  statistics: Statistics,
+  settings_window: Settings,
+  coordinate_readout: CoordinateReadout,
   egui_ctx: egui::Context,
   egui_state: egui_winit::State,
   egui_renderer: egui_wgpu::Renderer,
@@ -23,6 +42,9 @@ pub struct ApplicationState {
   queue: wgpu::Queue,
   pub device: wgpu::Device,
   config: wgpu::SurfaceConfiguration,
+  settings: RenderSettings,
+  sample_counts: Vec<u32>,
+  present_modes: Vec<wgpu::PresentMode>,
   is_surface_configured: bool,
   window: Arc<winit::window::Window>,
 }
@@ -30,12 +52,14 @@ pub struct ApplicationState {
 impl ApplicationState {
   pub async fn new(
     window: Arc<winit::window::Window>,
+    mut settings: RenderSettings,
+    schedule: Schedule,
     scene_builder: Box<dyn FnOnce() -> Scene>,
   ) -> Result<Self> {
     let size = window.inner_size();
 
     let instance = wgpu::Instance::new(wgpu::InstanceDescriptor {
-      backends: wgpu::Backends::PRIMARY,
+      backends: settings.backends,
       flags: Default::default(),
       memory_budget_thresholds: Default::default(),
       backend_options: Default::default(),
@@ -45,7 +69,7 @@ impl ApplicationState {
     let surface = instance.create_surface(window.clone()).unwrap();
     let adapter = instance
       .request_adapter(&wgpu::RequestAdapterOptions {
-        power_preference: wgpu::PowerPreference::HighPerformance,
+        power_preference: settings.power_preference,
         compatible_surface: Some(&surface),
         force_fallback_adapter: false,
       })
@@ -53,7 +77,11 @@ impl ApplicationState {
     let (device, queue) = adapter
       .request_device(&wgpu::DeviceDescriptor {
         label: None,
-        required_features: wgpu::Features::empty(),
+        // Lets MSAA use every sample count the adapter supports, not just 1 and 4, and
+        // the profiler time passes where timestamps are available.
+        required_features: adapter.features()
+          & (wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
+            | Renderer::GPU_TIMING_FEATURES),
         experimental_features: wgpu::ExperimentalFeatures::disabled(),
         required_limits: wgpu::Limits::default(),
         memory_hints: Default::default(),
@@ -68,12 +96,15 @@ impl ApplicationState {
       .find(|f| f.is_srgb())
       .copied()
       .unwrap_or(surface_caps.formats[0]);
+    let sample_counts = Renderer::supported_sample_counts(&adapter, &device);
+    settings.msaa_samples = settings.resolve_msaa_samples(&sample_counts);
+    settings.present_mode = settings.resolve_present_mode(&surface_caps.present_modes);
     let config = wgpu::SurfaceConfiguration {
       usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
       format: surface_format,
       width: size.width,
       height: size.height,
-      present_mode: surface_caps.present_modes[0],
+      present_mode: settings.present_mode,
       alpha_mode: surface_caps.alpha_modes[0],
       view_formats: vec![],
       desired_maximum_frame_latency: 2,
@@ -82,7 +113,8 @@ impl ApplicationState {
     let scene = scene_builder();
     let hierarchy = Hierarchy::new();
     let inspector = Inspector::new();
-    let renderer = Renderer::new(&device, surface_format, size.width, size.height);
+    let mut renderer = Renderer::new(&device, surface_format, size.width, size.height, &settings);
+    let profiler = Profiler::new(renderer.enable_gpu_timing(&device, &queue));
 
     let egui_ctx = egui::Context::default();
     let egui_state = egui_winit::State::new(
@@ -93,6 +125,7 @@ impl ApplicationState {
       None,
       None,
     );
+    // egui draws after the scene is resolved, straight onto the surface.
     let egui_renderer = egui_wgpu::Renderer::new(
       &device,
       surface_format,
@@ -104,10 +137,21 @@ impl ApplicationState {
 
     Ok(Self {
       start_time: Instant::now(),
+      last_update: Instant::now(),
+      last_frame: Instant::now(),
+      frames: 0,
+      delta: 0.0,
+      schedule,
+      update_ms: 0.0,
+      profiler,
+      input: Input::default(),
       surface,
       device,
       queue,
       config,
+      settings,
+      sample_counts,
+      present_modes: surface_caps.present_modes,
       is_surface_configured: false,
       window,
       renderer,
@@ -117,6 +161,11 @@ impl ApplicationState {
       egui_renderer,
       hierarchy,
       inspector,
+      scale_bar: ScaleBar::new(),
+      compass: Compass::new(),
+      statistics: Statistics::new(),
+      settings_window: Settings::new(),
+      coordinate_readout: CoordinateReadout::new(),
     })
   }
 
@@ -125,12 +174,13 @@ impl ApplicationState {
       self.config.width = width;
       self.config.height = height;
       self.surface.configure(&self.device, &self.config);
-      self.renderer.resize(&self.device, width, height);
+      self.renderer.resize(width, height);
       self.is_surface_configured = true;
     }
   }
 
   pub fn on_window_event(&mut self, event: &winit::event::WindowEvent) -> bool {
+    self.input.on_window_event(event);
     self
       .egui_state
       .on_window_event(&self.window, event)
@@ -143,6 +193,7 @@ impl ApplicationState {
     if !self.is_surface_configured {
       return Ok(());
     }
+    let frame_start = Instant::now();
 
     let output = match self.surface.get_current_texture() {
       wgpu::CurrentSurfaceTexture::Success(t) => t,
@@ -159,6 +210,8 @@ impl ApplicationState {
       }
       wgpu::CurrentSurfaceTexture::Lost => return Err(Error::LostDevice),
     };
+    // Acquiring the texture may block on the swapchain, which is not render time.
+    let render_start = Instant::now();
 
     let view = output
       .texture
@@ -169,28 +222,31 @@ impl ApplicationState {
         label: Some("Render Encoder"),
       });
 
-    let aspect = self.config.width as f32 / self.config.height as f32;
+    let viewport = Vec2::new(self.config.width as f32, self.config.height as f32);
     let time = self.start_time.elapsed().as_secs_f32();
-    self.renderer.render(
-      &self.device,
-      &self.scene,
-      &self.queue,
-      &view,
-      &mut encoder,
-      aspect,
-      time,
-    );
 
+    // The UI runs first so its edits show this frame; the statistics are the last frame's.
+    let mut settings = self.settings.clone();
     let raw_input = self.egui_state.take_egui_input(&self.window);
     let full_output = self.egui_ctx.run_ui(raw_input, |ctx| {
-      self.hierarchy.draw(&self.scene, ctx);
+      self.hierarchy.draw(&mut self.scene, ctx);
       self
         .inspector
         .draw(self.hierarchy.selected, &mut self.scene, ctx);
+      self.scale_bar.draw(&self.scene, viewport, ctx);
+      self.compass.draw(&mut self.scene, ctx);
+      self.statistics.draw(&self.profiler, ctx);
+      self
+        .settings_window
+        .draw(&mut settings, &self.sample_counts, &self.present_modes, ctx);
+      self
+        .coordinate_readout
+        .draw(&self.scene, &self.input, viewport, ctx);
     });
     self
       .egui_state
       .handle_platform_output(&self.window, full_output.platform_output);
+    self.run_stage(Stage::PreRender);
 
     let tris = self
       .egui_ctx
@@ -213,12 +269,23 @@ impl ApplicationState {
       &screen_desc,
     );
 
-    {
-      let mut egui_pass = encoder
+    let mut frame = self.renderer.render(
+      &self.device,
+      &self.scene,
+      &self.queue,
+      &view,
+      viewport,
+      time,
+    );
+    let output_target = frame.output();
+    let egui_renderer = &self.egui_renderer;
+    frame.add_pass("egui", &[output_target], &[output_target], |ctx| {
+      let mut egui_pass = ctx
+        .encoder
         .begin_render_pass(&wgpu::RenderPassDescriptor {
           label: Some("egui Pass"),
           color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-            view: &view,
+            view: ctx.view(output_target),
             resolve_target: None,
             depth_slice: None,
             ops: wgpu::Operations {
@@ -232,10 +299,9 @@ impl ApplicationState {
           multiview_mask: None,
         })
         .forget_lifetime();
-      self
-        .egui_renderer
-        .render(&mut egui_pass, &tris, &screen_desc);
-    }
+      egui_renderer.render(&mut egui_pass, &tris, &screen_desc);
+    });
+    frame.execute(&self.device, &mut encoder)?;
 
     for id in &full_output.textures_delta.free {
       self.egui_renderer.free_texture(id);
@@ -246,10 +312,69 @@ impl ApplicationState {
         .into_iter()
         .chain(std::iter::once(encoder.finish())),
     );
+    self.profiler.record(FrameSample {
+      frame_ms: (frame_start - self.last_frame).as_secs_f32() * 1000.0,
+      update_ms: self.update_ms,
+      render_ms: render_start.elapsed().as_secs_f32() * 1000.0,
+      gpu_passes: self.renderer.pass_timings().unwrap_or_default().to_vec(),
+      stats: self.renderer.stats(),
+      meshes: self.renderer.assets().len(),
+      mesh_bytes: self.renderer.assets().gpu_bytes(),
+      // Walking the allocator is not free, so it is only sampled while shown.
+      gpu_allocated: self
+        .statistics
+        .is_open()
+        .then(|| self.device.generate_allocator_report())
+        .flatten()
+        .map(|report| report.total_allocated_bytes),
+    });
+    self.last_frame = frame_start;
     output.present();
+
+    if settings != self.settings {
+      self.apply_settings(settings);
+    }
     Ok(())
   }
 
+  /// Systems run each frame; see [`Schedule`].
+  pub fn schedule_mut(&mut self) -> &mut Schedule {
+    &mut self.schedule
+  }
+
+  fn run_stage(&mut self, stage: Stage) {
+    let ctx = SystemContext {
+      input: &self.input,
+      delta: self.delta,
+      elapsed: self.start_time.elapsed().as_secs_f32(),
+      frame: self.frames.saturating_sub(1),
+      fixed_alpha: self.schedule.fixed_alpha(),
+    };
+    self.schedule.run(stage, &mut self.scene, &ctx);
+  }
+
+  pub fn profiler(&self) -> &Profiler {
+    &self.profiler
+  }
+
+  pub fn settings(&self) -> &RenderSettings {
+    &self.settings
+  }
+
+  /// Applies the runtime-adjustable part of `settings`; see [`RenderSettings`].
+  pub fn apply_settings(&mut self, mut settings: RenderSettings) {
+    settings.msaa_samples = settings.resolve_msaa_samples(&self.sample_counts);
+    settings.present_mode = settings.resolve_present_mode(&self.present_modes);
+    self.renderer.apply_settings(&self.device, &settings);
+    if settings.present_mode != self.config.present_mode {
+      self.config.present_mode = settings.present_mode;
+      if self.is_surface_configured {
+        self.surface.configure(&self.device, &self.config);
+      }
+    }
+    self.settings = settings;
+  }
+
   pub(crate) fn handle_key(
     &self,
     event_loop: &winit::event_loop::ActiveEventLoop,
@@ -261,5 +386,30 @@ impl ApplicationState {
     }
   }
 
-  pub(crate) fn update(&mut self) {}
+  pub fn input(&self) -> &Input {
+    &self.input
+  }
+
+  pub(crate) fn update(&mut self) {
+    self.input.pointer_captured =
+      self.egui_ctx.egui_wants_pointer_input() || self.egui_ctx.is_pointer_over_egui();
+
+    let now = Instant::now();
+    let dt = (now - self.last_update).as_secs_f32();
+    self.last_update = now;
+
+    self.delta = dt;
+    self.frames += 1;
+    self.scene.end_frame();
+
+    let viewport = Vec2::new(self.config.width as f32, self.config.height as f32);
+    self.run_stage(Stage::PreUpdate);
+    self.run_stage(Stage::FixedUpdate);
+    self.run_stage(Stage::Update);
+    self.scene.update_cameras(&self.input, dt, viewport);
+    self.run_stage(Stage::PostUpdate);
+
+    self.input.end_frame();
+    self.update_ms = now.elapsed().as_secs_f32() * 1000.0;
+  }
 }
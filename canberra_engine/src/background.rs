@@ -0,0 +1,263 @@
+use std::{
+  io::Cursor,
+  path::PathBuf,
+  sync::atomic::{AtomicU64, Ordering},
+};
+
+use glam::Vec3;
+
+use crate::{Error, Result};
+
+/// What the renderer draws behind the scene's geometry.
+#[derive(Debug, Clone, Default, serde::Serialize, serde::Deserialize)]
+pub enum Background {
+  /// Solid [`crate::RenderSettings::clear_color`].
+  #[default]
+  Clear,
+  Gradient(Gradient),
+  Skybox(Skybox),
+  Atmosphere(Atmosphere),
+}
+
+/// Vertical blend from the ground color through the horizon to the zenith, in linear RGB.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Gradient {
+  pub zenith: [f32; 3],
+  pub horizon: [f32; 3],
+  pub ground: [f32; 3],
+}
+
+impl Default for Gradient {
+  fn default() -> Self {
+    Self {
+      zenith: [0.08, 0.24, 0.6],
+      horizon: [0.62, 0.75, 0.9],
+      ground: [0.2, 0.19, 0.18],
+    }
+  }
+}
+
+/// Faces of a [`Skybox`], in the order they are stored and uploaded.
+pub const SKYBOX_FACES: [&str; 6] = ["+X", "-X", "+Y", "-Y", "+Z", "-Z"];
+
+static NEXT_SKYBOX_ID: AtomicU64 = AtomicU64::new(0);
+
+fn next_skybox_id() -> u64 {
+  NEXT_SKYBOX_ID.fetch_add(1, Ordering::Relaxed)
+}
+
+/// Cubemap of six square sRGB faces, seen from inside (+X east, +Y up, +Z south).
+///
+/// Scenes store the paths of the face images; the renderer decodes them when the skybox
+/// is first drawn.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Skybox {
+  /// PNG files of the faces in [`SKYBOX_FACES`] order.
+  faces: [PathBuf; 6],
+  /// Linear scale on the texels, so a skybox can light up the HDR image.
+  pub intensity: f32,
+  /// Identifies the faces for the renderer's GPU copy.
+  #[serde(skip, default = "next_skybox_id")]
+  id: u64,
+}
+
+impl Skybox {
+  /// Skybox of six PNG files in [`SKYBOX_FACES`] order; see [`SkyboxImage::from_png_faces`].
+  pub fn new(faces: [impl Into<PathBuf>; 6]) -> Self {
+    Self {
+      faces: faces.map(Into::into),
+      intensity: 1.0,
+      id: next_skybox_id(),
+    }
+  }
+
+  pub fn with_intensity(mut self, intensity: f32) -> Self {
+    self.intensity = intensity;
+    self
+  }
+
+  pub fn faces(&self) -> &[PathBuf; 6] {
+    &self.faces
+  }
+
+  /// Reads and decodes the faces.
+  pub fn load(&self) -> Result<SkyboxImage> {
+    let faces = self
+      .faces
+      .iter()
+      .map(std::fs::read)
+      .collect::<std::io::Result<Vec<_>>>()?;
+    SkyboxImage::from_png_faces(std::array::from_fn(|i| faces[i].as_slice()))
+  }
+
+  pub(crate) fn id(&self) -> u64 {
+    self.id
+  }
+}
+
+/// Decoded faces of a [`Skybox`].
+#[derive(Debug, Clone)]
+pub struct SkyboxImage {
+  size: u32,
+  /// RGBA8 texels of all faces in [`SKYBOX_FACES`] order, rows top to bottom.
+  texels: Vec<u8>,
+}
+
+impl SkyboxImage {
+  /// Wraps RGBA8 texels of six `size`-square faces in [`SKYBOX_FACES`] order.
+  pub fn new(size: u32, texels: Vec<u8>) -> Result<Self> {
+    let expected = 6 * 4 * (size as usize).pow(2);
+    if size == 0 || texels.len() != expected {
+      return Err(Error::InvalidSkybox(format!(
+        "expected {expected} bytes for six {size}x{size} faces, got {}",
+        texels.len()
+      )));
+    }
+    Ok(Self { size, texels })
+  }
+
+  /// Decodes six PNG faces in [`SKYBOX_FACES`] order; all must be square and equally sized.
+  pub fn from_png_faces(faces: [&[u8]; 6]) -> Result<Self> {
+    let mut size = None;
+    let mut texels = Vec::new();
+    for (bytes, name) in faces.into_iter().zip(SKYBOX_FACES) {
+      let mut decoder = png::Decoder::new(Cursor::new(bytes));
+      decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+      let mut reader = decoder.read_info()?;
+      let mut buf = vec![
+        0;
+        reader
+          .output_buffer_size()
+          .ok_or_else(|| Error::InvalidSkybox(format!("face {name} too large")))?
+      ];
+      let info = reader.next_frame(&mut buf)?;
+      if info.width != info.height || size.is_some_and(|s| s != info.width) {
+        return Err(Error::InvalidSkybox(format!(
+          "face {name} is {}x{}, faces must be square and the same size",
+          info.width, info.height
+        )));
+      }
+      size = Some(info.width);
+
+      let channels = match info.color_type {
+        png::ColorType::Grayscale => 1,
+        png::ColorType::GrayscaleAlpha => 2,
+        png::ColorType::Rgb => 3,
+        png::ColorType::Rgba => 4,
+        other => {
+          return Err(Error::InvalidSkybox(format!(
+            "face {name} has unsupported color type {other:?}"
+          )));
+        }
+      };
+      for row in buf.chunks_exact(info.line_size).take(info.height as usize) {
+        for px in row.chunks_exact(channels).take(info.width as usize) {
+          texels.extend(match channels {
+            1 | 2 => [px[0], px[0], px[0], 255],
+            _ => [px[0], px[1], px[2], 255],
+          });
+        }
+      }
+    }
+    Self::new(size.unwrap_or(0), texels)
+  }
+
+  /// Edge length of each face in texels.
+  pub fn size(&self) -> u32 {
+    self.size
+  }
+
+  pub fn texels(&self) -> &[u8] {
+    &self.texels
+  }
+}
+
+/// Single-scattering sky (Rayleigh and Mie) around a spherical planet whose surface is
+/// the scene's `y = 0` plane, in metres.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct Atmosphere {
+  /// Unit vector toward the sun, in scene space.
+  pub sun_direction: Vec3,
+  pub sun_intensity: f32,
+  pub planet_radius: f32,
+  /// Height of the top of the atmosphere above the surface.
+  pub atmosphere_height: f32,
+  /// Rayleigh scattering coefficients at sea level per metre, by RGB wavelength.
+  pub rayleigh_scattering: [f32; 3],
+  pub rayleigh_scale_height: f32,
+  /// Mie scattering coefficient at sea level per metre.
+  pub mie_scattering: f32,
+  pub mie_scale_height: f32,
+  /// Mie phase asymmetry; toward 1 concentrates the glow around the sun.
+  pub mie_anisotropy: f32,
+}
+
+impl Default for Atmosphere {
+  /// Earth's atmosphere with the sun 30° above the southern horizon.
+  fn default() -> Self {
+    Self {
+      sun_direction: Self::sun_direction(180.0, 30.0),
+      sun_intensity: 20.0,
+      planet_radius: 6_371_000.0,
+      atmosphere_height: 100_000.0,
+      rayleigh_scattering: [5.5e-6, 13.0e-6, 22.4e-6],
+      rayleigh_scale_height: 8_000.0,
+      mie_scattering: 21e-6,
+      mie_scale_height: 1_200.0,
+      mie_anisotropy: 0.758,
+    }
+  }
+}
+
+impl Atmosphere {
+  /// Scene-space direction toward a sun at compass `azimuth` (degrees clockwise from north)
+  /// and `elevation` (degrees above the horizon).
+  pub fn sun_direction(azimuth: f32, elevation: f32) -> Vec3 {
+    let (azimuth, elevation) = (azimuth.to_radians(), elevation.to_radians());
+    Vec3::new(
+      azimuth.sin() * elevation.cos(),
+      elevation.sin(),
+      -azimuth.cos() * elevation.cos(),
+    )
+  }
+
+  pub fn with_sun(mut self, azimuth: f32, elevation: f32) -> Self {
+    self.sun_direction = Self::sun_direction(azimuth, elevation);
+    self
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn skybox() -> Skybox {
+    Skybox::new(SKYBOX_FACES.map(|face| format!("sky/{face}.png")))
+  }
+
+  #[test]
+  fn scenes_store_face_paths() {
+    let json = serde_json::to_string(&Background::Skybox(skybox().with_intensity(2.0))).unwrap();
+    assert!(
+      json.contains("sky/+X.png") && json.contains("sky/-Z.png"),
+      "{json}"
+    );
+    let Background::Skybox(loaded) = serde_json::from_str(&json).unwrap() else {
+      panic!("not a skybox: {json}");
+    };
+    assert_eq!(loaded.faces(), skybox().faces());
+    assert_eq!(loaded.intensity, 2.0);
+  }
+
+  #[test]
+  fn missing_faces_fail_to_load() {
+    assert!(matches!(skybox().load(), Err(Error::Io(_))));
+  }
+
+  #[test]
+  fn images_need_six_square_faces() {
+    assert!(SkyboxImage::new(2, vec![0; 6 * 4 * 4]).is_ok());
+    assert!(SkyboxImage::new(2, vec![0; 4 * 4]).is_err());
+    assert!(SkyboxImage::new(0, Vec::new()).is_err());
+  }
+}
@@ -1,15 +1,23 @@
-use std::any::Any;
-
 use glam::Mat4;
 
-use crate::Component;
+use super::RenderLayers;
+use crate::{Component, Inspect};
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+#[derive(Debug, Clone, Component, Inspect, serde::Serialize, serde::Deserialize)]
+#[component(category = "Camera")]
 pub struct Camera {
+  #[inspect(label = "FOV", degrees, range = 1.0..=179.0, speed = 0.1, decimals = 1)]
   pub fov_y: f32,
+  /// Replaced by the viewport's when rendering.
+  #[inspect(skip)]
   pub aspect: f32,
+  #[inspect(range = 0.001..=10000.0, speed = 0.01, decimals = 3)]
   pub near: f32,
+  #[inspect(range = 0.001..=10000.0, speed = 1.0, decimals = 1)]
   pub far: f32,
+  /// Entities on none of these layers are not drawn.
+  #[serde(default)]
+  pub layers: RenderLayers,
 }
 
 impl Camera {
@@ -19,71 +27,23 @@ impl Camera {
       aspect,
       near,
       far,
+      layers: RenderLayers::DEFAULT,
     }
   }
 
-  pub fn projection_matrix(&self) -> Mat4 {
-    Mat4::perspective_rh(self.fov_y, self.aspect, self.near, self.far)
-  }
-}
-
-#[typetag::serde]
-impl Component for Camera {
-  fn name(&self) -> &'static str {
-    "Camera"
-  }
-
-  fn as_any(&self) -> &dyn Any {
+  pub fn with_layers(mut self, layers: RenderLayers) -> Self {
+    self.layers = layers;
     self
   }
 
-  fn as_any_mut(&mut self) -> &mut dyn Any {
-    self
+  pub fn projection_matrix(&self) -> Mat4 {
+    Mat4::perspective_rh(self.fov_y, self.aspect, self.near, self.far)
   }
+}
 
-  fn inspect(&mut self, ui: &mut egui::Ui) {
-    const DRAG_WIDTH: f32 = 60.0; 
-
-    egui::Grid::new("camera")
-      .num_columns(2)
-      .spacing([8.0, 4.0])
-      .show(ui, |ui| {
-        ui.label("FOV");
-        let mut fov_deg = self.fov_y.to_degrees();
-        if ui
-          .add_sized(
-            [DRAG_WIDTH, ui.available_height()],
-            egui::DragValue::new(&mut fov_deg)
-              .suffix("°")
-              .speed(0.1)
-              .max_decimals(1)
-              .range(1.0..=179.0),
-          )
-          .changed()
-        {
-          self.fov_y = fov_deg.to_radians();
-        }
-        ui.end_row();
-
-        ui.label("Near");
-        ui.add_sized(
-          [DRAG_WIDTH, ui.available_height()],
-          egui::DragValue::new(&mut self.near)
-            .speed(0.01)
-            .max_decimals(3)
-            .range(0.001..=self.far),
-        );
-        ui.end_row();
-
-        ui.label("Far");
-        ui.add_sized(
-          [DRAG_WIDTH, ui.available_height()],
-          egui::DragValue::new(&mut self.far)
-            .speed(1.0)
-            .max_decimals(1)
-            .range(self.near..=10000.0),
-        );
-        ui.end_row();
-      });
+impl Default for Camera {
+  /// 60° vertical field of view; the aspect is replaced by the viewport's when rendering.
+  fn default() -> Self {
+    Self::new(60_f32.to_radians(), 1.0, 0.1, 100.0)
   }
 }
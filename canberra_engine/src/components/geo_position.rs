@@ -0,0 +1,25 @@
+use crate::{Component, Inspect};
+
+/// Geodetic (WGS84) position of an entity: degrees of longitude/latitude and metres of altitude.
+#[derive(
+  Debug, Default, Clone, Copy, PartialEq, Component, Inspect, serde::Serialize, serde::Deserialize,
+)]
+#[component(category = "Geo")]
+pub struct GeoPosition {
+  #[inspect(range = -180.0..=180.0, speed = 0.0001, decimals = 6, suffix = "°")]
+  pub longitude: f64,
+  #[inspect(range = -90.0..=90.0, speed = 0.0001, decimals = 6, suffix = "°")]
+  pub latitude: f64,
+  #[inspect(speed = 0.1, decimals = 1, suffix = " m")]
+  pub altitude: f64,
+}
+
+impl GeoPosition {
+  pub fn new(longitude: f64, latitude: f64, altitude: f64) -> Self {
+    Self {
+      longitude,
+      latitude,
+      altitude,
+    }
+  }
+}
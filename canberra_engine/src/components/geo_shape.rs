@@ -0,0 +1,32 @@
+use crate::{Component, Inspect, InspectOptions, geo::GeoGeometry};
+
+/// Source geometry of an imported geographic feature, kept for lossless export.
+#[derive(Debug, Clone, PartialEq, Component, serde::Serialize, serde::Deserialize)]
+#[component(hidden)]
+pub struct GeoShape {
+  pub geometry: GeoGeometry,
+}
+
+impl GeoShape {
+  pub fn new(geometry: GeoGeometry) -> Self {
+    Self { geometry }
+  }
+}
+
+// Hand-written: summarises the geometry, read-only.
+impl Inspect for GeoShape {
+  fn inspect(&mut self, ui: &mut egui::Ui, _options: &InspectOptions) -> bool {
+    egui::Grid::new("geo_shape")
+      .num_columns(2)
+      .spacing([8.0, 4.0])
+      .show(ui, |ui| {
+        ui.label("Type");
+        ui.label(self.geometry.kind());
+        ui.end_row();
+        ui.label("Positions");
+        ui.label(self.geometry.positions().count().to_string());
+        ui.end_row();
+      });
+    false
+  }
+}
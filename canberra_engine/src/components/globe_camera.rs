@@ -0,0 +1,238 @@
+use glam::{DVec3, Mat4, Quat, Vec3};
+use winit::event::MouseButton;
+
+use crate::{
+  Component, Input, Inspect, InspectOptions,
+  components::GeoPosition,
+  geo::{LocalProjection, WGS84_A},
+};
+
+const METRES_PER_DEGREE: f64 = WGS84_A * std::f64::consts::PI / 180.0;
+const ROTATE_DEGREES_PER_PIXEL: f64 = 0.3;
+const ZOOM_PER_NOTCH: f64 = 0.85;
+const MAX_TILT: f64 = 85.0;
+
+/// Orbit camera around a point on the WGS84 ellipsoid.
+///
+/// Attach next to a [`Camera`](crate::components::Camera) on a root entity to switch the
+/// scene into globe mode: the frame is re-centered on `target` every update (east-north-up
+/// axes, +Y up), geo-anchored entities are placed on the ellipsoid, and the view morphs
+/// from a flat Web Mercator map near the ground to the full globe as `range` grows.
+#[derive(Debug, Clone, Component, serde::Serialize, serde::Deserialize)]
+#[component(category = "Camera")]
+pub struct GlobeCamera {
+  /// Point the camera orbits and looks at.
+  pub target: GeoPosition,
+  /// Clockwise from north, in degrees.
+  pub heading: f64,
+  /// Angle away from looking straight down, in degrees.
+  pub tilt: f64,
+  /// Distance from the target in metres.
+  pub range: f64,
+  pub min_range: f64,
+  pub max_range: f64,
+  /// Below this range the map is fully flat.
+  pub flat_range: f64,
+  /// Above this range the map is fully wrapped onto the globe.
+  pub globe_range: f64,
+}
+
+impl Default for GlobeCamera {
+  fn default() -> Self {
+    Self {
+      target: GeoPosition::default(),
+      heading: 0.0,
+      tilt: 0.0,
+      range: 2.0e7,
+      min_range: 50.0,
+      max_range: 4.0e7,
+      flat_range: 1.5e5,
+      globe_range: 1.5e6,
+    }
+  }
+}
+
+impl GlobeCamera {
+  pub fn new(target: GeoPosition, range: f64) -> Self {
+    Self {
+      target,
+      range,
+      ..Default::default()
+    }
+  }
+
+  /// Flat-to-globe blend factor: 0 is a flat map, 1 is the full ellipsoid.
+  pub fn morph(&self) -> f32 {
+    let t = ((self.range - self.flat_range) / (self.globe_range - self.flat_range)).clamp(0.0, 1.0);
+    (t * t * (3.0 - 2.0 * t)) as f32
+  }
+
+  /// Projection whose origin is the target; this is the frame the scene is rendered in.
+  pub fn frame(&self) -> LocalProjection {
+    LocalProjection::new(self.target)
+  }
+
+  /// Eye position in the target-centered frame.
+  pub fn eye(&self) -> DVec3 {
+    let (sin_t, cos_t) = self.tilt.to_radians().sin_cos();
+    self.range * (cos_t * DVec3::Y - sin_t * self.forward())
+  }
+
+  /// Eye position as a geodetic position.
+  pub fn eye_position(&self) -> GeoPosition {
+    self.frame().unproject_f64(self.eye())
+  }
+
+  /// World transform of the camera in the target-centered frame.
+  pub fn world_matrix(&self) -> Mat4 {
+    let (sin_t, cos_t) = self.tilt.to_radians().sin_cos();
+    let up = cos_t * self.forward() + sin_t * DVec3::Y;
+    Mat4::look_at_rh(self.eye().as_vec3(), Vec3::ZERO, up.as_vec3()).inverse()
+  }
+
+  pub fn rotation(&self) -> Quat {
+    Quat::from_mat4(&self.world_matrix())
+  }
+
+  /// Near and far clip distances that keep the visible horizon inside the frustum.
+  pub fn clip_range(&self) -> (f32, f32) {
+    let altitude = self.eye_position().altitude.max(1.0);
+    let horizon = (altitude * (2.0 * WGS84_A + altitude)).sqrt();
+    let near = (altitude * 0.01).clamp(0.1, 1.0e4);
+    let far = self.range + horizon + WGS84_A * 0.1;
+    (near as f32, far as f32)
+  }
+
+  /// Moves the target by a ground offset in metres along the current heading.
+  pub fn pan(&mut self, right: f64, forward: f64) {
+    let (sin_h, cos_h) = self.heading.to_radians().sin_cos();
+    let north = forward * cos_h - right * sin_h;
+    let east = forward * sin_h + right * cos_h;
+    let cos_lat = self.target.latitude.to_radians().cos().max(1e-6);
+    self.target.latitude = (self.target.latitude + north / METRES_PER_DEGREE).clamp(-89.9, 89.9);
+    self.target.longitude = (self.target.longitude + east / (METRES_PER_DEGREE * cos_lat) + 540.0)
+      .rem_euclid(360.0)
+      - 180.0;
+  }
+
+  pub fn rotate(&mut self, heading: f64, tilt: f64) {
+    self.heading = (self.heading + heading).rem_euclid(360.0);
+    self.tilt = (self.tilt + tilt).clamp(0.0, MAX_TILT);
+  }
+
+  pub fn zoom(&mut self, factor: f64) {
+    self.range = (self.range * factor).clamp(self.min_range, self.max_range);
+  }
+
+  /// Left-drag pans, right-drag changes heading and tilt, the wheel zooms.
+  pub fn handle_input(&mut self, input: &Input, fov_y: f32, viewport_height: f32) {
+    if input.pointer_captured {
+      return;
+    }
+    let delta = input.cursor_delta.as_dvec2();
+    if input.mouse_down(MouseButton::Left) {
+      let metres_per_pixel =
+        2.0 * self.range * (fov_y as f64 * 0.5).tan() / viewport_height.max(1.0) as f64;
+      self.pan(-delta.x * metres_per_pixel, delta.y * metres_per_pixel);
+    }
+    if input.mouse_down(MouseButton::Right) {
+      self.rotate(
+        delta.x * ROTATE_DEGREES_PER_PIXEL,
+        -delta.y * ROTATE_DEGREES_PER_PIXEL,
+      );
+    }
+    if input.scroll != 0.0 {
+      self.zoom(ZOOM_PER_NOTCH.powf(input.scroll as f64));
+    }
+  }
+
+  fn forward(&self) -> DVec3 {
+    let (sin_h, cos_h) = self.heading.to_radians().sin_cos();
+    DVec3::new(sin_h, 0.0, -cos_h)
+  }
+}
+
+// Hand-written: the range is bounded by `min_range`/`max_range` and drags in proportion
+// to itself, and the morph it leads to is shown alongside.
+impl Inspect for GlobeCamera {
+  fn inspect(&mut self, ui: &mut egui::Ui, _options: &InspectOptions) -> bool {
+    const DRAG_WIDTH: f32 = 90.0;
+
+    let mut changed = false;
+    egui::Grid::new("globe_camera")
+      .num_columns(2)
+      .spacing([8.0, 4.0])
+      .show(ui, |ui| {
+        ui.label("Longitude");
+        changed |= ui
+          .add_sized(
+            [DRAG_WIDTH, ui.available_height()],
+            egui::DragValue::new(&mut self.target.longitude)
+              .suffix("°")
+              .speed(0.01)
+              .max_decimals(5)
+              .range(-180.0..=180.0),
+          )
+          .changed();
+        ui.end_row();
+
+        ui.label("Latitude");
+        changed |= ui
+          .add_sized(
+            [DRAG_WIDTH, ui.available_height()],
+            egui::DragValue::new(&mut self.target.latitude)
+              .suffix("°")
+              .speed(0.01)
+              .max_decimals(5)
+              .range(-89.9..=89.9),
+          )
+          .changed();
+        ui.end_row();
+
+        ui.label("Heading");
+        changed |= ui
+          .add_sized(
+            [DRAG_WIDTH, ui.available_height()],
+            egui::DragValue::new(&mut self.heading)
+              .suffix("°")
+              .speed(0.5)
+              .max_decimals(1)
+              .range(0.0..=360.0),
+          )
+          .changed();
+        ui.end_row();
+
+        ui.label("Tilt");
+        changed |= ui
+          .add_sized(
+            [DRAG_WIDTH, ui.available_height()],
+            egui::DragValue::new(&mut self.tilt)
+              .suffix("°")
+              .speed(0.5)
+              .max_decimals(1)
+              .range(0.0..=MAX_TILT),
+          )
+          .changed();
+        ui.end_row();
+
+        ui.label("Range");
+        let speed = self.range * 0.01;
+        changed |= ui
+          .add_sized(
+            [DRAG_WIDTH, ui.available_height()],
+            egui::DragValue::new(&mut self.range)
+              .suffix(" m")
+              .speed(speed)
+              .max_decimals(0)
+              .range(self.min_range..=self.max_range),
+          )
+          .changed();
+        ui.end_row();
+
+        ui.label("Morph");
+        ui.label(format!("{:.2}", self.morph()));
+        ui.end_row();
+      });
+    changed
+  }
+}
@@ -0,0 +1,141 @@
+use crate::{Aabb, Component, Inspect, Result, components::Mesh};
+
+/// One level of an [`Lod`].
+#[derive(Debug, Clone, Inspect, serde::Serialize, serde::Deserialize)]
+pub struct LodLevel {
+  #[inspect(skip)]
+  pub mesh: Mesh,
+  /// Smallest screen coverage this level is drawn at: the bounding sphere's projected
+  /// diameter as a fraction of the viewport height.
+  #[inspect(range = 0.0..=10.0, speed = 0.001, decimals = 3)]
+  pub min_coverage: f32,
+}
+
+/// Mesh variants picked per frame by how large the entity appears on screen.
+///
+/// Takes precedence over a [`Mesh`] on the same entity. Below the last level's threshold
+/// the entity is not drawn at all.
+#[derive(Debug, Clone, Default, Component, Inspect, serde::Serialize, serde::Deserialize)]
+#[component(category = "Rendering")]
+pub struct Lod {
+  /// Ordered from most to least detailed, with decreasing `min_coverage`.
+  pub levels: Vec<LodLevel>,
+  /// Width of the dithered cross-fade below each threshold, as a fraction of it;
+  /// 0 switches levels instantly.
+  #[inspect(label = "Cross-fade", range = 0.0..=1.0, speed = 0.01, decimals = 2)]
+  pub cross_fade: f32,
+}
+
+impl Lod {
+  pub fn new(levels: Vec<LodLevel>) -> Self {
+    Self {
+      levels,
+      cross_fade: 0.0,
+    }
+  }
+
+  /// Builds levels from one mesh by simplification; each entry is the fraction of
+  /// triangles to keep and the level's `min_coverage`.
+  pub fn generate(mesh: &Mesh, levels: &[(f32, f32)]) -> Result<Self> {
+    let levels = levels
+      .iter()
+      .map(|&(ratio, min_coverage)| {
+        let mesh = if ratio >= 1.0 {
+          mesh.clone()
+        } else {
+          mesh.simplify(ratio)?
+        };
+        Ok(LodLevel { mesh, min_coverage })
+      })
+      .collect::<Result<_>>()?;
+    Ok(Self::new(levels))
+  }
+
+  pub fn with_cross_fade(mut self, cross_fade: f32) -> Self {
+    self.cross_fade = cross_fade;
+    self
+  }
+
+  /// Local-space bounds of all levels.
+  pub fn bounds(&self) -> Option<Aabb> {
+    self
+      .levels
+      .iter()
+      .filter_map(|l| l.mesh.bounds())
+      .reduce(|a, b| a.union(&b))
+  }
+
+  /// Levels to draw at `coverage` with their cross-fade weights in `0..=1`. While fading,
+  /// the more detailed level comes first and the weights add up to one.
+  pub fn select(&self, coverage: f32) -> impl Iterator<Item = (usize, f32)> {
+    let fade = self.cross_fade.clamp(0.0, 1.0);
+    // Fraction of the way up the band below `threshold`, if `coverage` is inside it.
+    let blend = |threshold: f32| {
+      let low = threshold * (1.0 - fade);
+      (fade > 0.0 && coverage > low).then(|| ((coverage - low) / (threshold - low)).min(1.0))
+    };
+
+    let selected = match self.levels.iter().position(|l| coverage >= l.min_coverage) {
+      Some(0) => [Some((0, 1.0)), None],
+      Some(i) => match blend(self.levels[i - 1].min_coverage) {
+        Some(b) => [Some((i - 1, b)), Some((i, 1.0 - b))],
+        None => [Some((i, 1.0)), None],
+      },
+      // Too small for any level: fade the coarsest one out.
+      None => {
+        let last = self.levels.len().checked_sub(1);
+        let fading = last.and_then(|l| Some((l, blend(self.levels[l].min_coverage)?)));
+        [fading, None]
+      }
+    };
+    selected.into_iter().flatten()
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn lod(cross_fade: f32) -> Lod {
+    let level = |min_coverage| LodLevel {
+      mesh: Mesh::cube(),
+      min_coverage,
+    };
+    Lod::new(vec![level(0.5), level(0.2), level(0.05)]).with_cross_fade(cross_fade)
+  }
+
+  fn select(lod: &Lod, coverage: f32) -> Vec<(usize, f32)> {
+    lod.select(coverage).collect()
+  }
+
+  #[test]
+  fn picks_the_most_detailed_level_covered() {
+    let lod = lod(0.0);
+    assert_eq!(select(&lod, 0.6), [(0, 1.0)]);
+    assert_eq!(select(&lod, 0.3), [(1, 1.0)]);
+    assert_eq!(select(&lod, 0.05), [(2, 1.0)]);
+    assert_eq!(select(&lod, 0.01), []);
+  }
+
+  #[test]
+  fn cross_fades_below_each_threshold() {
+    let lod = lod(0.5);
+    let fading = select(&lod, 0.4);
+    assert_eq!(fading.len(), 2);
+    assert_eq!((fading[0].0, fading[1].0), (0, 1));
+    assert!((fading[0].1 - 0.6).abs() < 1e-5);
+    assert!((fading[0].1 + fading[1].1 - 1.0).abs() < 1e-5);
+    // Below the fade band only the coarser level is drawn.
+    assert_eq!(select(&lod, 0.22), [(1, 1.0)]);
+  }
+
+  #[test]
+  fn coarsest_level_fades_out() {
+    let lod = lod(0.5);
+    let fading = select(&lod, 0.03);
+    assert_eq!(fading.len(), 1);
+    assert_eq!(fading[0].0, 2);
+    assert!((fading[0].1 - 0.2).abs() < 1e-5);
+    assert_eq!(select(&lod, 0.02), []);
+  }
+}
@@ -0,0 +1,299 @@
+use glam::{DVec2, DVec3, Mat4, Quat, Vec2, Vec3};
+use winit::event::MouseButton;
+
+use crate::{
+  Component, Input, Inspect, InspectOptions, Ray,
+  components::{Camera, GeoPosition, Transform},
+  geo::LocalProjection,
+};
+
+/// Ground resolution at zoom 0 on the equator for 256 px tiles, in metres per pixel.
+const EQUATOR_METRES_PER_PIXEL: f64 = 156_543.033_928_041;
+const ZOOM_PER_NOTCH: f64 = 0.5;
+const ROTATE_DEGREES_PER_PIXEL: f64 = 0.3;
+/// Inertial pan speed below which the map is considered at rest, in metres per second.
+const REST_SPEED: f64 = 0.01;
+
+/// Slippy-map style camera over the scene's flat frame ([`Scene::geo_reference`]).
+///
+/// Each update derives the entity's [`Transform`] and its [`Camera`] clip planes from the
+/// center, zoom level, pitch and bearing, so the renderer keeps consuming them unchanged.
+/// Left-drag pans (with inertia), the wheel zooms toward the cursor and right-drag
+/// rotates the bearing horizontally and the pitch vertically.
+///
+/// [`Scene::geo_reference`]: crate::Scene::geo_reference
+#[derive(Debug, Clone, Component, serde::Serialize, serde::Deserialize)]
+#[component(category = "Camera")]
+pub struct MapCamera {
+  pub center: GeoPosition,
+  /// Web map zoom level: each step halves the ground distance covered by a pixel.
+  pub zoom: f64,
+  /// Angle away from looking straight down, in degrees.
+  pub pitch: f64,
+  /// Clockwise from north, in degrees.
+  pub bearing: f64,
+  pub min_zoom: f64,
+  pub max_zoom: f64,
+  pub max_pitch: f64,
+  /// South-west and north-east corners the center is kept inside.
+  pub bounds: Option<[GeoPosition; 2]>,
+  /// Exponential decay rate of the pan velocity after release, per second; 0 disables inertia.
+  pub inertia: f64,
+  #[serde(skip)]
+  velocity: DVec2,
+}
+
+impl Default for MapCamera {
+  fn default() -> Self {
+    Self {
+      center: GeoPosition::default(),
+      zoom: 15.0,
+      pitch: 0.0,
+      bearing: 0.0,
+      min_zoom: 0.0,
+      max_zoom: 22.0,
+      max_pitch: 80.0,
+      bounds: None,
+      inertia: 4.0,
+      velocity: DVec2::ZERO,
+    }
+  }
+}
+
+impl MapCamera {
+  pub fn new(center: GeoPosition, zoom: f64) -> Self {
+    Self {
+      center,
+      zoom,
+      ..Default::default()
+    }
+  }
+
+  /// Ground distance covered by one pixel at the center, in metres.
+  pub fn metres_per_pixel(&self) -> f64 {
+    EQUATOR_METRES_PER_PIXEL * self.center.latitude.to_radians().cos() / 2f64.powf(self.zoom)
+  }
+
+  /// Eye-to-center distance that gives the zoom level's ground resolution.
+  pub fn distance(&self, fov_y: f32, viewport_height: f32) -> f64 {
+    viewport_height as f64 * 0.5 * self.metres_per_pixel() / (fov_y as f64 * 0.5).tan()
+  }
+
+  /// Camera transform in the frame of `projection`.
+  pub fn transform(
+    &self,
+    projection: &LocalProjection,
+    fov_y: f32,
+    viewport_height: f32,
+  ) -> Transform {
+    let target = projection.project_f64(self.center);
+    let (sin_b, cos_b) = self.bearing.to_radians().sin_cos();
+    let (sin_p, cos_p) = self.pitch.to_radians().sin_cos();
+    let forward = DVec3::new(sin_b, 0.0, -cos_b);
+    let eye = target + self.distance(fov_y, viewport_height) * (cos_p * DVec3::Y - sin_p * forward);
+    let up = cos_p * forward + sin_p * DVec3::Y;
+    let world = Mat4::look_at_rh(eye.as_vec3(), target.as_vec3(), up.as_vec3()).inverse();
+    Transform {
+      position: eye.as_vec3(),
+      rotation: Quat::from_mat4(&world),
+      ..Default::default()
+    }
+  }
+
+  /// Near and far clip distances for the current zoom and pitch.
+  pub fn clip_range(&self, fov_y: f32, viewport_height: f32) -> (f32, f32) {
+    let distance = self.distance(fov_y, viewport_height);
+    // Looking toward the horizon needs far more depth than looking straight down.
+    let reach = 1.0 / self.pitch.to_radians().cos().max(0.1);
+    ((distance * 0.01) as f32, (distance * 20.0 * reach) as f32)
+  }
+
+  /// Moves the center by a ground offset in metres.
+  pub fn pan(&mut self, projection: &LocalProjection, east: f64, north: f64) {
+    let local = projection.project_f64(self.center) + DVec3::new(east, 0.0, -north);
+    let moved = projection.unproject_f64(local);
+    self.center.longitude = moved.longitude;
+    self.center.latitude = moved.latitude;
+    if let Some([sw, ne]) = self.bounds {
+      self.center.longitude = self.center.longitude.clamp(sw.longitude, ne.longitude);
+      self.center.latitude = self.center.latitude.clamp(sw.latitude, ne.latitude);
+    }
+  }
+
+  pub fn rotate(&mut self, bearing: f64, pitch: f64) {
+    self.bearing = (self.bearing + bearing).rem_euclid(360.0);
+    self.pitch = (self.pitch + pitch).clamp(0.0, self.max_pitch);
+  }
+
+  pub fn set_zoom(&mut self, zoom: f64) {
+    self.zoom = zoom.clamp(self.min_zoom, self.max_zoom);
+  }
+
+  /// Applies one frame of input and inertia, returning the derived camera transform.
+  pub fn update(
+    &mut self,
+    input: &Input,
+    dt: f32,
+    camera: &Camera,
+    viewport: Vec2,
+    projection: &LocalProjection,
+  ) -> Transform {
+    let dt = dt as f64;
+    let active = !input.pointer_captured;
+    let dragging = active && input.mouse_down(MouseButton::Left);
+
+    if let (true, Some(cursor)) = (dragging, input.cursor) {
+      let previous = cursor - input.cursor_delta;
+      let hits = (
+        self.ground_hit(previous, camera, viewport, projection),
+        self.ground_hit(cursor, camera, viewport, projection),
+      );
+      if let (Some(from), Some(to)) = hits {
+        // Keep the grabbed ground point under the cursor.
+        let delta = DVec2::new(from.x - to.x, to.z - from.z);
+        self.pan(projection, delta.x, delta.y);
+        if dt > 0.0 {
+          self.velocity = self.velocity.lerp(delta / dt, 0.5);
+        }
+      }
+    } else if self.inertia > 0.0 && self.velocity.length() > REST_SPEED {
+      let step = self.velocity * dt;
+      self.pan(projection, step.x, step.y);
+      self.velocity *= (-self.inertia * dt).exp();
+    } else {
+      self.velocity = DVec2::ZERO;
+    }
+
+    if active && input.mouse_down(MouseButton::Right) {
+      let delta = input.cursor_delta.as_dvec2();
+      self.rotate(
+        delta.x * ROTATE_DEGREES_PER_PIXEL,
+        -delta.y * ROTATE_DEGREES_PER_PIXEL,
+      );
+    }
+
+    if active && input.scroll != 0.0 {
+      let anchor = input
+        .cursor
+        .and_then(|c| self.ground_hit(c, camera, viewport, projection));
+      self.set_zoom(self.zoom + input.scroll as f64 * ZOOM_PER_NOTCH);
+      let moved = input
+        .cursor
+        .and_then(|c| self.ground_hit(c, camera, viewport, projection));
+      if let (Some(before), Some(after)) = (anchor, moved) {
+        self.pan(projection, before.x - after.x, after.z - before.z);
+      }
+    }
+
+    self.transform(projection, camera.fov_y, viewport.y)
+  }
+
+  fn ground_hit(
+    &self,
+    cursor: Vec2,
+    camera: &Camera,
+    viewport: Vec2,
+    projection: &LocalProjection,
+  ) -> Option<DVec3> {
+    let mut cam = camera.clone();
+    cam.aspect = viewport.x / viewport.y.max(1.0);
+    let world = self.transform(projection, cam.fov_y, viewport.y).matrix();
+    let inv_view_proj = (cam.projection_matrix() * world.inverse()).inverse();
+    let ground = projection.project(self.center).y;
+    Ray::from_screen(inv_view_proj, cursor, viewport)
+      .intersect_ground(ground)
+      .map(Vec3::as_dvec3)
+  }
+}
+
+// Hand-written: zoom and pitch are bounded by the camera's own `min_zoom`, `max_zoom`
+// and `max_pitch`.
+impl Inspect for MapCamera {
+  fn inspect(&mut self, ui: &mut egui::Ui, _options: &InspectOptions) -> bool {
+    const DRAG_WIDTH: f32 = 90.0;
+
+    let (min_zoom, max_zoom, max_pitch) = (self.min_zoom, self.max_zoom, self.max_pitch);
+    let mut changed = false;
+    egui::Grid::new("map_camera")
+      .num_columns(2)
+      .spacing([8.0, 4.0])
+      .show(ui, |ui| {
+        ui.label("Longitude");
+        changed |= ui
+          .add_sized(
+            [DRAG_WIDTH, ui.available_height()],
+            egui::DragValue::new(&mut self.center.longitude)
+              .suffix("°")
+              .speed(0.0001)
+              .max_decimals(6)
+              .range(-180.0..=180.0),
+          )
+          .changed();
+        ui.end_row();
+
+        ui.label("Latitude");
+        changed |= ui
+          .add_sized(
+            [DRAG_WIDTH, ui.available_height()],
+            egui::DragValue::new(&mut self.center.latitude)
+              .suffix("°")
+              .speed(0.0001)
+              .max_decimals(6)
+              .range(-85.0..=85.0),
+          )
+          .changed();
+        ui.end_row();
+
+        ui.label("Zoom");
+        changed |= ui
+          .add_sized(
+            [DRAG_WIDTH, ui.available_height()],
+            egui::DragValue::new(&mut self.zoom)
+              .speed(0.05)
+              .max_decimals(2)
+              .range(min_zoom..=max_zoom),
+          )
+          .changed();
+        ui.end_row();
+
+        ui.label("Pitch");
+        changed |= ui
+          .add_sized(
+            [DRAG_WIDTH, ui.available_height()],
+            egui::DragValue::new(&mut self.pitch)
+              .suffix("°")
+              .speed(0.5)
+              .max_decimals(1)
+              .range(0.0..=max_pitch),
+          )
+          .changed();
+        ui.end_row();
+
+        ui.label("Bearing");
+        changed |= ui
+          .add_sized(
+            [DRAG_WIDTH, ui.available_height()],
+            egui::DragValue::new(&mut self.bearing)
+              .suffix("°")
+              .speed(0.5)
+              .max_decimals(1)
+              .range(0.0..=360.0),
+          )
+          .changed();
+        ui.end_row();
+
+        ui.label("Inertia");
+        changed |= ui
+          .add_sized(
+            [DRAG_WIDTH, ui.available_height()],
+            egui::DragValue::new(&mut self.inertia)
+              .speed(0.1)
+              .max_decimals(1)
+              .range(0.0..=20.0),
+          )
+          .changed();
+        ui.end_row();
+      });
+    changed
+  }
+}
@@ -0,0 +1,59 @@
+use crate::{Component, Inspect};
+
+/// Icon drawn by a [`Marker`].
+#[derive(
+  Debug, Clone, Copy, PartialEq, Eq, Default, Inspect, serde::Serialize, serde::Deserialize,
+)]
+pub enum MarkerShape {
+  #[default]
+  Circle,
+  Square,
+  Diamond,
+  Triangle,
+}
+
+/// Camera-facing icon of constant screen size at the entity's origin, or at its
+/// [`GeoPosition`](crate::components::GeoPosition) when the scene is geo-referenced.
+///
+/// All markers in a scene are drawn with a single instanced draw call. Markers with a
+/// non-zero `cluster_radius` merge with their neighbours on screen into a badge showing
+/// how many markers it stands for.
+#[derive(Debug, Clone, Component, Inspect, serde::Serialize, serde::Deserialize)]
+#[component(category = "Rendering")]
+pub struct Marker {
+  /// Diameter in pixels.
+  #[inspect(range = 1.0..=256.0, speed = 0.5, decimals = 1, suffix = " px")]
+  pub size: f32,
+  #[inspect(color)]
+  pub color: [f32; 4],
+  pub shape: MarkerShape,
+  /// Markers closer than this on screen, in pixels, are drawn as one cluster; 0 disables.
+  #[inspect(range = 0.0..=512.0, speed = 0.5, decimals = 0, suffix = " px")]
+  pub cluster_radius: f32,
+}
+
+impl Default for Marker {
+  fn default() -> Self {
+    Self {
+      size: 16.0,
+      color: [1.0, 0.3, 0.2, 1.0],
+      shape: MarkerShape::Circle,
+      cluster_radius: 0.0,
+    }
+  }
+}
+
+impl Marker {
+  pub fn new(size: f32, color: [f32; 4]) -> Self {
+    Self {
+      size,
+      color,
+      ..Default::default()
+    }
+  }
+
+  pub fn with_clustering(mut self, radius: f32) -> Self {
+    self.cluster_radius = radius;
+    self
+  }
+}
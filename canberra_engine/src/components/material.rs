@@ -1,14 +1,46 @@
-use std::any::Any;
-
 use crate::{
-  Component,
+  Component, Inspect, InspectOptions,
   renderer::{GLOBAL_SHADER_REGISTRY, ShaderHandle},
 };
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+/// How a material's output combines with what is already drawn.
+#[derive(
+  Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Inspect, serde::Serialize, serde::Deserialize,
+)]
+pub enum BlendMode {
+  /// Replaces the background and writes depth; alpha is ignored.
+  #[default]
+  Opaque,
+  /// Mixes with the background by alpha.
+  AlphaBlend,
+  /// Adds alpha-scaled color to the background, for glows and highlights.
+  Additive,
+  /// Like [`BlendMode::AlphaBlend`] for colors already multiplied by their alpha.
+  Premultiplied,
+}
+
+impl BlendMode {
+  pub const ALL: [Self; 4] = [
+    Self::Opaque,
+    Self::AlphaBlend,
+    Self::Additive,
+    Self::Premultiplied,
+  ];
+
+  /// Whether objects using this mode are drawn in the sorted pass without depth writes.
+  pub fn is_transparent(self) -> bool {
+    self != Self::Opaque
+  }
+}
+
+#[derive(Debug, Clone, Component, Inspect, serde::Serialize, serde::Deserialize)]
+#[component(category = "Rendering")]
 pub struct Material {
+  #[inspect(color)]
   pub color: [f32; 4],
   pub shader: ShaderHandle,
+  #[serde(default)]
+  pub blend: BlendMode,
 }
 
 impl Default for Material {
@@ -16,6 +48,7 @@ impl Default for Material {
     Self {
       color: [1.0, 1.0, 1.0, 1.0],
       shader: ShaderHandle::default(),
+      blend: BlendMode::Opaque,
     }
   }
 }
@@ -27,50 +60,30 @@ impl Material {
       ..Default::default()
     }
   }
-}
 
-#[typetag::serde]
-impl Component for Material {
-  fn name(&self) -> &'static str {
-    "Material"
-  }
-
-  fn as_any(&self) -> &dyn Any {
-    self
-  }
-
-  fn as_any_mut(&mut self) -> &mut dyn Any {
+  pub fn with_blend(mut self, blend: BlendMode) -> Self {
+    self.blend = blend;
     self
   }
+}
 
-  fn inspect(&mut self, ui: &mut egui::Ui) {
-    egui::Grid::new("material")
-      .num_columns(2)
-      .spacing([8.0, 4.0])
-      .show(ui, |ui| {
-        ui.label("Color");
-        ui.color_edit_button_rgba_unmultiplied(&mut self.color);
-        ui.end_row();
-
-        ui.label("Shader");
-
-        let registry = GLOBAL_SHADER_REGISTRY.load();
-        let selected_text = registry
-          .get(self.shader)
-          .map(|s| s.name.clone())
-          .unwrap_or_else(|| format!("Unknown ({})", self.shader.0));
-
-        egui::ComboBox::from_id_salt("mat_shader")
-          .selected_text(selected_text)
-          .show_ui(ui, |ui| {
-            let mut handles: Vec<_> = registry.shaders.keys().collect();
-            handles.sort_by_key(|h| h.0);
+/// Picks from the registered shaders.
+impl Inspect for ShaderHandle {
+  fn inspect(&mut self, ui: &mut egui::Ui, _options: &InspectOptions) -> bool {
+    let registry = GLOBAL_SHADER_REGISTRY.load();
+    let selected_text = registry
+      .get(*self)
+      .map(|s| s.name.clone())
+      .unwrap_or_else(|| format!("Unknown ({})", self.0));
 
-            for (handle, shader) in &registry.shaders {
-              ui.selectable_value(&mut self.shader, *handle, &shader.name);
-            }
-          });
-        ui.end_row();
+    let mut changed = false;
+    egui::ComboBox::from_id_salt(ui.next_auto_id().with("ShaderHandle"))
+      .selected_text(selected_text)
+      .show_ui(ui, |ui| {
+        for (handle, shader) in &registry.shaders {
+          changed |= ui.selectable_value(self, *handle, &shader.name).changed();
+        }
       });
+    changed
   }
 }
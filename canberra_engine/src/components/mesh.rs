@@ -1,28 +1,21 @@
-use std::any::Any;
+use std::sync::OnceLock;
 
-use crate::{Component, Vertex};
+use glam::Vec3;
 
-#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+use crate::{Aabb, Component, Inspect, InspectOptions, Result, Vertex, simplify};
+
+#[derive(Debug, Clone, Component, serde::Serialize, serde::Deserialize)]
+#[component(category = "Rendering", create = Mesh::cube)]
 pub struct Mesh {
-  pub vertices: Vec<Vertex>,
-  pub indices: Vec<u16>,
+  vertices: Vec<Vertex>,
+  indices: Vec<u16>,
+  #[serde(skip)]
+  bounds: OnceLock<Option<Aabb>>,
 }
 
-#[typetag::serde]
-impl Component for Mesh {
-  fn name(&self) -> &'static str {
-    "Mesh"
-  }
-
-  fn as_any(&self) -> &dyn Any {
-    self
-  }
-
-  fn as_any_mut(&mut self) -> &mut dyn Any {
-    self
-  }
-
-  fn inspect(&mut self, ui: &mut egui::Ui) {
+// Hand-written: shows counts derived from the private geometry, read-only.
+impl Inspect for Mesh {
+  fn inspect(&mut self, ui: &mut egui::Ui, _options: &InspectOptions) -> bool {
     egui::Grid::new("mesh")
       .num_columns(2)
       .spacing([8.0, 4.0])
@@ -37,12 +30,49 @@ impl Component for Mesh {
         ui.label((self.indices.len() / 3).to_string());
         ui.end_row();
       });
+    false
   }
 }
 
 impl Mesh {
   pub fn new(vertices: Vec<Vertex>, indices: Vec<u16>) -> Self {
-    Self { vertices, indices }
+    Self {
+      vertices,
+      indices,
+      bounds: OnceLock::new(),
+    }
+  }
+
+  pub fn vertices(&self) -> &[Vertex] {
+    &self.vertices
+  }
+
+  pub fn indices(&self) -> &[u16] {
+    &self.indices
+  }
+
+  /// Replaces the vertices, recomputing the bounds on next use.
+  pub fn set_vertices(&mut self, vertices: Vec<Vertex>) {
+    self.vertices = vertices;
+    self.bounds = OnceLock::new();
+  }
+
+  pub fn set_indices(&mut self, indices: Vec<u16>) {
+    self.indices = indices;
+  }
+
+  /// Local-space bounds of the vertices, computed on first use; `None` when empty.
+  pub fn bounds(&self) -> Option<Aabb> {
+    *self
+      .bounds
+      .get_or_init(|| Aabb::from_points(self.vertices.iter().map(|v| Vec3::from(v.position))))
+  }
+
+  /// Quadric-error simplification keeping about `ratio` of the triangles, for building
+  /// [`Lod`](crate::components::Lod) levels offline.
+  pub fn simplify(&self, ratio: f32) -> Result<Self> {
+    let target = (self.indices.len() / 3) as f32 * ratio.clamp(0.0, 1.0);
+    simplify::simplify(self, target.ceil() as usize)
   }
 
   pub fn cube() -> Self {
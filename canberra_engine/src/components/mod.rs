@@ -1,6 +1,39 @@
 mod camera;
+mod geo_position;
+mod geo_shape;
+mod globe_camera;
+mod lod;
+mod map_camera;
+mod marker;
 mod material;
 mod mesh;
+mod polyline;
+mod prefab_instance;
+mod properties;
+mod relations;
+mod render_layers;
+mod tags;
+mod terrain;
 mod transform;
+mod visibility;
 
-pub use self::{camera::Camera, material::Material, mesh::Mesh, transform::Transform};
+pub use self::{
+  camera::Camera,
+  geo_position::GeoPosition,
+  geo_shape::GeoShape,
+  globe_camera::GlobeCamera,
+  lod::{Lod, LodLevel},
+  map_camera::MapCamera,
+  marker::{Marker, MarkerShape},
+  material::{BlendMode, Material},
+  mesh::Mesh,
+  polyline::{LineCap, LineJoin, LineUnit, MAX_DASH_ENTRIES, Polyline},
+  prefab_instance::{PrefabInstance, PropertyOverride},
+  properties::Properties,
+  relations::{Children, Parent},
+  render_layers::RenderLayers,
+  tags::Tags,
+  terrain::Terrain,
+  transform::Transform,
+  visibility::Visibility,
+};
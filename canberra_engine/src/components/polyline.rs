@@ -0,0 +1,192 @@
+use glam::Vec3;
+
+use crate::{Aabb, Component, Inspect, InspectOptions};
+
+/// Entries of [`Polyline::dash`] the line shader reads.
+pub const MAX_DASH_ENTRIES: usize = 4;
+
+/// Unit of a polyline's width and dash lengths.
+#[derive(
+  Debug, Clone, Copy, PartialEq, Eq, Default, Inspect, serde::Serialize, serde::Deserialize,
+)]
+pub enum LineUnit {
+  /// Constant on screen regardless of distance.
+  #[default]
+  Pixels,
+  /// World-space metres, shrinking with distance like any geometry.
+  Metres,
+}
+
+/// Shape drawn where two segments meet.
+#[derive(
+  Debug, Clone, Copy, PartialEq, Eq, Default, Inspect, serde::Serialize, serde::Deserialize,
+)]
+pub enum LineJoin {
+  /// Sharp corner, beveled once it exceeds [`Polyline::miter_limit`].
+  #[default]
+  Miter,
+  Bevel,
+  Round,
+}
+
+/// Shape drawn at the two open ends of the line.
+#[derive(
+  Debug, Clone, Copy, PartialEq, Eq, Default, Inspect, serde::Serialize, serde::Deserialize,
+)]
+pub enum LineCap {
+  /// Ends exactly at the end point.
+  #[default]
+  Butt,
+  /// Extends half the width past the end point.
+  Square,
+  Round,
+}
+
+/// A line strip through `points` (in the entity's local space), expanded to its width
+/// on the GPU so it stays crisp at any zoom.
+#[derive(Debug, Clone, Component, serde::Serialize, serde::Deserialize)]
+#[component(category = "Rendering")]
+pub struct Polyline {
+  pub points: Vec<Vec3>,
+  pub width: f32,
+  pub unit: LineUnit,
+  pub join: LineJoin,
+  pub cap: LineCap,
+  /// Longest miter, as a multiple of half the width, before falling back to a bevel.
+  pub miter_limit: f32,
+  /// Alternating dash and gap lengths in `unit`; empty draws a solid line.
+  /// Only the first [`MAX_DASH_ENTRIES`] are used.
+  pub dash: Vec<f32>,
+  pub color: [f32; 4],
+}
+
+impl Default for Polyline {
+  fn default() -> Self {
+    Self {
+      points: Vec::new(),
+      width: 2.0,
+      unit: LineUnit::Pixels,
+      join: LineJoin::Miter,
+      cap: LineCap::Butt,
+      miter_limit: 4.0,
+      dash: Vec::new(),
+      color: [1.0, 1.0, 1.0, 1.0],
+    }
+  }
+}
+
+impl Polyline {
+  pub fn new(points: Vec<Vec3>, width: f32, unit: LineUnit) -> Self {
+    Self {
+      points,
+      width,
+      unit,
+      ..Default::default()
+    }
+  }
+
+  /// Local-space bounds of the points, widened by the line width when it is in metres.
+  pub fn bounds(&self) -> Option<Aabb> {
+    let bounds = Aabb::from_points(self.points.iter().copied())?;
+    Some(match self.unit {
+      LineUnit::Pixels => bounds,
+      LineUnit::Metres => bounds.inflate(self.width * 0.5),
+    })
+  }
+
+  /// Length of the whole line in local units.
+  pub fn length(&self) -> f32 {
+    self.points.windows(2).map(|w| w[0].distance(w[1])).sum()
+  }
+}
+
+// Hand-written: the width and dash rows follow `unit` and the number of dash entries.
+impl Inspect for Polyline {
+  fn inspect(&mut self, ui: &mut egui::Ui, _options: &InspectOptions) -> bool {
+    const DRAG_WIDTH: f32 = 60.0;
+
+    let suffix = match self.unit {
+      LineUnit::Pixels => " px",
+      LineUnit::Metres => " m",
+    };
+    let mut changed = false;
+    egui::Grid::new("polyline")
+      .num_columns(2)
+      .spacing([8.0, 4.0])
+      .show(ui, |ui| {
+        ui.label("Points");
+        ui.label(self.points.len().to_string());
+        ui.end_row();
+
+        ui.label("Color");
+        changed |= ui
+          .color_edit_button_rgba_unmultiplied(&mut self.color)
+          .changed();
+        ui.end_row();
+
+        ui.label("Width");
+        changed |= ui
+          .add_sized(
+            [DRAG_WIDTH, ui.available_height()],
+            egui::DragValue::new(&mut self.width)
+              .suffix(suffix)
+              .speed(0.1)
+              .max_decimals(1)
+              .range(0.0..=f32::MAX),
+          )
+          .changed();
+        ui.end_row();
+
+        ui.label("Unit");
+        changed |= self.unit.inspect(ui, &InspectOptions::default());
+        ui.end_row();
+
+        ui.label("Join");
+        changed |= self.join.inspect(ui, &InspectOptions::default());
+        ui.end_row();
+
+        ui.label("Cap");
+        changed |= self.cap.inspect(ui, &InspectOptions::default());
+        ui.end_row();
+
+        ui.label("Miter limit");
+        changed |= ui
+          .add_sized(
+            [DRAG_WIDTH, ui.available_height()],
+            egui::DragValue::new(&mut self.miter_limit)
+              .speed(0.1)
+              .max_decimals(1)
+              .range(1.0..=100.0),
+          )
+          .changed();
+        ui.end_row();
+
+        for (i, length) in self.dash.iter_mut().enumerate() {
+          ui.label(if i % 2 == 0 { "Dash" } else { "Gap" });
+          changed |= ui
+            .add_sized(
+              [DRAG_WIDTH, ui.available_height()],
+              egui::DragValue::new(length)
+                .suffix(suffix)
+                .speed(0.1)
+                .max_decimals(1)
+                .range(0.0..=f32::MAX),
+            )
+            .changed();
+          ui.end_row();
+        }
+      });
+
+    ui.horizontal(|ui| {
+      if self.dash.len() < MAX_DASH_ENTRIES && ui.button("Add dash").clicked() {
+        self.dash.extend([self.width * 2.0, self.width * 2.0]);
+        changed = true;
+      }
+      if !self.dash.is_empty() && ui.button("Solid").clicked() {
+        self.dash.clear();
+        changed = true;
+      }
+    });
+    changed
+  }
+}
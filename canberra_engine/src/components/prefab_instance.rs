@@ -0,0 +1,86 @@
+use uuid::Uuid;
+
+use crate::{Component, EntityRef, Inspect, InspectOptions};
+
+/// A property of one entity in a prefab instance that keeps its own value when the prefab
+/// changes.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub struct PropertyOverride {
+  /// Id of the entity in the prefab, not in the instance.
+  pub entity: Uuid,
+  /// Serialized type name of the component, or `None` for the entity's name.
+  pub component: Option<String>,
+  /// Which component of that type, for types allowing [several](Component::multiple).
+  pub index: usize,
+  /// JSON pointer into the serialized component, e.g. `/color/0`; empty for all of it.
+  pub path: String,
+}
+
+/// Marks the root of an entity subtree instantiated from a [`crate::Prefab`].
+/// [`crate::Scene::update_prefab`] brings it up to date with the prefab, keeping the
+/// properties that differ from what the instance was last given as overrides.
+#[derive(Debug, Clone, PartialEq, Component, serde::Serialize, serde::Deserialize)]
+#[component(hidden)]
+pub struct PrefabInstance {
+  pub prefab: Uuid,
+  /// Prefab entity ids and the instance entities made from them. The instance side is an
+  /// [`EntityRef`] so that copies of the instance point at their own entities.
+  entities: Vec<(Uuid, EntityRef)>,
+  pub overrides: Vec<PropertyOverride>,
+}
+
+impl PrefabInstance {
+  pub(crate) fn new(prefab: Uuid, entities: Vec<(Uuid, Uuid)>) -> Self {
+    let mut instance = Self {
+      prefab,
+      entities: Vec::new(),
+      overrides: Vec::new(),
+    };
+    instance.set_entities(entities);
+    instance
+  }
+
+  /// Prefab entity ids and the instance entities made from them.
+  pub(crate) fn entities(&self) -> impl Iterator<Item = (Uuid, Uuid)> + '_ {
+    self
+      .entities
+      .iter()
+      .filter_map(|&(prefab, instance)| Some((prefab, instance.id()?)))
+  }
+
+  pub(crate) fn set_entities(&mut self, entities: Vec<(Uuid, Uuid)>) {
+    self.entities = entities
+      .into_iter()
+      .map(|(prefab, instance)| (prefab, EntityRef::new(instance)))
+      .collect();
+  }
+
+  /// The instance entity made from the prefab entity `id`.
+  pub fn instance_of(&self, id: Uuid) -> Option<Uuid> {
+    self
+      .entities()
+      .find(|(prefab, _)| *prefab == id)
+      .map(|(_, instance)| instance)
+  }
+
+  pub fn is_overridden(&self, entity: Uuid, component: Option<&str>, index: usize) -> bool {
+    self
+      .overrides
+      .iter()
+      .any(|o| o.entity == entity && o.component.as_deref() == component && o.index == index)
+  }
+}
+
+impl Inspect for PrefabInstance {
+  fn inspect(&mut self, ui: &mut egui::Ui, _options: &InspectOptions) -> bool {
+    ui.label(format!("Prefab {}", self.prefab));
+    if self.overrides.is_empty() {
+      ui.weak("No overrides");
+    }
+    for o in &self.overrides {
+      let component = o.component.as_deref().unwrap_or("Name");
+      ui.label(format!("{component}{}", o.path));
+    }
+    false
+  }
+}
@@ -0,0 +1,70 @@
+use std::collections::BTreeMap;
+
+use serde_json::Value;
+
+use crate::{Component, Inspect, InspectOptions};
+
+/// Free-form key/value attributes, e.g. the `properties` object of a GeoJSON feature.
+#[derive(Debug, Default, Clone, PartialEq, Component, serde::Serialize, serde::Deserialize)]
+#[component(category = "Geo")]
+pub struct Properties {
+  pub values: BTreeMap<String, Value>,
+}
+
+impl Properties {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn get(&self, key: &str) -> Option<&Value> {
+    self.values.get(key)
+  }
+
+  pub fn insert(&mut self, key: impl Into<String>, value: impl Into<Value>) -> Option<Value> {
+    self.values.insert(key.into(), value.into())
+  }
+}
+
+// Hand-written: one row per key, with a widget picked by each value's JSON type.
+impl Inspect for Properties {
+  fn inspect(&mut self, ui: &mut egui::Ui, _options: &InspectOptions) -> bool {
+    let mut changed = false;
+    egui::Grid::new("properties")
+      .num_columns(2)
+      .spacing([8.0, 4.0])
+      .show(ui, |ui| {
+        for (key, value) in &mut self.values {
+          ui.label(key);
+          match value {
+            Value::String(s) => {
+              changed |= ui.text_edit_singleline(s).changed();
+            }
+            Value::Bool(b) => {
+              changed |= ui.checkbox(b, "").changed();
+            }
+            Value::Number(n) => {
+              if let Some(mut i) = n.as_i64() {
+                if ui.add(egui::DragValue::new(&mut i)).changed() {
+                  *n = i.into();
+                  changed = true;
+                }
+              } else {
+                let mut f = n.as_f64().unwrap_or_default();
+                if ui.add(egui::DragValue::new(&mut f)).changed()
+                  && let Some(number) = serde_json::Number::from_f64(f)
+                {
+                  *n = number;
+                  changed = true;
+                }
+              }
+            }
+            other => {
+              ui.label(other.to_string());
+            }
+          }
+          ui.end_row();
+        }
+      });
+    changed
+  }
+}
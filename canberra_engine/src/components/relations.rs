@@ -0,0 +1,66 @@
+use uuid::Uuid;
+
+use crate::{Component, Inspect, InspectOptions};
+
+/// The entity this one is a child of. Kept by the [`crate::Scene`] as entities are spawned
+/// and despawned; roots have none.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Component, serde::Serialize, serde::Deserialize)]
+#[component(hidden)]
+pub struct Parent {
+  entity: Uuid,
+}
+
+impl Parent {
+  pub(crate) fn new(entity: Uuid) -> Self {
+    Self { entity }
+  }
+
+  pub fn entity(&self) -> Uuid {
+    self.entity
+  }
+}
+
+impl Inspect for Parent {
+  fn inspect(&mut self, ui: &mut egui::Ui, _options: &InspectOptions) -> bool {
+    ui.label(self.entity.to_string());
+    false
+  }
+}
+
+/// The children of an entity, in order. Kept by the [`crate::Scene`] alongside [`Parent`];
+/// entities without children have none.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Component, serde::Serialize, serde::Deserialize)]
+#[component(hidden)]
+pub struct Children {
+  entities: Vec<Uuid>,
+}
+
+impl Children {
+  pub fn as_slice(&self) -> &[Uuid] {
+    &self.entities
+  }
+
+  pub fn len(&self) -> usize {
+    self.entities.len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.entities.is_empty()
+  }
+
+  /// Inserts `entity` at `index`, or last when `index` is past the end.
+  pub(crate) fn insert(&mut self, index: usize, entity: Uuid) {
+    self.entities.insert(index.min(self.entities.len()), entity);
+  }
+
+  pub(crate) fn remove(&mut self, entity: Uuid) {
+    self.entities.retain(|&e| e != entity);
+  }
+}
+
+impl Inspect for Children {
+  fn inspect(&mut self, ui: &mut egui::Ui, _options: &InspectOptions) -> bool {
+    ui.label(format!("{} children", self.entities.len()));
+    false
+  }
+}
@@ -0,0 +1,77 @@
+use crate::{Component, EntityView, Inspect, InspectOptions};
+
+/// The layers, out of 32, an entity is drawn on; entities without one are on layer 0. A
+/// [`Camera`](crate::components::Camera) draws the entities sharing a layer with its
+/// `layers`.
+#[derive(
+  Debug, Clone, Copy, PartialEq, Eq, Hash, Component, serde::Serialize, serde::Deserialize,
+)]
+#[component(category = "Rendering")]
+pub struct RenderLayers {
+  /// Bit `n` set for layer `n`.
+  pub mask: u32,
+}
+
+impl RenderLayers {
+  pub const COUNT: u32 = 32;
+  /// Layer 0 only.
+  pub const DEFAULT: Self = Self::layer(0);
+  pub const ALL: Self = Self { mask: u32::MAX };
+  pub const NONE: Self = Self { mask: 0 };
+
+  /// Layer `n` only; panics unless `n < 32`.
+  pub const fn layer(n: u32) -> Self {
+    Self { mask: 1 << n }
+  }
+
+  pub const fn with(self, n: u32) -> Self {
+    Self {
+      mask: self.mask | Self::layer(n).mask,
+    }
+  }
+
+  pub const fn without(self, n: u32) -> Self {
+    Self {
+      mask: self.mask & !Self::layer(n).mask,
+    }
+  }
+
+  pub fn contains(self, n: u32) -> bool {
+    n < Self::COUNT && self.mask & (1 << n) != 0
+  }
+
+  pub fn intersects(self, other: Self) -> bool {
+    self.mask & other.mask != 0
+  }
+
+  /// The layers of `entity`, [`RenderLayers::DEFAULT`] when it has none.
+  pub fn of(entity: EntityView<'_>) -> Self {
+    entity.get_component::<Self>().copied().unwrap_or_default()
+  }
+}
+
+impl Default for RenderLayers {
+  fn default() -> Self {
+    Self::DEFAULT
+  }
+}
+
+impl Inspect for RenderLayers {
+  fn inspect(&mut self, ui: &mut egui::Ui, _options: &InspectOptions) -> bool {
+    let mut changed = false;
+    ui.vertical(|ui| {
+      for row in 0..Self::COUNT / 8 {
+        ui.horizontal(|ui| {
+          for n in row * 8..(row + 1) * 8 {
+            let on = self.contains(n);
+            if ui.selectable_label(on, n.to_string()).clicked() {
+              *self = if on { self.without(n) } else { self.with(n) };
+              changed = true;
+            }
+          }
+        });
+      }
+    });
+    changed
+  }
+}
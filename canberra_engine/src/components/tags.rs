@@ -0,0 +1,73 @@
+use crate::{Component, Inspect, InspectOptions};
+
+/// Free-form labels, e.g. the map layer a feature belongs to. Find tagged entities with
+/// [`crate::Scene::tagged`].
+#[derive(Debug, Default, Clone, PartialEq, Eq, Component, serde::Serialize, serde::Deserialize)]
+#[component(category = "General")]
+pub struct Tags {
+  pub values: Vec<String>,
+}
+
+impl Tags {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  pub fn contains(&self, tag: &str) -> bool {
+    self.values.iter().any(|t| t == tag)
+  }
+
+  /// Adds `tag` unless present; returns whether it was added.
+  pub fn insert(&mut self, tag: impl Into<String>) -> bool {
+    let tag = tag.into();
+    let added = !self.contains(&tag);
+    if added {
+      self.values.push(tag);
+    }
+    added
+  }
+
+  /// Returns whether `tag` was present.
+  pub fn remove(&mut self, tag: &str) -> bool {
+    let len = self.values.len();
+    self.values.retain(|t| t != tag);
+    self.values.len() != len
+  }
+
+  pub fn iter(&self) -> impl Iterator<Item = &str> {
+    self.values.iter().map(String::as_str)
+  }
+}
+
+impl<S: Into<String>> FromIterator<S> for Tags {
+  fn from_iter<I: IntoIterator<Item = S>>(iter: I) -> Self {
+    let mut tags = Self::new();
+    for tag in iter {
+      tags.insert(tag);
+    }
+    tags
+  }
+}
+
+impl Inspect for Tags {
+  fn inspect(&mut self, ui: &mut egui::Ui, _options: &InspectOptions) -> bool {
+    let mut changed = false;
+    let mut remove = None;
+    for (index, tag) in self.values.iter_mut().enumerate() {
+      ui.horizontal(|ui| {
+        changed |= ui.text_edit_singleline(tag).changed();
+        if ui.small_button("✕").clicked() {
+          remove = Some(index);
+        }
+      });
+    }
+    if let Some(index) = remove {
+      self.values.remove(index);
+    }
+    let add = ui.small_button("Add Tag").clicked();
+    if add {
+      self.values.push(String::new());
+    }
+    changed || remove.is_some() || add
+  }
+}
@@ -0,0 +1,331 @@
+use std::sync::OnceLock;
+
+use glam::{Vec2, Vec3};
+
+use crate::{Aabb, Component, Inspect, InspectOptions, Vertex, components::Mesh, geo::Heightmap};
+
+/// Keeps the densest level (plus skirts) addressable with 16-bit indices.
+const MAX_RESOLUTION: u32 = 250;
+
+/// A square elevation tile centered on the entity's origin, meshed from a [`Heightmap`].
+///
+/// The grid spans `size` metres along X (east) and Z (south); elevation goes to +Y.
+/// Each coarser level of detail halves the grid resolution, and skirts hang below
+/// every border so cracks between neighbouring tiles at different levels stay hidden.
+#[derive(Debug, Clone, Component, serde::Serialize, serde::Deserialize)]
+#[component(hidden)]
+pub struct Terrain {
+  heightmap: Heightmap,
+  /// Edge length of the tile in metres.
+  size: f32,
+  vertical_scale: f32,
+  /// How far skirts extend below the tile border, in metres.
+  skirt_depth: f32,
+  /// Quads per side at the most detailed level.
+  resolution: u32,
+  /// Camera distances past which the next coarser level is used, ascending.
+  lod_distances: Vec<f32>,
+  /// Meshes per level, built from the fields above; the setters drop them.
+  #[serde(skip)]
+  lods: OnceLock<Vec<Mesh>>,
+}
+
+impl Terrain {
+  pub fn new(heightmap: Heightmap, size: f32) -> Self {
+    let resolution = (heightmap.width().max(heightmap.height()) - 1).min(MAX_RESOLUTION);
+    Self {
+      heightmap,
+      size,
+      vertical_scale: 1.0,
+      skirt_depth: size * 0.02,
+      resolution,
+      lod_distances: vec![size, size * 2.0, size * 4.0],
+      lods: OnceLock::new(),
+    }
+  }
+
+  pub fn heightmap(&self) -> &Heightmap {
+    &self.heightmap
+  }
+
+  pub fn size(&self) -> f32 {
+    self.size
+  }
+
+  pub fn vertical_scale(&self) -> f32 {
+    self.vertical_scale
+  }
+
+  pub fn skirt_depth(&self) -> f32 {
+    self.skirt_depth
+  }
+
+  pub fn resolution(&self) -> u32 {
+    self.resolution
+  }
+
+  pub fn lod_distances(&self) -> &[f32] {
+    &self.lod_distances
+  }
+
+  pub fn set_heightmap(&mut self, heightmap: Heightmap) {
+    self.heightmap = heightmap;
+    self.invalidate();
+  }
+
+  pub fn set_size(&mut self, size: f32) {
+    self.size = size;
+    self.invalidate();
+  }
+
+  pub fn set_vertical_scale(&mut self, vertical_scale: f32) {
+    self.vertical_scale = vertical_scale;
+    self.invalidate();
+  }
+
+  pub fn set_skirt_depth(&mut self, skirt_depth: f32) {
+    self.skirt_depth = skirt_depth;
+    self.invalidate();
+  }
+
+  /// Clamped to what 16-bit indices can address.
+  pub fn set_resolution(&mut self, resolution: u32) {
+    self.resolution = resolution.clamp(2, MAX_RESOLUTION);
+    self.invalidate();
+  }
+
+  /// Distances past which each coarser level is used, ascending.
+  pub fn set_lod_distances(&mut self, lod_distances: Vec<f32>) {
+    self.lod_distances = lod_distances;
+    self.invalidate();
+  }
+
+  pub fn lod_count(&self) -> usize {
+    self.lod_distances.len() + 1
+  }
+
+  /// Level of detail to use when viewed from `distance` metres away.
+  pub fn lod_for_distance(&self, distance: f32) -> usize {
+    self.lod_distances.iter().filter(|&&d| distance > d).count()
+  }
+
+  /// Distance from a point in the terrain's local space to the tile footprint.
+  pub fn distance_to(&self, local: Vec3) -> f32 {
+    let half = self.size * 0.5;
+    let (lo, hi) = self.heightmap.range();
+    let nearest = local.clamp(
+      Vec3::new(-half, lo * self.vertical_scale, -half),
+      Vec3::new(half, hi * self.vertical_scale, half),
+    );
+    local.distance(nearest)
+  }
+
+  /// Local-space bounds of the tile, skirts included.
+  pub fn bounds(&self) -> Aabb {
+    let half = self.size * 0.5;
+    let (lo, hi) = self.heightmap.range();
+    let (lo, hi) = (lo * self.vertical_scale, hi * self.vertical_scale);
+    Aabb::new(
+      Vec3::new(-half, lo.min(hi) - self.skirt_depth, -half),
+      Vec3::new(half, lo.max(hi), half),
+    )
+  }
+
+  /// Mesh for the given level of detail, generated on first use.
+  pub fn mesh(&self, lod: usize) -> &Mesh {
+    let lods = self
+      .lods
+      .get_or_init(|| (0..self.lod_count()).map(|l| self.build(l)).collect());
+    &lods[lod.min(lods.len() - 1)]
+  }
+
+  /// Drops the cached meshes, rebuilt on next use.
+  fn invalidate(&mut self) {
+    self.lods = OnceLock::new();
+  }
+
+  fn build(&self, lod: usize) -> Mesh {
+    let res = (self.resolution.min(MAX_RESOLUTION) >> lod).max(2);
+    let n = res + 1;
+    let step = self.size / res as f32;
+    let half = self.size * 0.5;
+
+    let height = |c: i64, r: i64| {
+      let u = c.clamp(0, res as i64) as f32 / res as f32;
+      let v = r.clamp(0, res as i64) as f32 / res as f32;
+      self.heightmap.sample(u, v) * self.vertical_scale
+    };
+
+    let mut vertices = Vec::with_capacity((n * n + 4 * n) as usize);
+    for r in 0..n as i64 {
+      for c in 0..n as i64 {
+        // Central differences; one-sided at the border thanks to clamping.
+        let dx = (height(c + 1, r) - height(c - 1, r)) / (2.0 * step);
+        let dz = (height(c, r + 1) - height(c, r - 1)) / (2.0 * step);
+        vertices.push(Vertex {
+          position: [
+            -half + c as f32 * step,
+            height(c, r),
+            -half + r as f32 * step,
+          ],
+          normal: Vec3::new(-dx, 1.0, -dz).normalize().into(),
+        });
+      }
+    }
+
+    let mut indices = Vec::with_capacity((res * res * 6 + 4 * res * 6) as usize);
+    let idx = |c: u32, r: u32| (r * n + c) as u16;
+    for r in 0..res {
+      for c in 0..res {
+        indices.extend([idx(c, r), idx(c, r + 1), idx(c + 1, r)]);
+        indices.extend([idx(c + 1, r), idx(c, r + 1), idx(c + 1, r + 1)]);
+      }
+    }
+
+    let borders: [(Vec<u16>, Vec2); 4] = [
+      ((0..n).map(|c| idx(c, 0)).collect(), Vec2::new(0.0, -1.0)),
+      ((0..n).map(|c| idx(c, res)).collect(), Vec2::new(0.0, 1.0)),
+      ((0..n).map(|r| idx(0, r)).collect(), Vec2::new(-1.0, 0.0)),
+      ((0..n).map(|r| idx(res, r)).collect(), Vec2::new(1.0, 0.0)),
+    ];
+    for (edge, outward) in borders {
+      let base = vertices.len() as u16;
+      for &i in &edge {
+        let top = vertices[i as usize];
+        let mut bottom = top;
+        bottom.position[1] -= self.skirt_depth;
+        vertices.push(bottom);
+      }
+      for k in 0..edge.len() - 1 {
+        let (t0, t1) = (edge[k], edge[k + 1]);
+        let (b0, b1) = (base + k as u16, base + k as u16 + 1);
+        // Orient the wall so its front face points away from the tile.
+        let along =
+          Vec3::from(vertices[t1 as usize].position) - Vec3::from(vertices[t0 as usize].position);
+        let facing = Vec3::NEG_Y.cross(along);
+        if facing.x * outward.x + facing.z * outward.y >= 0.0 {
+          indices.extend([t0, b0, t1, t1, b0, b1]);
+        } else {
+          indices.extend([t0, t1, b0, t1, b1, b0]);
+        }
+      }
+    }
+
+    Mesh::new(vertices, indices)
+  }
+}
+
+// Hand-written: one row per level of detail, and edits must drop the cached meshes.
+impl Inspect for Terrain {
+  fn inspect(&mut self, ui: &mut egui::Ui, _options: &InspectOptions) -> bool {
+    const DRAG_WIDTH: f32 = 60.0;
+
+    let mut changed = false;
+    egui::Grid::new("terrain")
+      .num_columns(2)
+      .spacing([8.0, 4.0])
+      .show(ui, |ui| {
+        ui.label("Heightmap");
+        ui.label(format!(
+          "{}x{}",
+          self.heightmap.width(),
+          self.heightmap.height()
+        ));
+        ui.end_row();
+
+        ui.label("Size");
+        changed |= ui
+          .add_sized(
+            [DRAG_WIDTH, ui.available_height()],
+            egui::DragValue::new(&mut self.size)
+              .suffix(" m")
+              .speed(1.0)
+              .range(1.0..=f32::MAX),
+          )
+          .changed();
+        ui.end_row();
+
+        ui.label("Vertical scale");
+        changed |= ui
+          .add_sized(
+            [DRAG_WIDTH, ui.available_height()],
+            egui::DragValue::new(&mut self.vertical_scale)
+              .speed(0.01)
+              .max_decimals(2),
+          )
+          .changed();
+        ui.end_row();
+
+        ui.label("Skirt depth");
+        changed |= ui
+          .add_sized(
+            [DRAG_WIDTH, ui.available_height()],
+            egui::DragValue::new(&mut self.skirt_depth)
+              .suffix(" m")
+              .speed(0.1)
+              .range(0.0..=f32::MAX),
+          )
+          .changed();
+        ui.end_row();
+
+        ui.label("Resolution");
+        changed |= ui
+          .add_sized(
+            [DRAG_WIDTH, ui.available_height()],
+            egui::DragValue::new(&mut self.resolution).range(2..=MAX_RESOLUTION),
+          )
+          .changed();
+        ui.end_row();
+
+        for (i, distance) in self.lod_distances.iter_mut().enumerate() {
+          ui.label(format!("LOD {}", i + 1));
+          changed |= ui
+            .add_sized(
+              [DRAG_WIDTH, ui.available_height()],
+              egui::DragValue::new(distance)
+                .suffix(" m")
+                .speed(1.0)
+                .range(0.0..=f32::MAX),
+            )
+            .changed();
+          ui.end_row();
+        }
+      });
+
+    if changed {
+      self.invalidate();
+    }
+    changed
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn terrain() -> Terrain {
+    let heightmap = Heightmap::new(3, 3, (0..9).map(|i| i as f32).collect()).unwrap();
+    Terrain::new(heightmap, 100.0)
+  }
+
+  #[test]
+  fn setters_rebuild_the_mesh() {
+    let mut terrain = terrain();
+    let top = |terrain: &Terrain| terrain.mesh(0).bounds().unwrap().max.y;
+    assert_eq!(top(&terrain), 8.0);
+    terrain.set_vertical_scale(2.0);
+    assert_eq!(top(&terrain), 16.0);
+    terrain.set_size(10.0);
+    assert_eq!(terrain.mesh(0).bounds().unwrap().max.x, 5.0);
+  }
+
+  #[test]
+  fn levels_follow_distance() {
+    let mut terrain = terrain();
+    terrain.set_lod_distances(vec![10.0, 20.0]);
+    assert_eq!(terrain.lod_count(), 3);
+    assert_eq!(terrain.lod_for_distance(5.0), 0);
+    assert_eq!(terrain.lod_for_distance(15.0), 1);
+    assert_eq!(terrain.lod_for_distance(50.0), 2);
+  }
+}
@@ -0,0 +1,68 @@
+use crate::{Component, EntityView, Inspect};
+
+/// Whether an entity's renderables are drawn and picked. Entities without one inherit
+/// their parent's.
+#[derive(
+  Debug,
+  Clone,
+  Copy,
+  Default,
+  PartialEq,
+  Eq,
+  Component,
+  Inspect,
+  serde::Serialize,
+  serde::Deserialize,
+)]
+#[component(category = "Rendering")]
+#[serde(tag = "visibility")]
+pub enum Visibility {
+  /// Shown when the parent is; roots are shown.
+  #[default]
+  Inherited,
+  /// Shown even when the parent is hidden.
+  Visible,
+  /// Hidden, along with the descendants that inherit.
+  Hidden,
+}
+
+impl Visibility {
+  /// The visibility of `entity`, [`Visibility::Inherited`] when it has none.
+  pub fn of(entity: EntityView<'_>) -> Self {
+    entity.get_component::<Self>().copied().unwrap_or_default()
+  }
+
+  /// Whether an entity with this visibility is shown, given whether its parent is.
+  pub fn resolve(self, parent_visible: bool) -> bool {
+    match self {
+      Self::Inherited => parent_visible,
+      Self::Visible => true,
+      Self::Hidden => false,
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn resolve_follows_the_parent_only_when_inherited() {
+    assert!(Visibility::Inherited.resolve(true));
+    assert!(!Visibility::Inherited.resolve(false));
+    assert!(Visibility::Visible.resolve(false));
+    assert!(!Visibility::Hidden.resolve(true));
+  }
+
+  #[test]
+  fn entities_without_one_inherit() {
+    let mut scene = crate::Scene::new();
+    let entity = crate::Entity::new("plain");
+    let id = entity.id();
+    scene.add(entity);
+    assert_eq!(
+      Visibility::of(scene.find(id).unwrap()),
+      Visibility::Inherited
+    );
+  }
+}
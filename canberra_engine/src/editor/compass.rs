@@ -0,0 +1,60 @@
+use crate::Scene;
+
+const RADIUS: f32 = 22.0;
+
+/// Compass rose in the top-right corner showing the camera bearing. Click it to face north.
+pub struct Compass;
+
+impl Compass {
+  pub fn new() -> Self {
+    Self
+  }
+
+  pub fn draw(&self, scene: &mut Scene, ctx: &egui::Context) {
+    let bearing = scene.camera_bearing().to_radians();
+
+    egui::Area::new(egui::Id::new("compass"))
+      .anchor(egui::Align2::RIGHT_TOP, [-10.0, 10.0])
+      .show(ctx, |ui| {
+        let (rect, response) =
+          ui.allocate_exact_size(egui::Vec2::splat(RADIUS * 2.0), egui::Sense::click());
+        let painter = ui.painter();
+        let center = rect.center();
+        let visuals = ui.visuals();
+        painter.circle_filled(center, RADIUS, visuals.extreme_bg_color);
+        painter.circle_stroke(center, RADIUS, visuals.widgets.inactive.fg_stroke);
+
+        // Screen-space north: rotate "up" against the camera's bearing.
+        let rot = egui::emath::Rot2::from_angle(-bearing);
+        let north = rot * egui::vec2(0.0, -(RADIUS - 4.0));
+        let side = rot * egui::vec2(5.0, 0.0);
+        painter.add(egui::Shape::convex_polygon(
+          vec![center + north, center + side, center - side],
+          egui::Color32::from_rgb(210, 70, 70),
+          egui::Stroke::NONE,
+        ));
+        painter.add(egui::Shape::convex_polygon(
+          vec![center - north, center - side, center + side],
+          visuals.widgets.inactive.fg_stroke.color,
+          egui::Stroke::NONE,
+        ));
+        painter.text(
+          center + north * 1.35,
+          egui::Align2::CENTER_CENTER,
+          "N",
+          egui::FontId::proportional(10.0),
+          visuals.strong_text_color(),
+        );
+
+        if response.on_hover_text("Reset north").clicked() {
+          scene.reset_north();
+        }
+      });
+  }
+}
+
+impl Default for Compass {
+  fn default() -> Self {
+    Self::new()
+  }
+}
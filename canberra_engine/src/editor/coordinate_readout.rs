@@ -0,0 +1,68 @@
+use glam::{Vec2, Vec3};
+
+use crate::{Input, Ray, Scene};
+
+/// Geographic position under the cursor, picked against scene meshes and terrain.
+pub struct CoordinateReadout {
+  /// Last cursor ray and the point it hit; the scene is only picked again once the
+  /// cursor or the camera moves.
+  last: Option<(Ray, Option<Vec3>)>,
+}
+
+impl CoordinateReadout {
+  pub fn new() -> Self {
+    Self { last: None }
+  }
+
+  pub fn draw(&mut self, scene: &Scene, input: &Input, viewport: Vec2, ctx: &egui::Context) {
+    let text = match input.cursor {
+      Some(cursor) if !input.pointer_captured => {
+        let ray = scene.screen_ray(cursor, viewport);
+        let point = match self.last {
+          Some((last, point)) if last == ray => point,
+          _ => {
+            let point = scene
+              .pick(&ray)
+              .map(|p| p.point)
+              .or_else(|| ray.intersect_ground(0.0));
+            self.last = Some((ray, point));
+            point
+          }
+        };
+        match (point, scene.geo_frame()) {
+          (Some(point), Some(frame)) => {
+            let geo = frame.unproject(point);
+            format!(
+              "{:.6}° {}, {:.6}° {}  {:.1} m",
+              geo.latitude.abs(),
+              if geo.latitude >= 0.0 { 'N' } else { 'S' },
+              geo.longitude.abs(),
+              if geo.longitude >= 0.0 { 'E' } else { 'W' },
+              geo.altitude,
+            )
+          }
+          (Some(point), None) => {
+            format!("x {:.2}  y {:.2}  z {:.2}", point.x, point.y, point.z)
+          }
+          (None, _) => "—".to_string(),
+        }
+      }
+      _ => "—".to_string(),
+    };
+
+    egui::Area::new(egui::Id::new("coordinate_readout"))
+      .anchor(egui::Align2::RIGHT_BOTTOM, [-10.0, -10.0])
+      .interactable(false)
+      .show(ctx, |ui| {
+        egui::Frame::popup(ui.style()).show(ui, |ui| {
+          ui.monospace(text);
+        });
+      });
+  }
+}
+
+impl Default for CoordinateReadout {
+  fn default() -> Self {
+    Self::new()
+  }
+}
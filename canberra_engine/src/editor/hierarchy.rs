@@ -1,47 +1,224 @@
+use egui::{Key, KeyboardShortcut, Modifiers};
 use uuid::Uuid;
 
-use crate::{Entity, Scene};
+use crate::{Entity, EntityView, Scene, components::Visibility};
+
+const DUPLICATE: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::D);
+const COPY: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::C);
+const PASTE: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::V);
+
+/// Edit picked while drawing the tree, applied once it is drawn.
+enum Command {
+  Duplicate(Uuid),
+  Copy(Uuid),
+  /// Pastes a serialized entity after a sibling, or as the last root.
+  Paste(Option<Uuid>, String),
+  SetVisibility(Uuid, Visibility),
+  /// Shows or hides the entities with a tag, e.g. a map layer.
+  SetTagVisibility(String, Visibility),
+}
 
 pub struct Hierarchy {
   pub selected: Option<Uuid>,
+  /// The last copied entity, serialized; the system clipboard gets it too.
+  clipboard: Option<String>,
 }
 
 impl Hierarchy {
   pub fn new() -> Self {
-    Self { selected: None }
+    Self {
+      selected: None,
+      clipboard: None,
+    }
   }
 
-  pub fn draw(&mut self, scene: &Scene, ctx: &egui::Context) {
+  pub fn draw(&mut self, scene: &mut Scene, ctx: &egui::Context) {
+    let mut command = self.shortcut_command(ctx);
     egui::Window::new("Hierarchy")
       .resizable(true)
       .min_size([200.0, 200.0])
       .show(ctx, |ui| {
-        for entity in &scene.entities {
-          draw_entity(entity, &mut self.selected, ui);
+        Self::draw_layers(scene, &mut command, ui);
+        for entity in scene.roots() {
+          self.draw_entity(entity, true, &mut command, ui);
         }
       });
+    if let Some(command) = command {
+      self.apply(command, scene, ctx);
+    }
   }
-}
 
-fn draw_entity(entity: &Entity, selected: &mut Option<Uuid>, ui: &mut egui::Ui) {
-  if entity.children().is_empty() {
-    let is_sel = *selected == Some(entity.id());
-    if ui.selectable_label(is_sel, &entity.name).clicked() {
-      *selected = if is_sel { None } else { Some(entity.id()) };
+  /// Ctrl+D duplicates the selection; copy and paste arrive as clipboard events. Text
+  /// fields keep them while focused.
+  fn shortcut_command(&self, ctx: &egui::Context) -> Option<Command> {
+    if ctx.egui_wants_keyboard_input() {
+      return None;
     }
-  } else {
-    let coll_id = egui::Id::new(entity.id());
-    egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), coll_id, true)
-      .show_header(ui, |ui: &mut egui::Ui| {
-        let is_sel = *selected == Some(entity.id());
-        if ui.selectable_label(is_sel, &entity.name).clicked() {
-          *selected = if is_sel { None } else { Some(entity.id()) };
-        }
+    let selected = self.selected;
+    ctx.input_mut(|i| {
+      if let Some(id) = selected
+        && i.consume_shortcut(&DUPLICATE)
+      {
+        return Some(Command::Duplicate(id));
+      }
+      i.events.iter().find_map(|event| match event {
+        egui::Event::Copy => selected.map(Command::Copy),
+        egui::Event::Paste(text) => Some(Command::Paste(selected, text.clone())),
+        _ => None,
       })
-      .body(|ui| {
-        for child in entity.children() {
-          draw_entity(child, selected, ui);
+    })
+  }
+
+  fn apply(&mut self, command: Command, scene: &mut Scene, ctx: &egui::Context) {
+    match command {
+      Command::Duplicate(id) => match scene.duplicate(id) {
+        Ok(copy) => self.selected = copy.or(self.selected),
+        Err(e) => tracing::warn!("cannot duplicate entity: {e}"),
+      },
+      Command::Copy(id) => {
+        let Some(entity) = scene.find(id) else {
+          return;
+        };
+        match serde_json::to_string(&entity) {
+          Ok(json) => {
+            ctx.copy_text(json.clone());
+            self.clipboard = Some(json);
+          }
+          Err(e) => tracing::warn!("cannot copy entity: {e}"),
+        }
+      }
+      Command::Paste(after, json) => {
+        // Anything else on the clipboard is not meant for the hierarchy.
+        let Ok(entity) = serde_json::from_str::<Entity>(&json) else {
+          return;
+        };
+        match entity.duplicate() {
+          Ok(copy) => {
+            self.selected = Some(copy.id());
+            match after {
+              Some(sibling) => scene.insert_after(sibling, copy),
+              None => scene.add(copy),
+            }
+          }
+          Err(e) => tracing::warn!("cannot paste entity: {e}"),
+        }
+      }
+      Command::SetVisibility(id, visibility) => {
+        if let Some(mut entity) = scene.find_mut(id) {
+          entity.add_component(visibility);
+        }
+      }
+      Command::SetTagVisibility(tag, visibility) => scene.set_tagged_visibility(&tag, visibility),
+    }
+  }
+
+  /// A checkbox per tag in the scene, switching the tagged entities on and off together.
+  fn draw_layers(scene: &Scene, command: &mut Option<Command>, ui: &mut egui::Ui) {
+    let tags = scene.tags();
+    if tags.is_empty() {
+      return;
+    }
+    egui::CollapsingHeader::new("Layers")
+      .default_open(true)
+      .show(ui, |ui| {
+        for tag in tags {
+          let mut shown = scene
+            .tagged(tag)
+            .any(|e| Visibility::of(e) != Visibility::Hidden);
+          if ui.checkbox(&mut shown, tag).changed() {
+            let visibility = if shown {
+              Visibility::Inherited
+            } else {
+              Visibility::Hidden
+            };
+            *command = Some(Command::SetTagVisibility(tag.to_string(), visibility));
+          }
         }
       });
+    ui.separator();
+  }
+
+  fn draw_entity(
+    &mut self,
+    entity: EntityView<'_>,
+    parent_visible: bool,
+    command: &mut Option<Command>,
+    ui: &mut egui::Ui,
+  ) {
+    let visible = Visibility::of(entity).resolve(parent_visible);
+    if entity.children().len() == 0 {
+      self.draw_label(entity, parent_visible, command, ui);
+    } else {
+      let coll_id = egui::Id::new(entity.id());
+      egui::collapsing_header::CollapsingState::load_with_default_open(ui.ctx(), coll_id, true)
+        .show_header(ui, |ui: &mut egui::Ui| {
+          self.draw_label(entity, parent_visible, command, ui);
+        })
+        .body(|ui| {
+          for child in entity.children() {
+            self.draw_entity(child, visible, command, ui);
+          }
+        });
+    }
+  }
+
+  fn draw_label(
+    &mut self,
+    entity: EntityView<'_>,
+    parent_visible: bool,
+    command: &mut Option<Command>,
+    ui: &mut egui::Ui,
+  ) {
+    let id = entity.id();
+    let own = Visibility::of(entity);
+    let visible = own.resolve(parent_visible);
+    let is_sel = self.selected == Some(id);
+    let response = ui
+      .horizontal(|ui| {
+        let eye = egui::RichText::new("👁");
+        let eye = if visible { eye } else { eye.weak() };
+        let toggle = ui
+          .add(egui::Button::new(eye).frame(false))
+          .on_hover_text(if visible { "Hide" } else { "Show" });
+        if toggle.clicked() {
+          // Showing an entity hidden by its parent overrides the parent.
+          let visibility = if visible {
+            Visibility::Hidden
+          } else if own == Visibility::Hidden && parent_visible {
+            Visibility::Inherited
+          } else {
+            Visibility::Visible
+          };
+          *command = Some(Command::SetVisibility(id, visibility));
+        }
+        ui.selectable_label(is_sel, entity.name())
+      })
+      .inner;
+    if response.clicked() {
+      self.selected = if is_sel { None } else { Some(id) };
+    }
+    response.context_menu(|ui| {
+      let ctx = ui.ctx().clone();
+      let item =
+        |name, shortcut| egui::Button::new(name).shortcut_text(ctx.format_shortcut(shortcut));
+      if ui.add(item("Duplicate", &DUPLICATE)).clicked() {
+        *command = Some(Command::Duplicate(id));
+      }
+      if ui.add(item("Copy", &COPY)).clicked() {
+        *command = Some(Command::Copy(id));
+      }
+      let paste = ui.add_enabled(self.clipboard.is_some(), item("Paste", &PASTE));
+      if paste.clicked()
+        && let Some(json) = &self.clipboard
+      {
+        *command = Some(Command::Paste(Some(id), json.clone()));
+      }
+    });
+  }
+}
+
+impl Default for Hierarchy {
+  fn default() -> Self {
+    Self::new()
   }
 }
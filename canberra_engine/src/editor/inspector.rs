@@ -1,6 +1,6 @@
 use uuid::Uuid;
 
-use crate::{Entity, Scene};
+use crate::{GLOBAL_COMPONENT_REGISTRY, Scene, hierarchy};
 
 pub struct Inspector;
 
@@ -13,38 +13,70 @@ impl Inspector {
     let Some(id) = selected else {
       return;
     };
-    let Some(entity) = find_entity_mut(&mut scene.entities, id) else {
+    hierarchy::set_choices(ctx, scene);
+    let Some(mut entity) = scene.find_mut(id) else {
       return;
     };
 
-    let name = entity.name.clone();
+    let name = entity.name().to_string();
     egui::Window::new("Inspector")
       .resizable(true)
       .min_size([260.0, 100.0])
       .show(ctx, |ui| {
         ui.heading(&name);
         ui.separator();
-        for component in entity.iter_mut() {
+        let mut remove = None;
+        // Drawing must not mark components as changed; only those whose widgets report an
+        // edit are stamped afterwards.
+        let mut changed = Vec::new();
+        for (index, component) in entity.iter_mut_untracked().enumerate() {
           let cname = component.name();
           egui::CollapsingHeader::new(cname)
+            .id_salt(index)
             .default_open(true)
             .show(ui, |ui| {
-              component.inspect(ui);
+              if component.inspect(ui) {
+                changed.push(index);
+              }
+            })
+            .header_response
+            .context_menu(|ui| {
+              if ui.button("Remove Component").clicked() {
+                remove = Some(index);
+              }
             });
         }
+        for index in changed {
+          entity.set_changed_at(index);
+        }
+        if let Some(index) = remove {
+          entity.remove_component_at(index);
+        }
+
+        ui.separator();
+        ui.menu_button("Add Component", |ui| {
+          let registry = &*GLOBAL_COMPONENT_REGISTRY;
+          for category in registry.categories() {
+            ui.menu_button(category, |ui| {
+              for info in registry.iter().filter(|info| info.category == category) {
+                let present = entity.iter().any(|c| c.as_any().type_id() == info.type_id);
+                let addable = info.multiple || !present;
+                if ui
+                  .add_enabled(addable, egui::Button::new(info.name))
+                  .clicked()
+                {
+                  entity.insert_boxed(info.create());
+                }
+              }
+            });
+          }
+        });
       });
   }
 }
 
-fn find_entity_mut<'a>(entities: &'a mut [Entity], id: Uuid) -> Option<&'a mut Entity> {
-  // Two-pass: check this level first (immutable scan for index), then recurse into children.
-  if let Some(idx) = entities.iter().position(|e| e.id() == id) {
-    return Some(&mut entities[idx]);
-  }
-  for entity in entities.iter_mut() {
-    if let Some(found) = find_entity_mut(entity.children_mut(), id) {
-      return Some(found);
-    }
+impl Default for Inspector {
+  fn default() -> Self {
+    Self::new()
   }
-  None
 }
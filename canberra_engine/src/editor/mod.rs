@@ -1,4 +1,12 @@
+mod compass;
+mod coordinate_readout;
 mod hierarchy;
 mod inspector;
+mod scale_bar;
+mod settings;
+mod statistics;
 
-pub use self::{hierarchy::Hierarchy, inspector::Inspector};
+pub use self::{
+  compass::Compass, coordinate_readout::CoordinateReadout, hierarchy::Hierarchy,
+  inspector::Inspector, scale_bar::ScaleBar, settings::Settings, statistics::Statistics,
+};
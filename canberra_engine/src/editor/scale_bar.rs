@@ -0,0 +1,91 @@
+use glam::Vec2;
+
+use crate::Scene;
+
+const MAX_WIDTH: f32 = 120.0;
+const METRES_PER_FOOT: f64 = 0.3048;
+const FEET_PER_MILE: f64 = 5280.0;
+
+/// Map scale bar in the bottom-left corner. Click it to switch metric/imperial units.
+pub struct ScaleBar {
+  pub imperial: bool,
+}
+
+impl ScaleBar {
+  pub fn new() -> Self {
+    Self { imperial: false }
+  }
+
+  pub fn draw(&mut self, scene: &Scene, viewport: Vec2, ctx: &egui::Context) {
+    let Some(metres_per_pixel) = scene.ground_resolution(viewport) else {
+      return;
+    };
+    let metres_per_point = metres_per_pixel as f64 * ctx.pixels_per_point() as f64;
+    if !metres_per_point.is_finite() || metres_per_point <= 0.0 {
+      return;
+    }
+
+    let max_metres = MAX_WIDTH as f64 * metres_per_point;
+    let (length_m, label) = if self.imperial {
+      let feet = max_metres / METRES_PER_FOOT;
+      if feet >= FEET_PER_MILE {
+        let miles = nice(feet / FEET_PER_MILE);
+        (
+          miles * FEET_PER_MILE * METRES_PER_FOOT,
+          format!("{miles} mi"),
+        )
+      } else {
+        let feet = nice(feet);
+        (feet * METRES_PER_FOOT, format!("{feet} ft"))
+      }
+    } else if max_metres >= 1000.0 {
+      let km = nice(max_metres / 1000.0);
+      (km * 1000.0, format!("{km} km"))
+    } else {
+      let m = nice(max_metres);
+      (m, format!("{m} m"))
+    };
+    let width = (length_m / metres_per_point) as f32;
+
+    egui::Area::new(egui::Id::new("scale_bar"))
+      .anchor(egui::Align2::LEFT_BOTTOM, [10.0, -10.0])
+      .show(ctx, |ui| {
+        let (rect, response) =
+          ui.allocate_exact_size(egui::vec2(MAX_WIDTH, 24.0), egui::Sense::click());
+        let painter = ui.painter();
+        let color = ui.visuals().strong_text_color();
+        let stroke = egui::Stroke::new(2.0, color);
+        let left = rect.left_bottom() + egui::vec2(0.0, -4.0);
+        let right = left + egui::vec2(width, 0.0);
+        painter.line_segment([left, right], stroke);
+        painter.line_segment([left, left - egui::vec2(0.0, 6.0)], stroke);
+        painter.line_segment([right, right - egui::vec2(0.0, 6.0)], stroke);
+        painter.text(
+          left + egui::vec2(width * 0.5, -8.0),
+          egui::Align2::CENTER_BOTTOM,
+          label,
+          egui::FontId::proportional(12.0),
+          color,
+        );
+        if response.clicked() {
+          self.imperial = !self.imperial;
+        }
+      });
+  }
+}
+
+impl Default for ScaleBar {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Largest 1, 2 or 5 times a power of ten not exceeding `value`.
+fn nice(value: f64) -> f64 {
+  let magnitude = 10f64.powf(value.log10().floor());
+  let step = [5.0, 2.0, 1.0]
+    .into_iter()
+    .find(|s| s * magnitude <= value)
+    .unwrap_or(1.0);
+  step * magnitude
+}
@@ -0,0 +1,150 @@
+use crate::{GLOBAL_SHADER_REGISTRY, PostSettings, RenderSettings, ToneMapping};
+
+/// Collapsible window for adjusting [`RenderSettings`] at runtime.
+pub struct Settings;
+
+impl Settings {
+  pub fn new() -> Self {
+    Self
+  }
+
+  /// Edits `settings` in place, offering only the sample counts and present modes the
+  /// device supports. Settings fixed at device creation are shown read-only.
+  pub fn draw(
+    &self,
+    settings: &mut RenderSettings,
+    sample_counts: &[u32],
+    present_modes: &[wgpu::PresentMode],
+    ctx: &egui::Context,
+  ) {
+    egui::Window::new("Render Settings")
+      .default_open(false)
+      .resizable(false)
+      .show(ctx, |ui| {
+        egui::Grid::new("render_settings")
+          .num_columns(2)
+          .spacing([8.0, 4.0])
+          .show(ui, |ui| {
+            ui.label("MSAA");
+            egui::ComboBox::from_id_salt("render_settings_msaa")
+              .selected_text(format!("{}x", settings.msaa_samples))
+              .show_ui(ui, |ui| {
+                for &count in sample_counts {
+                  ui.selectable_value(&mut settings.msaa_samples, count, format!("{count}x"));
+                }
+              });
+            ui.end_row();
+
+            ui.label("Clear color");
+            ui.color_edit_button_rgba_unmultiplied(&mut settings.clear_color);
+            ui.end_row();
+
+            ui.label("Present mode");
+            egui::ComboBox::from_id_salt("render_settings_present_mode")
+              .selected_text(format!("{:?}", settings.present_mode))
+              .show_ui(ui, |ui| {
+                let auto = [wgpu::PresentMode::AutoVsync, wgpu::PresentMode::AutoNoVsync];
+                for &mode in auto.iter().chain(present_modes) {
+                  ui.selectable_value(&mut settings.present_mode, mode, format!("{mode:?}"));
+                }
+              });
+            ui.end_row();
+
+            ui.label("Power preference");
+            ui.label(format!("{:?}", settings.power_preference))
+              .on_hover_text("Applied at startup");
+            ui.end_row();
+
+            ui.label("Backends");
+            ui.label(format!("{:?}", settings.backends))
+              .on_hover_text("Applied at startup");
+            ui.end_row();
+          });
+
+        ui.separator();
+        Self::draw_post(&mut settings.post, ui);
+      });
+  }
+
+  fn draw_post(post: &mut PostSettings, ui: &mut egui::Ui) {
+    const DRAG_WIDTH: f32 = 60.0;
+
+    let drag = |ui: &mut egui::Ui, value: &mut f32, max: f32| {
+      ui.add_sized(
+        [DRAG_WIDTH, ui.available_height()],
+        egui::DragValue::new(value)
+          .speed(0.01)
+          .max_decimals(2)
+          .range(0.0..=max),
+      );
+    };
+    egui::Grid::new("post_settings")
+      .num_columns(2)
+      .spacing([8.0, 4.0])
+      .show(ui, |ui| {
+        ui.label("Bloom");
+        ui.checkbox(&mut post.bloom, "");
+        ui.end_row();
+
+        if post.bloom {
+          ui.label("Threshold");
+          drag(ui, &mut post.bloom_threshold, 10.0);
+          ui.end_row();
+
+          ui.label("Knee");
+          drag(ui, &mut post.bloom_knee, 1.0);
+          ui.end_row();
+
+          ui.label("Intensity");
+          drag(ui, &mut post.bloom_intensity, 10.0);
+          ui.end_row();
+        }
+
+        ui.label("Tone mapping");
+        egui::ComboBox::from_id_salt("render_settings_tone_mapping")
+          .selected_text(format!("{:?}", post.tone_mapping))
+          .show_ui(ui, |ui| {
+            for mode in ToneMapping::ALL {
+              ui.selectable_value(&mut post.tone_mapping, mode, format!("{mode:?}"));
+            }
+          });
+        ui.end_row();
+
+        ui.label("Exposure");
+        drag(ui, &mut post.exposure, 16.0);
+        ui.end_row();
+
+        ui.label("Contrast");
+        drag(ui, &mut post.contrast, 2.0);
+        ui.end_row();
+
+        ui.label("Saturation");
+        drag(ui, &mut post.saturation, 2.0);
+        ui.end_row();
+
+        ui.label("FXAA");
+        ui.checkbox(&mut post.fxaa, "");
+        ui.end_row();
+
+        let registry = GLOBAL_SHADER_REGISTRY.load();
+        for (handle, shader) in &registry.post_passes {
+          ui.label(&shader.name);
+          let mut enabled = !post.disabled_passes.contains(handle);
+          if ui.checkbox(&mut enabled, "").changed() {
+            if enabled {
+              post.disabled_passes.remove(handle);
+            } else {
+              post.disabled_passes.insert(*handle);
+            }
+          }
+          ui.end_row();
+        }
+      });
+  }
+}
+
+impl Default for Settings {
+  fn default() -> Self {
+    Self::new()
+  }
+}
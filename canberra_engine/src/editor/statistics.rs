@@ -0,0 +1,188 @@
+use crate::{PROFILER_HISTORY, Profiler};
+
+const GRAPH_WIDTH: f32 = 240.0;
+const GRAPH_HEIGHT: f32 = 60.0;
+/// Frame budgets drawn as guides: 60 and 30 frames per second.
+const BUDGETS_MS: [f32; 2] = [1000.0 / 60.0, 1000.0 / 30.0];
+const FRAME_COLOR: egui::Color32 = egui::Color32::from_rgb(230, 190, 80);
+const GPU_COLOR: egui::Color32 = egui::Color32::from_rgb(90, 190, 120);
+
+/// Collapsible window with frame timings, GPU pass timings and the renderer's counts.
+pub struct Statistics {
+  open: bool,
+}
+
+impl Statistics {
+  pub fn new() -> Self {
+    Self { open: false }
+  }
+
+  /// Whether the window was expanded when last drawn, so costly samples can be skipped
+  /// while nobody looks at them.
+  pub fn is_open(&self) -> bool {
+    self.open
+  }
+
+  pub fn draw(&mut self, profiler: &Profiler, ctx: &egui::Context) {
+    let response = egui::Window::new("Statistics")
+      .default_open(false)
+      .resizable(false)
+      .show(ctx, |ui| {
+        let Some(sample) = profiler.latest() else {
+          return;
+        };
+        let average = profiler.average_frame_ms();
+        egui::Grid::new("statistics_timings")
+          .num_columns(2)
+          .spacing([8.0, 4.0])
+          .show(ui, |ui| {
+            ui.label("FPS");
+            ui.label(format!("{:.0}", 1000.0 / average.max(f32::EPSILON)));
+            ui.end_row();
+
+            ui.colored_label(FRAME_COLOR, "Frame");
+            ui.label(format!("{:.2} ms", sample.frame_ms));
+            ui.end_row();
+
+            ui.label("Update");
+            ui.label(format!("{:.2} ms", sample.update_ms));
+            ui.end_row();
+
+            ui.label("Render (CPU)");
+            ui.label(format!("{:.2} ms", sample.render_ms));
+            ui.end_row();
+
+            ui.colored_label(GPU_COLOR, "GPU");
+            match sample.gpu_ms() {
+              Some(ms) => ui.label(format!("{ms:.2} ms")),
+              None => ui
+                .weak("n/a")
+                .on_hover_text("The adapter lacks timestamp queries; only CPU times are shown"),
+            };
+            ui.end_row();
+          });
+
+        frame_graph(ui, profiler);
+
+        if profiler.gpu_timing() {
+          egui::CollapsingHeader::new("GPU passes").show(ui, |ui| {
+            egui::Grid::new("statistics_passes")
+              .num_columns(2)
+              .spacing([8.0, 4.0])
+              .show(ui, |ui| {
+                for (name, ms) in &sample.gpu_passes {
+                  ui.label(*name);
+                  ui.label(format!("{ms:.3} ms"));
+                  ui.end_row();
+                }
+              });
+          });
+        }
+
+        ui.separator();
+        let stats = sample.stats;
+        let total = stats.drawn + stats.culled;
+        egui::Grid::new("statistics")
+          .num_columns(2)
+          .spacing([8.0, 4.0])
+          .show(ui, |ui| {
+            ui.label("Drawn");
+            ui.label(stats.drawn.to_string());
+            ui.end_row();
+
+            ui.label("Culled");
+            ui.label(stats.culled.to_string());
+            ui.end_row();
+
+            ui.label("Culled %");
+            let percent = if total > 0 {
+              stats.culled as f32 / total as f32 * 100.0
+            } else {
+              0.0
+            };
+            ui.label(format!("{percent:.1}"));
+            ui.end_row();
+
+            ui.label("Draw calls");
+            ui.label(stats.draw_calls.to_string());
+            ui.end_row();
+
+            ui.label("Triangles");
+            ui.label(stats.triangles.to_string());
+            ui.end_row();
+
+            ui.label("GPU meshes");
+            ui.label(sample.meshes.to_string());
+            ui.end_row();
+
+            ui.label("Mesh memory");
+            ui.label(format_bytes(sample.mesh_bytes));
+            ui.end_row();
+
+            if let Some(bytes) = sample.gpu_allocated {
+              ui.label("GPU allocated");
+              ui.label(format_bytes(bytes));
+              ui.end_row();
+            }
+          });
+      });
+    self.open = response.is_some_and(|r| r.inner.is_some());
+  }
+}
+
+impl Default for Statistics {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+/// Frame and GPU times over the profiler's history, newest on the right.
+fn frame_graph(ui: &mut egui::Ui, profiler: &Profiler) {
+  let (rect, _) =
+    ui.allocate_exact_size(egui::vec2(GRAPH_WIDTH, GRAPH_HEIGHT), egui::Sense::hover());
+  let painter = ui.painter_at(rect);
+  let visuals = ui.visuals();
+  painter.rect_filled(rect, 2.0, visuals.extreme_bg_color);
+
+  let scale = profiler
+    .history()
+    .map(|s| s.frame_ms)
+    .fold(BUDGETS_MS[1], f32::max);
+  // Right-aligned, so a short history grows in from the right.
+  let start = PROFILER_HISTORY - profiler.history().len();
+  let point = |i: usize, ms: f32| {
+    egui::pos2(
+      rect.left() + (start + i) as f32 / (PROFILER_HISTORY - 1) as f32 * rect.width(),
+      rect.bottom() - ms / scale * rect.height(),
+    )
+  };
+
+  for budget in BUDGETS_MS {
+    let y = point(0, budget).y;
+    painter.hline(rect.x_range(), y, visuals.widgets.noninteractive.bg_stroke);
+  }
+  let frame: Vec<_> = profiler
+    .history()
+    .enumerate()
+    .map(|(i, s)| point(i, s.frame_ms))
+    .collect();
+  painter.add(egui::Shape::line(
+    frame,
+    egui::Stroke::new(1.5, FRAME_COLOR),
+  ));
+  let gpu: Vec<_> = profiler
+    .history()
+    .enumerate()
+    .filter_map(|(i, s)| Some(point(i, s.gpu_ms()?)))
+    .collect();
+  painter.add(egui::Shape::line(gpu, egui::Stroke::new(1.5, GPU_COLOR)));
+}
+
+fn format_bytes(bytes: u64) -> String {
+  const MIB: f64 = 1024.0 * 1024.0;
+  if bytes as f64 >= MIB {
+    format!("{:.1} MiB", bytes as f64 / MIB)
+  } else {
+    format!("{:.1} KiB", bytes as f64 / 1024.0)
+  }
+}
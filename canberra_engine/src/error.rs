@@ -1,3 +1,5 @@
+use uuid::Uuid;
+
 #[derive(Debug, thiserror::Error)]
 pub enum Error {
   #[error("Device lost")]
@@ -20,6 +22,39 @@ pub enum Error {
 
   #[error(transparent)]
   Io(#[from] std::io::Error),
+
+  #[error(transparent)]
+  GeoJson(Box<geojson::Error>),
+
+  #[error("Invalid geometry: {0}")]
+  InvalidGeometry(String),
+
+  #[error(transparent)]
+  Png(#[from] png::DecodingError),
+
+  #[error("Invalid heightmap: {0}")]
+  InvalidHeightmap(String),
+
+  #[error("Invalid skybox: {0}")]
+  InvalidSkybox(String),
+
+  #[error("Invalid render graph: {0}")]
+  InvalidRenderGraph(String),
+
+  #[error("Mesh has {0} vertices, more than 16-bit indices can address")]
+  MeshTooLarge(usize),
+
+  #[error(transparent)]
+  Json(#[from] serde_json::Error),
+
+  #[error("Unknown prefab {0}")]
+  UnknownPrefab(Uuid),
+}
+
+impl From<geojson::Error> for Error {
+  fn from(e: geojson::Error) -> Self {
+    Self::GeoJson(Box::new(e))
+  }
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
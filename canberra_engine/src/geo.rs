@@ -0,0 +1,20 @@
+mod ellipsoid;
+mod geojson;
+mod geometry;
+mod globe;
+mod heightmap;
+mod projection;
+pub(crate) mod tessellate;
+
+pub use self::{
+  ellipsoid::{WGS84_A, WGS84_B, WGS84_F, ecef_to_geodetic, geodetic_to_ecef},
+  geojson::GeoJsonOptions,
+  geometry::GeoGeometry,
+  globe::{below_horizon, place, web_mercator, web_mercator_inverse},
+  heightmap::{ElevationEncoding, Heightmap},
+  projection::LocalProjection,
+};
+pub(crate) use self::{
+  geojson::{export_geojson, import_geojson},
+  globe::{horizon_eye, update_globe},
+};
@@ -0,0 +1,91 @@
+use glam::DVec3;
+
+use crate::components::GeoPosition;
+
+/// WGS84 semi-major axis in metres.
+pub const WGS84_A: f64 = 6_378_137.0;
+/// WGS84 flattening.
+pub const WGS84_F: f64 = 1.0 / 298.257_223_563;
+/// WGS84 semi-minor axis in metres.
+pub const WGS84_B: f64 = WGS84_A * (1.0 - WGS84_F);
+
+const E2: f64 = WGS84_F * (2.0 - WGS84_F);
+
+/// Converts a geodetic position to Earth-centered, Earth-fixed coordinates (metres).
+pub fn geodetic_to_ecef(position: GeoPosition) -> DVec3 {
+  let (lat, lon) = (
+    position.latitude.to_radians(),
+    position.longitude.to_radians(),
+  );
+  let (sin_lat, cos_lat) = lat.sin_cos();
+  let (sin_lon, cos_lon) = lon.sin_cos();
+  let n = WGS84_A / (1.0 - E2 * sin_lat * sin_lat).sqrt();
+  DVec3::new(
+    (n + position.altitude) * cos_lat * cos_lon,
+    (n + position.altitude) * cos_lat * sin_lon,
+    (n * (1.0 - E2) + position.altitude) * sin_lat,
+  )
+}
+
+/// Converts Earth-centered, Earth-fixed coordinates (metres) back to a geodetic position.
+pub fn ecef_to_geodetic(ecef: DVec3) -> GeoPosition {
+  let lon = ecef.y.atan2(ecef.x);
+  let p = ecef.x.hypot(ecef.y);
+  if p < 1e-9 {
+    return GeoPosition::new(
+      lon.to_degrees(),
+      90.0_f64.copysign(ecef.z),
+      ecef.z.abs() - WGS84_B,
+    );
+  }
+
+  // Fixed-point iteration on latitude; converges to sub-millimetre in a handful of steps.
+  let mut lat = ecef.z.atan2(p * (1.0 - E2));
+  let mut alt = 0.0;
+  for _ in 0..5 {
+    let sin_lat = lat.sin();
+    let n = WGS84_A / (1.0 - E2 * sin_lat * sin_lat).sqrt();
+    alt = p / lat.cos() - n;
+    lat = ecef.z.atan2(p * (1.0 - E2 * n / (n + alt)));
+  }
+  GeoPosition::new(lon.to_degrees(), lat.to_degrees(), alt)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn equator_and_poles_lie_on_the_axes() {
+    let equator = geodetic_to_ecef(GeoPosition::new(0.0, 0.0, 0.0));
+    assert!((equator - DVec3::new(WGS84_A, 0.0, 0.0)).length() < 1e-6);
+    let north = geodetic_to_ecef(GeoPosition::new(0.0, 90.0, 0.0));
+    assert!((north - DVec3::new(0.0, 0.0, WGS84_B)).length() < 1e-6);
+  }
+
+  #[test]
+  fn ecef_round_trip() {
+    for position in [
+      GeoPosition::new(0.0, 0.0, 0.0),
+      GeoPosition::new(151.2093, -33.8688, 58.0),
+      GeoPosition::new(-74.006, 40.7128, -30.0),
+      GeoPosition::new(179.9, 89.5, 8848.0),
+      GeoPosition::new(-45.0, -60.0, 400_000.0),
+    ] {
+      let back = ecef_to_geodetic(geodetic_to_ecef(position));
+      assert!(
+        (back.longitude - position.longitude).abs() < 1e-9,
+        "{back:?}"
+      );
+      assert!((back.latitude - position.latitude).abs() < 1e-9, "{back:?}");
+      assert!((back.altitude - position.altitude).abs() < 1e-3, "{back:?}");
+    }
+  }
+
+  #[test]
+  fn poles_round_trip() {
+    let south = ecef_to_geodetic(geodetic_to_ecef(GeoPosition::new(0.0, -90.0, 100.0)));
+    assert_eq!(south.latitude, -90.0);
+    assert!((south.altitude - 100.0).abs() < 1e-6);
+  }
+}
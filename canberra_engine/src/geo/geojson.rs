@@ -0,0 +1,368 @@
+use ::geojson::{
+  Feature, FeatureCollection, GeoJson, Geometry, JsonObject, Position, Value, feature::Id,
+};
+use glam::{DVec3, Vec3};
+use uuid::Uuid;
+
+use super::{GeoGeometry, LocalProjection, tessellate::MeshBuilder};
+use crate::{
+  Entity, EntityView, Error, Result, Scene,
+  components::{GeoPosition, GeoShape, Material, Properties, Tags, Transform},
+};
+
+/// Controls how GeoJSON features are turned into meshes on import.
+#[derive(Debug, Clone)]
+pub struct GeoJsonOptions {
+  /// Name of the root entity the imported features are parented to.
+  pub name: String,
+  /// Edge length of the cube emitted for each point, in metres.
+  pub point_size: f32,
+  /// Width of the ribbon extruded along line strings, in metres.
+  pub line_width: f32,
+  pub point_color: [f32; 4],
+  pub line_color: [f32; 4],
+  pub polygon_color: [f32; 4],
+  /// Feature property naming the map layer a feature belongs to, e.g. `"layer"`. Features
+  /// are then grouped under one child of the root per layer, named and tagged after it, so
+  /// that layers such as roads or buildings can be switched with
+  /// [`Scene::set_tagged_visibility`]. Features without the property stay under the root.
+  pub layer_property: Option<String>,
+}
+
+impl Default for GeoJsonOptions {
+  fn default() -> Self {
+    Self {
+      name: "GeoJSON".to_string(),
+      point_size: 4.0,
+      line_width: 2.0,
+      point_color: [0.9, 0.3, 0.2, 1.0],
+      line_color: [0.95, 0.8, 0.2, 1.0],
+      polygon_color: [0.3, 0.6, 0.9, 1.0],
+      layer_property: None,
+    }
+  }
+}
+
+pub(crate) fn import_geojson(
+  scene: &mut Scene,
+  src: &str,
+  options: &GeoJsonOptions,
+) -> Result<Uuid> {
+  let features = match src.parse::<GeoJson>()? {
+    GeoJson::FeatureCollection(fc) => fc.features,
+    GeoJson::Feature(f) => vec![f],
+    GeoJson::Geometry(g) => vec![Feature::from(g)],
+  };
+  Ok(import_features(scene, features, options))
+}
+
+/// Adds `features` under a new root entity and returns its id. Features that cannot be
+/// converted or meshed are skipped with a warning.
+fn import_features(scene: &mut Scene, features: Vec<Feature>, options: &GeoJsonOptions) -> Uuid {
+  let mut parsed = Vec::with_capacity(features.len());
+  for (i, feature) in features.into_iter().enumerate() {
+    let Some(geometry) = &feature.geometry else {
+      tracing::warn!("skipping GeoJSON feature {i} without geometry");
+      continue;
+    };
+    let geometry = match from_geojson(&geometry.value) {
+      Ok(geometry) => geometry,
+      Err(e) => {
+        tracing::warn!("skipping GeoJSON feature {i}: {e}");
+        continue;
+      }
+    };
+    let name = match (&feature.id, feature.property("name")) {
+      (Some(Id::String(s)), _) => s.clone(),
+      (Some(Id::Number(n)), _) => n.to_string(),
+      (None, Some(serde_json::Value::String(s))) => s.clone(),
+      _ => format!("Feature {i}"),
+    };
+    let properties = feature.properties.unwrap_or_default();
+    parsed.push((name, geometry, properties));
+  }
+
+  let projection = *scene
+    .geo_reference
+    .get_or_insert_with(|| LocalProjection::new(center_of(parsed.iter().map(|(_, g, _)| g))));
+
+  let mut root = Entity::new(&options.name);
+  root.add_component(Transform::default());
+  let mut layers: Vec<Entity> = Vec::new();
+  for (name, geometry, properties) in parsed {
+    let layer = options
+      .layer_property
+      .as_ref()
+      .and_then(|key| properties.get(key))
+      .map(|value| match value {
+        serde_json::Value::String(s) => s.clone(),
+        other => other.to_string(),
+      });
+    let mut entity = Entity::new(&name);
+    if let Some(anchor) = geometry.anchor() {
+      let origin = projection.project_f64(anchor);
+      let mut builder = MeshBuilder::new();
+      if let Err(e) = build_mesh(&mut builder, &geometry, &projection, origin, options) {
+        tracing::warn!("skipping GeoJSON feature {name}: {e}");
+        continue;
+      }
+
+      entity.add_component(Transform::from_translation(origin.as_vec3()));
+      entity.add_component(anchor);
+      if !builder.is_empty() {
+        let color = color_of(&geometry, options);
+        let mut meshes = builder.build();
+        if meshes.len() == 1 {
+          entity.add_component(meshes.remove(0));
+          entity.add_component(Material::with_color(color));
+        } else {
+          // Too many vertices for 16-bit indices: draw each part from a child entity.
+          for (i, mesh) in meshes.into_iter().enumerate() {
+            let mut part = Entity::new(&format!("{name} (part {})", i + 1));
+            part.add_component(Transform::default());
+            part.add_component(mesh);
+            part.add_component(Material::with_color(color));
+            entity.add_child(part);
+          }
+        }
+      }
+    }
+    entity.add_component(GeoShape::new(geometry));
+    entity.add_component(Properties::from(properties));
+    let Some(layer) = layer else {
+      root.add_child(entity);
+      continue;
+    };
+    let index = match layers.iter().position(|g| g.name == layer) {
+      Some(index) => index,
+      None => {
+        let mut group = Entity::new(&layer);
+        group.add_component(Tags::from_iter([layer]));
+        layers.push(group);
+        layers.len() - 1
+      }
+    };
+    layers[index].add_child(entity);
+  }
+  for layer in layers {
+    root.add_child(layer);
+  }
+
+  let id = root.id();
+  scene.add(root);
+  id
+}
+
+pub(crate) fn export_geojson(scene: &Scene) -> String {
+  let features = scene.iter().filter_map(feature_of).collect();
+  GeoJson::from(FeatureCollection {
+    bbox: None,
+    features,
+    foreign_members: None,
+  })
+  .to_string()
+}
+
+fn feature_of(entity: EntityView<'_>) -> Option<Feature> {
+  let value = match (
+    entity.get_component::<GeoShape>(),
+    entity.get_component::<GeoPosition>(),
+  ) {
+    (Some(shape), _) => to_geojson(&shape.geometry),
+    (None, Some(position)) => Value::Point(to_position(*position)),
+    (None, None) => return None,
+  };
+  Some(Feature {
+    bbox: None,
+    geometry: Some(Geometry::new(value)),
+    id: Some(Id::String(entity.name().to_string())),
+    properties: Some(
+      entity
+        .get_component::<Properties>()
+        .map(|p| p.values.clone().into_iter().collect())
+        .unwrap_or_default(),
+    ),
+    foreign_members: None,
+  })
+}
+
+fn build_mesh(
+  builder: &mut MeshBuilder,
+  geometry: &GeoGeometry,
+  projection: &LocalProjection,
+  origin: DVec3,
+  options: &GeoJsonOptions,
+) -> Result<()> {
+  let local = |p: &GeoPosition| (projection.project_f64(*p) - origin).as_vec3();
+  let ring = |ps: &[GeoPosition]| ps.iter().map(local).collect::<Vec<Vec3>>();
+  match geometry {
+    GeoGeometry::Point(p) => builder.point(local(p), options.point_size),
+    GeoGeometry::MultiPoint(ps) => {
+      for p in ps {
+        builder.point(local(p), options.point_size);
+      }
+    }
+    GeoGeometry::LineString(ps) => builder.line(&ring(ps), options.line_width),
+    GeoGeometry::MultiLineString(lines) => {
+      for line in lines {
+        builder.line(&ring(line), options.line_width);
+      }
+    }
+    GeoGeometry::Polygon(rings) => {
+      builder.polygon(&rings.iter().map(|r| ring(r)).collect::<Vec<_>>())?
+    }
+    GeoGeometry::MultiPolygon(polys) => {
+      for rings in polys {
+        builder.polygon(&rings.iter().map(|r| ring(r)).collect::<Vec<_>>())?;
+      }
+    }
+    GeoGeometry::Collection(geoms) => {
+      for g in geoms {
+        build_mesh(builder, g, projection, origin, options)?;
+      }
+    }
+  }
+  Ok(())
+}
+
+fn color_of(geometry: &GeoGeometry, options: &GeoJsonOptions) -> [f32; 4] {
+  match geometry {
+    GeoGeometry::Point(_) | GeoGeometry::MultiPoint(_) => options.point_color,
+    GeoGeometry::LineString(_) | GeoGeometry::MultiLineString(_) => options.line_color,
+    GeoGeometry::Polygon(_) | GeoGeometry::MultiPolygon(_) => options.polygon_color,
+    GeoGeometry::Collection(geoms) => geoms
+      .first()
+      .map(|g| color_of(g, options))
+      .unwrap_or(options.polygon_color),
+  }
+}
+
+/// Center of the bounding box of every position in `geometries`, at zero altitude.
+fn center_of<'a>(geometries: impl Iterator<Item = &'a GeoGeometry>) -> GeoPosition {
+  let mut min = DVec3::splat(f64::INFINITY);
+  let mut max = DVec3::splat(f64::NEG_INFINITY);
+  for p in geometries.flat_map(|g| g.positions()) {
+    let v = DVec3::new(p.longitude, p.latitude, 0.0);
+    min = min.min(v);
+    max = max.max(v);
+  }
+  if !min.is_finite() {
+    return GeoPosition::default();
+  }
+  let center = (min + max) * 0.5;
+  GeoPosition::new(center.x, center.y, 0.0)
+}
+
+fn from_position(p: &Position) -> Result<GeoPosition> {
+  match p.as_slice() {
+    [lon, lat] => Ok(GeoPosition::new(*lon, *lat, 0.0)),
+    [lon, lat, alt, ..] => Ok(GeoPosition::new(*lon, *lat, *alt)),
+    _ => Err(Error::InvalidGeometry(format!(
+      "position needs at least 2 coordinates, got {}",
+      p.len()
+    ))),
+  }
+}
+
+fn from_positions(ps: &[Position]) -> Result<Vec<GeoPosition>> {
+  ps.iter().map(from_position).collect()
+}
+
+fn from_rings(rings: &[Vec<Position>]) -> Result<Vec<Vec<GeoPosition>>> {
+  rings.iter().map(|r| from_positions(r)).collect()
+}
+
+fn from_geojson(value: &Value) -> Result<GeoGeometry> {
+  Ok(match value {
+    Value::Point(p) => GeoGeometry::Point(from_position(p)?),
+    Value::MultiPoint(ps) => GeoGeometry::MultiPoint(from_positions(ps)?),
+    Value::LineString(ps) => GeoGeometry::LineString(from_positions(ps)?),
+    Value::MultiLineString(lines) => GeoGeometry::MultiLineString(from_rings(lines)?),
+    Value::Polygon(rings) => GeoGeometry::Polygon(from_rings(rings)?),
+    Value::MultiPolygon(polys) => GeoGeometry::MultiPolygon(
+      polys
+        .iter()
+        .map(|rings| from_rings(rings))
+        .collect::<Result<_>>()?,
+    ),
+    Value::GeometryCollection(geoms) => GeoGeometry::Collection(
+      geoms
+        .iter()
+        .map(|g| from_geojson(&g.value))
+        .collect::<Result<_>>()?,
+    ),
+  })
+}
+
+fn to_position(p: GeoPosition) -> Position {
+  if p.altitude == 0.0 {
+    vec![p.longitude, p.latitude]
+  } else {
+    vec![p.longitude, p.latitude, p.altitude]
+  }
+}
+
+fn to_positions(ps: &[GeoPosition]) -> Vec<Position> {
+  ps.iter().copied().map(to_position).collect()
+}
+
+fn to_rings(rings: &[Vec<GeoPosition>]) -> Vec<Vec<Position>> {
+  rings.iter().map(|r| to_positions(r)).collect()
+}
+
+fn to_geojson(geometry: &GeoGeometry) -> Value {
+  match geometry {
+    GeoGeometry::Point(p) => Value::Point(to_position(*p)),
+    GeoGeometry::MultiPoint(ps) => Value::MultiPoint(to_positions(ps)),
+    GeoGeometry::LineString(ps) => Value::LineString(to_positions(ps)),
+    GeoGeometry::MultiLineString(lines) => Value::MultiLineString(to_rings(lines)),
+    GeoGeometry::Polygon(rings) => Value::Polygon(to_rings(rings)),
+    GeoGeometry::MultiPolygon(polys) => {
+      Value::MultiPolygon(polys.iter().map(|rings| to_rings(rings)).collect())
+    }
+    GeoGeometry::Collection(geoms) => {
+      Value::GeometryCollection(geoms.iter().map(|g| Geometry::new(to_geojson(g))).collect())
+    }
+  }
+}
+
+impl From<JsonObject> for Properties {
+  fn from(object: JsonObject) -> Self {
+    Self {
+      values: object.into_iter().collect(),
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn feature(name: &str, value: Value) -> Feature {
+    let mut feature = Feature::from(Geometry::new(value));
+    feature.set_property("name", name);
+    feature
+  }
+
+  #[test]
+  fn bad_features_are_skipped() {
+    let features = vec![
+      feature("good", Value::Point(vec![149.13, -35.28])),
+      feature("short", Value::Point(vec![149.13])),
+      feature(
+        "short ring",
+        Value::Polygon(vec![vec![vec![149.0, -35.0], vec![149.1]]]),
+      ),
+      Feature::default(),
+    ];
+    let mut scene = Scene::new();
+    let root = import_features(&mut scene, features, &GeoJsonOptions::default());
+    let children: Vec<_> = scene
+      .find(root)
+      .unwrap()
+      .children()
+      .map(|child| child.name().to_string())
+      .collect();
+    assert_eq!(children, ["good"]);
+  }
+}
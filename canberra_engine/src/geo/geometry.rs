@@ -0,0 +1,45 @@
+use crate::components::GeoPosition;
+
+/// Geometry in geographic coordinates, mirroring the GeoJSON geometry types.
+/// Polygons are lists of rings: the exterior ring first, then holes.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+pub enum GeoGeometry {
+  Point(GeoPosition),
+  MultiPoint(Vec<GeoPosition>),
+  LineString(Vec<GeoPosition>),
+  MultiLineString(Vec<Vec<GeoPosition>>),
+  Polygon(Vec<Vec<GeoPosition>>),
+  MultiPolygon(Vec<Vec<Vec<GeoPosition>>>),
+  Collection(Vec<GeoGeometry>),
+}
+
+impl GeoGeometry {
+  pub fn kind(&self) -> &'static str {
+    match self {
+      Self::Point(_) => "Point",
+      Self::MultiPoint(_) => "MultiPoint",
+      Self::LineString(_) => "LineString",
+      Self::MultiLineString(_) => "MultiLineString",
+      Self::Polygon(_) => "Polygon",
+      Self::MultiPolygon(_) => "MultiPolygon",
+      Self::Collection(_) => "GeometryCollection",
+    }
+  }
+
+  /// First position of the geometry, used as the entity's anchor.
+  pub fn anchor(&self) -> Option<GeoPosition> {
+    self.positions().next()
+  }
+
+  pub fn positions(&self) -> Box<dyn Iterator<Item = GeoPosition> + '_> {
+    match self {
+      Self::Point(p) => Box::new(std::iter::once(*p)),
+      Self::MultiPoint(ps) | Self::LineString(ps) => Box::new(ps.iter().copied()),
+      Self::MultiLineString(lines) | Self::Polygon(lines) => {
+        Box::new(lines.iter().flatten().copied())
+      }
+      Self::MultiPolygon(polys) => Box::new(polys.iter().flatten().flatten().copied()),
+      Self::Collection(geoms) => Box::new(geoms.iter().flat_map(|g| g.positions())),
+    }
+  }
+}
@@ -0,0 +1,136 @@
+use glam::{DQuat, DVec2, DVec3, Quat, Vec3};
+use uuid::Uuid;
+
+use super::{LocalProjection, WGS84_A, WGS84_B, geodetic_to_ecef};
+use crate::{
+  EntityViewMut, Scene,
+  components::{Camera, GeoPosition, GlobeCamera, Transform},
+};
+
+/// Latitude limit of the Web Mercator square.
+const MERCATOR_MAX_LATITUDE: f64 = 85.051_128_779_806_59;
+
+/// Projects onto Web Mercator (EPSG:3857), in metres.
+pub fn web_mercator(position: GeoPosition) -> DVec2 {
+  let lat = position
+    .latitude
+    .clamp(-MERCATOR_MAX_LATITUDE, MERCATOR_MAX_LATITUDE)
+    .to_radians();
+  DVec2::new(
+    WGS84_A * position.longitude.to_radians(),
+    WGS84_A * (std::f64::consts::FRAC_PI_4 + lat * 0.5).tan().ln(),
+  )
+}
+
+/// Inverse of [`web_mercator`], at zero altitude.
+pub fn web_mercator_inverse(xy: DVec2) -> GeoPosition {
+  GeoPosition::new(
+    (xy.x / WGS84_A).to_degrees(),
+    (2.0 * (xy.y / WGS84_A).exp().atan() - std::f64::consts::FRAC_PI_2).to_degrees(),
+    0.0,
+  )
+}
+
+/// Whether `point` (ECEF) is hidden behind the ellipsoid as seen from `eye` (ECEF).
+///
+/// Works in the scaled space where the ellipsoid becomes the unit sphere; points above
+/// the surface are treated as if they were on it, which keeps the test conservative.
+pub fn below_horizon(eye: DVec3, point: DVec3) -> bool {
+  let radii = DVec3::new(WGS84_A, WGS84_A, WGS84_B);
+  let eye = eye / radii;
+  let point = point / radii;
+  let vh = eye.length_squared() - 1.0;
+  if vh <= 0.0 {
+    return false;
+  }
+  let vt = point - eye;
+  let vt_dot_vc = -vt.dot(eye);
+  vt_dot_vc > vh && vt_dot_vc * vt_dot_vc / vt.length_squared() > vh
+}
+
+/// Places a geo position in `frame`, blending between the flat Web Mercator map
+/// (`morph = 0`) and the ellipsoid (`morph = 1`).
+pub fn place(frame: &LocalProjection, morph: f32, position: GeoPosition) -> (Vec3, Quat) {
+  let origin = frame.origin;
+  let scale = origin.latitude.to_radians().cos();
+  let planar = (web_mercator(position) - web_mercator(origin)) * scale;
+  let flat = DVec3::new(planar.x, position.altitude - origin.altitude, -planar.y);
+  let curved = frame.project_f64(position);
+
+  let morph = morph as f64;
+  let translation = flat.lerp(curved, morph);
+  let rotation = DQuat::IDENTITY.slerp(frame.orientation_at(position), morph);
+  (translation.as_vec3(), rotation.as_quat())
+}
+
+/// Re-centers the scene on the active globe camera and moves every geo-anchored entity
+/// onto the (partially) wrapped map. No-op unless a root camera carries a [`GlobeCamera`].
+///
+/// Geo-anchored entities are expected to sit under identity-transformed parents, since
+/// their `Transform` is overwritten with a frame-space placement.
+pub(crate) fn update_globe(scene: &mut Scene) {
+  let Some(id) = scene
+    .roots()
+    .find(|e| e.has::<GlobeCamera>() && e.has::<Camera>())
+    .map(|e| e.id())
+  else {
+    return;
+  };
+  let Some(mut camera) = scene.find_mut(id) else {
+    return;
+  };
+
+  let globe = camera.get_component::<GlobeCamera>().cloned().unwrap();
+  let (near, far) = globe.clip_range();
+  if let Some(mut cam) = camera.get_component_mut::<Camera>() {
+    cam.near = near;
+    cam.far = far;
+  }
+  set_transform(&mut camera, globe.eye().as_vec3(), globe.rotation());
+
+  let frame = globe.frame();
+  let morph = globe.morph();
+  let mut unplaced = Vec::new();
+  for (id, &position, transform) in
+    scene.query_mut::<(Uuid, &GeoPosition, Option<&mut Transform>)>()
+  {
+    let (translation, rotation) = place(&frame, morph, position);
+    match transform {
+      Some(mut transform) => {
+        transform.position = translation;
+        transform.rotation = rotation;
+      }
+      None => unplaced.push((id, translation, rotation)),
+    }
+  }
+  for (id, translation, rotation) in unplaced {
+    if let Some(mut entity) = scene.find_mut(id) {
+      set_transform(&mut entity, translation, rotation);
+    }
+  }
+}
+
+fn set_transform(entity: &mut EntityViewMut<'_>, position: Vec3, rotation: Quat) {
+  match entity.get_component_mut::<Transform>() {
+    Some(mut transform) => {
+      transform.position = position;
+      transform.rotation = rotation;
+    }
+    None => {
+      entity.add_component(Transform {
+        position,
+        rotation,
+        ..Default::default()
+      });
+    }
+  }
+}
+
+/// ECEF eye position of the active globe camera when the map is fully wrapped,
+/// which is the only state where horizon culling is meaningful.
+pub(crate) fn horizon_eye(scene: &Scene) -> Option<DVec3> {
+  scene.roots().find_map(|e| {
+    let globe = e.get_component::<GlobeCamera>()?;
+    (globe.morph() >= 1.0).then(|| geodetic_to_ecef(globe.eye_position()))
+  })
+}
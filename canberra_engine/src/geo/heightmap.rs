@@ -0,0 +1,194 @@
+use std::io::Cursor;
+
+use crate::{Error, Result};
+
+/// How elevation is packed into the RGB channels of a DEM tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ElevationEncoding {
+  /// `(R * 256 + G + B / 256) - 32768` metres (Mapzen/AWS Terrain Tiles).
+  Terrarium,
+  /// `-10000 + (R * 65536 + G * 256 + B) * 0.1` metres (Mapbox Terrain-RGB).
+  MapboxRgb,
+}
+
+impl ElevationEncoding {
+  pub fn decode(self, r: u8, g: u8, b: u8) -> f32 {
+    let (r, g, b) = (r as f32, g as f32, b as f32);
+    match self {
+      Self::Terrarium => r * 256.0 + g + b / 256.0 - 32768.0,
+      Self::MapboxRgb => -10000.0 + (r * 65536.0 + g * 256.0 + b) * 0.1,
+    }
+  }
+}
+
+/// Row-major grid of elevation samples in metres. Row 0 is the northern edge.
+#[derive(Debug, Clone, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(try_from = "RawHeightmap")]
+pub struct Heightmap {
+  width: u32,
+  height: u32,
+  samples: Vec<f32>,
+}
+
+/// Serialized form of a [`Heightmap`], checked by [`Heightmap::new`] when loaded.
+#[derive(serde::Deserialize)]
+struct RawHeightmap {
+  width: u32,
+  height: u32,
+  samples: Vec<f32>,
+}
+
+impl TryFrom<RawHeightmap> for Heightmap {
+  type Error = Error;
+
+  fn try_from(raw: RawHeightmap) -> Result<Self> {
+    Self::new(raw.width, raw.height, raw.samples)
+  }
+}
+
+impl Heightmap {
+  /// Builds a heightmap from raw samples. Non-finite values (nodata) are treated as sea level.
+  pub fn new(width: u32, height: u32, samples: Vec<f32>) -> Result<Self> {
+    if width < 2 || height < 2 {
+      return Err(Error::InvalidHeightmap(format!(
+        "grid must be at least 2x2, got {width}x{height}"
+      )));
+    }
+    let expected = (width as usize).checked_mul(height as usize);
+    if expected != Some(samples.len()) {
+      return Err(Error::InvalidHeightmap(format!(
+        "expected {width}x{height} samples, got {}",
+        samples.len()
+      )));
+    }
+    let samples = samples
+      .into_iter()
+      .map(|s| if s.is_finite() { s } else { 0.0 })
+      .collect();
+    Ok(Self {
+      width,
+      height,
+      samples,
+    })
+  }
+
+  /// Reads a headerless grid of little-endian `f32` samples, as exported from GeoTIFF DEMs.
+  pub fn from_f32_le(width: u32, height: u32, bytes: &[u8]) -> Result<Self> {
+    if !bytes.len().is_multiple_of(4) {
+      return Err(Error::InvalidHeightmap(format!(
+        "raw grid length {} is not a multiple of 4",
+        bytes.len()
+      )));
+    }
+    let samples = bytes
+      .chunks_exact(4)
+      .map(|c| f32::from_le_bytes([c[0], c[1], c[2], c[3]]))
+      .collect();
+    Self::new(width, height, samples)
+  }
+
+  /// Decodes an RGB-encoded DEM tile from PNG bytes.
+  pub fn from_png(bytes: &[u8], encoding: ElevationEncoding) -> Result<Self> {
+    let mut decoder = png::Decoder::new(Cursor::new(bytes));
+    decoder.set_transformations(png::Transformations::EXPAND | png::Transformations::STRIP_16);
+    let mut reader = decoder.read_info()?;
+    let mut buf = vec![
+      0;
+      reader
+        .output_buffer_size()
+        .ok_or_else(|| Error::InvalidHeightmap("image too large".to_string()))?
+    ];
+    let info = reader.next_frame(&mut buf)?;
+    let channels = match info.color_type {
+      png::ColorType::Rgb => 3,
+      png::ColorType::Rgba => 4,
+      other => {
+        return Err(Error::InvalidHeightmap(format!(
+          "expected an RGB or RGBA tile, got {other:?}"
+        )));
+      }
+    };
+
+    let mut samples = Vec::with_capacity(info.width as usize * info.height as usize);
+    for row in buf.chunks_exact(info.line_size).take(info.height as usize) {
+      samples.extend(
+        row
+          .chunks_exact(channels)
+          .take(info.width as usize)
+          .map(|px| encoding.decode(px[0], px[1], px[2])),
+      );
+    }
+    Self::new(info.width, info.height, samples)
+  }
+
+  pub fn width(&self) -> u32 {
+    self.width
+  }
+
+  pub fn height(&self) -> u32 {
+    self.height
+  }
+
+  pub fn samples(&self) -> &[f32] {
+    &self.samples
+  }
+
+  pub fn get(&self, x: u32, y: u32) -> f32 {
+    let x = x.min(self.width - 1);
+    let y = y.min(self.height - 1);
+    self.samples[y as usize * self.width as usize + x as usize]
+  }
+
+  /// Bilinearly interpolated elevation at normalized coordinates (`u` east, `v` south) in `[0, 1]`.
+  pub fn sample(&self, u: f32, v: f32) -> f32 {
+    let x = u.clamp(0.0, 1.0) * (self.width - 1) as f32;
+    let y = v.clamp(0.0, 1.0) * (self.height - 1) as f32;
+    let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+    let (tx, ty) = (x.fract(), y.fract());
+    let top = self.get(x0, y0) * (1.0 - tx) + self.get(x0 + 1, y0) * tx;
+    let bottom = self.get(x0, y0 + 1) * (1.0 - tx) + self.get(x0 + 1, y0 + 1) * tx;
+    top * (1.0 - ty) + bottom * ty
+  }
+
+  /// Lowest and highest sample.
+  pub fn range(&self) -> (f32, f32) {
+    self
+      .samples
+      .iter()
+      .fold((f32::INFINITY, f32::NEG_INFINITY), |(lo, hi), &s| {
+        (lo.min(s), hi.max(s))
+      })
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn rejects_bad_grids() {
+    assert!(Heightmap::new(1, 4, vec![0.0; 4]).is_err());
+    assert!(Heightmap::new(2, 2, vec![0.0; 3]).is_err());
+    assert!(Heightmap::new(u32::MAX, u32::MAX, vec![0.0; 4]).is_err());
+  }
+
+  #[test]
+  fn deserializing_checks_the_grid() {
+    let heightmap = Heightmap::new(2, 2, vec![0.0, 1.0, 2.0, f32::NAN]).unwrap();
+    let json = serde_json::to_string(&heightmap).unwrap();
+    assert_eq!(serde_json::from_str::<Heightmap>(&json).unwrap(), heightmap);
+
+    let short = r#"{"width":3,"height":3,"samples":[0.0,1.0]}"#;
+    assert!(serde_json::from_str::<Heightmap>(short).is_err());
+    let empty = r#"{"width":0,"height":0,"samples":[]}"#;
+    assert!(serde_json::from_str::<Heightmap>(empty).is_err());
+  }
+
+  #[test]
+  fn samples_interpolate_between_corners() {
+    let heightmap = Heightmap::new(2, 2, vec![0.0, 2.0, 4.0, 6.0]).unwrap();
+    assert_eq!(heightmap.sample(0.5, 0.5), 3.0);
+    assert_eq!(heightmap.sample(1.0, 0.0), 2.0);
+    assert_eq!(heightmap.range(), (0.0, 6.0));
+  }
+}
@@ -0,0 +1,100 @@
+use glam::{DMat3, DQuat, DVec3, Vec3};
+
+use super::{ecef_to_geodetic, geodetic_to_ecef};
+use crate::components::GeoPosition;
+
+/// Maps geodetic positions into the scene's local frame through an east-north-up
+/// tangent plane anchored at `origin`.
+///
+/// Scene axes: +X east, +Y up, -Z north.
+#[derive(Debug, Clone, Copy, PartialEq, serde::Serialize, serde::Deserialize)]
+pub struct LocalProjection {
+  pub origin: GeoPosition,
+}
+
+impl LocalProjection {
+  pub fn new(origin: GeoPosition) -> Self {
+    Self { origin }
+  }
+
+  /// Projects a geodetic position into scene coordinates (metres).
+  pub fn project(&self, position: GeoPosition) -> Vec3 {
+    self.project_f64(position).as_vec3()
+  }
+
+  pub fn project_f64(&self, position: GeoPosition) -> DVec3 {
+    let delta = geodetic_to_ecef(position) - geodetic_to_ecef(self.origin);
+    let enu = self.enu_basis().transpose() * delta;
+    DVec3::new(enu.x, enu.z, -enu.y)
+  }
+
+  /// Inverse of [`LocalProjection::project`].
+  pub fn unproject(&self, local: Vec3) -> GeoPosition {
+    self.unproject_f64(local.as_dvec3())
+  }
+
+  pub fn unproject_f64(&self, local: DVec3) -> GeoPosition {
+    let enu = DVec3::new(local.x, -local.z, local.y);
+    ecef_to_geodetic(geodetic_to_ecef(self.origin) + self.enu_basis() * enu)
+  }
+
+  /// Rotation taking the scene axes at `position` (east, up, south) into this projection's frame.
+  pub fn orientation_at(&self, position: GeoPosition) -> DQuat {
+    let to_local = self.enu_basis().transpose() * Self::new(position).enu_basis();
+    let scene = |enu: DVec3| DVec3::new(enu.x, enu.z, -enu.y);
+    DQuat::from_mat3(&DMat3::from_cols(
+      scene(to_local.x_axis),
+      scene(to_local.z_axis),
+      -scene(to_local.y_axis),
+    ))
+  }
+
+  /// Columns are the east, north and up unit vectors expressed in ECEF.
+  fn enu_basis(&self) -> DMat3 {
+    let (sin_lat, cos_lat) = self.origin.latitude.to_radians().sin_cos();
+    let (sin_lon, cos_lon) = self.origin.longitude.to_radians().sin_cos();
+    DMat3::from_cols(
+      DVec3::new(-sin_lon, cos_lon, 0.0),
+      DVec3::new(-sin_lat * cos_lon, -sin_lat * sin_lon, cos_lat),
+      DVec3::new(cos_lat * cos_lon, cos_lat * sin_lon, sin_lat),
+    )
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn origin_projects_to_zero() {
+    let origin = GeoPosition::new(151.2093, -33.8688, 20.0);
+    let projection = LocalProjection::new(origin);
+    assert!(projection.project_f64(origin).length() < 1e-6);
+  }
+
+  #[test]
+  fn axes_point_east_up_and_north() {
+    let projection = LocalProjection::new(GeoPosition::new(10.0, 45.0, 0.0));
+    let east = projection.project_f64(GeoPosition::new(10.001, 45.0, 0.0));
+    let north = projection.project_f64(GeoPosition::new(10.0, 45.001, 0.0));
+    let up = projection.project_f64(GeoPosition::new(10.0, 45.0, 100.0));
+    assert!(east.x > 0.0 && east.z.abs() < 1e-3 * east.x);
+    assert!(north.z < 0.0 && north.x.abs() < 1e-3 * -north.z);
+    assert!((up.y - 100.0).abs() < 1e-6);
+  }
+
+  #[test]
+  fn project_unproject_round_trip() {
+    let projection = LocalProjection::new(GeoPosition::new(-122.4194, 37.7749, 0.0));
+    for position in [
+      GeoPosition::new(-122.4194, 37.7749, 0.0),
+      GeoPosition::new(-122.40, 37.79, 35.0),
+      GeoPosition::new(-121.9, 37.3, 1200.0),
+    ] {
+      let back = projection.unproject_f64(projection.project_f64(position));
+      assert!((back.longitude - position.longitude).abs() < 1e-9);
+      assert!((back.latitude - position.latitude).abs() < 1e-9);
+      assert!((back.altitude - position.altitude).abs() < 1e-4);
+    }
+  }
+}
@@ -0,0 +1,249 @@
+use std::collections::HashMap;
+
+use glam::Vec3;
+
+use crate::{Error, Result, Vertex, components::Mesh};
+
+/// Most vertices a mesh can have with 16-bit indices.
+const MAX_VERTICES: usize = u16::MAX as usize + 1;
+
+/// Sharper joins than this (relative to half the line width) fall back to a bevel-like clamp.
+const MITER_LIMIT: f32 = 4.0;
+
+/// Accumulates ground-plane geometry (Y up) into a single [`Mesh`].
+#[derive(Debug, Default)]
+pub(crate) struct MeshBuilder {
+  vertices: Vec<Vertex>,
+  indices: Vec<u32>,
+}
+
+impl MeshBuilder {
+  pub(crate) fn new() -> Self {
+    Self::default()
+  }
+
+  pub(crate) fn is_empty(&self) -> bool {
+    self.indices.is_empty()
+  }
+
+  /// Appends an axis-aligned cube of edge `size` centered on `center`.
+  pub(crate) fn point(&mut self, center: Vec3, size: f32) {
+    let cube = Mesh::cube();
+    let base = self.vertices.len() as u32;
+    self.vertices.extend(cube.vertices().iter().map(|v| Vertex {
+      position: (Vec3::from(v.position) * size * 0.5 + center).into(),
+      normal: v.normal,
+    }));
+    self
+      .indices
+      .extend(cube.indices().iter().map(|&i| base + i as u32));
+  }
+
+  /// Appends a flat ribbon of `width` following `points`, with mitered joins.
+  pub(crate) fn line(&mut self, points: &[Vec3], width: f32) {
+    let mut points = points.to_vec();
+    points.dedup();
+    if points.len() < 2 {
+      return;
+    }
+
+    let half = width * 0.5;
+    let perp = |a: Vec3, b: Vec3| {
+      let d = (b - a).with_y(0.0).normalize_or_zero();
+      Vec3::new(-d.z, 0.0, d.x)
+    };
+
+    let base = self.vertices.len() as u32;
+    for i in 0..points.len() {
+      let offset = if i == 0 {
+        perp(points[0], points[1]) * half
+      } else if i == points.len() - 1 {
+        perp(points[i - 1], points[i]) * half
+      } else {
+        let a = perp(points[i - 1], points[i]);
+        let b = perp(points[i], points[i + 1]);
+        let miter = (a + b).normalize_or(a);
+        let scale = (1.0 / miter.dot(a).max(f32::EPSILON)).min(MITER_LIMIT);
+        miter * half * scale
+      };
+      for side in [offset, -offset] {
+        self.vertices.push(Vertex {
+          position: (points[i] + side).into(),
+          normal: Vec3::Y.into(),
+        });
+      }
+    }
+
+    for i in 0..points.len() as u32 - 1 {
+      let (l0, r0, l1, r1) = (
+        base + i * 2,
+        base + i * 2 + 1,
+        base + i * 2 + 2,
+        base + i * 2 + 3,
+      );
+      self.triangle(l0, r0, l1);
+      self.triangle(r0, r1, l1);
+    }
+  }
+
+  /// Triangulates a polygon given as an exterior ring followed by holes.
+  pub(crate) fn polygon(&mut self, rings: &[Vec<Vec3>]) -> Result<()> {
+    let mut flat = Vec::new();
+    let mut holes = Vec::new();
+    let base = self.vertices.len() as u32;
+    for (i, ring) in rings.iter().enumerate() {
+      // GeoJSON rings repeat the first position at the end.
+      let ring = match ring.split_last() {
+        Some((last, rest)) if rest.first() == Some(last) => rest,
+        _ => ring.as_slice(),
+      };
+      if ring.len() < 3 {
+        if i == 0 {
+          return Ok(());
+        }
+        continue;
+      }
+      if i > 0 {
+        holes.push(flat.len() / 2);
+      }
+      for p in ring {
+        flat.extend([p.x as f64, p.z as f64]);
+        self.vertices.push(Vertex {
+          position: (*p).into(),
+          normal: Vec3::Y.into(),
+        });
+      }
+    }
+
+    let triangles = earcutr::earcut(&flat, &holes, 2)
+      .map_err(|e| Error::InvalidGeometry(format!("polygon triangulation failed: {e}")))?;
+    for tri in triangles.chunks_exact(3) {
+      self.triangle(
+        base + tri[0] as u32,
+        base + tri[1] as u32,
+        base + tri[2] as u32,
+      );
+    }
+    Ok(())
+  }
+
+  /// Builds the accumulated geometry, split into as many meshes as needed for each to stay
+  /// within 16-bit indices.
+  pub(crate) fn build(self) -> Vec<Mesh> {
+    if self.vertices.len() <= MAX_VERTICES {
+      let indices = self.indices.into_iter().map(|i| i as u16).collect();
+      return vec![Mesh::new(self.vertices, indices)];
+    }
+
+    let mut meshes = Vec::new();
+    let mut remap = HashMap::new();
+    let mut vertices = Vec::new();
+    let mut indices = Vec::new();
+    for tri in self.indices.chunks_exact(3) {
+      let added = tri.iter().filter(|i| !remap.contains_key(*i)).count();
+      if vertices.len() + added > MAX_VERTICES {
+        meshes.push(Mesh::new(
+          std::mem::take(&mut vertices),
+          std::mem::take(&mut indices),
+        ));
+        remap.clear();
+      }
+      for &i in tri {
+        let local = *remap.entry(i).or_insert_with(|| {
+          vertices.push(self.vertices[i as usize]);
+          (vertices.len() - 1) as u16
+        });
+        indices.push(local);
+      }
+    }
+    if !indices.is_empty() {
+      meshes.push(Mesh::new(vertices, indices));
+    }
+    meshes
+  }
+
+  /// Pushes a triangle, flipping it if needed so its front face points up (+Y).
+  fn triangle(&mut self, a: u32, b: u32, c: u32) {
+    let pos = |i: u32| Vec3::from(self.vertices[i as usize].position);
+    let normal = (pos(b) - pos(a)).cross(pos(c) - pos(a));
+    if normal.y >= 0.0 {
+      self.indices.extend([a, b, c]);
+    } else {
+      self.indices.extend([a, c, b]);
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  fn square(size: f32) -> Vec<Vec3> {
+    vec![
+      Vec3::new(0.0, 0.0, 0.0),
+      Vec3::new(size, 0.0, 0.0),
+      Vec3::new(size, 0.0, size),
+      Vec3::new(0.0, 0.0, size),
+      Vec3::new(0.0, 0.0, 0.0),
+    ]
+  }
+
+  fn area(mesh: &Mesh) -> f32 {
+    mesh
+      .indices()
+      .chunks_exact(3)
+      .map(|tri| {
+        let p = |i: u16| Vec3::from(mesh.vertices()[i as usize].position);
+        (p(tri[1]) - p(tri[0])).cross(p(tri[2]) - p(tri[0])).y * 0.5
+      })
+      .sum()
+  }
+
+  #[test]
+  fn polygon_drops_closing_position_and_faces_up() {
+    let mut builder = MeshBuilder::new();
+    builder.polygon(&[square(2.0)]).unwrap();
+    let meshes = builder.build();
+    assert_eq!(meshes.len(), 1);
+    assert_eq!(meshes[0].vertices().len(), 4);
+    assert_eq!(meshes[0].indices().len(), 6);
+    assert!((area(&meshes[0]) - 4.0).abs() < 1e-5);
+  }
+
+  #[test]
+  fn polygon_hole_is_left_open() {
+    let hole = square(1.0)
+      .into_iter()
+      .map(|p| p + Vec3::new(0.5, 0.0, 0.5))
+      .rev()
+      .collect();
+    let mut builder = MeshBuilder::new();
+    builder.polygon(&[square(2.0), hole]).unwrap();
+    let meshes = builder.build();
+    assert!((area(&meshes[0]) - 3.0).abs() < 1e-5);
+  }
+
+  #[test]
+  fn degenerate_polygon_is_skipped() {
+    let mut builder = MeshBuilder::new();
+    builder
+      .polygon(&[vec![Vec3::ZERO, Vec3::X, Vec3::ZERO]])
+      .unwrap();
+    assert!(builder.is_empty());
+  }
+
+  #[test]
+  fn large_geometry_is_split_into_16_bit_meshes() {
+    let mut builder = MeshBuilder::new();
+    let points: Vec<_> = (0..40_000).map(|i| Vec3::new(i as f32, 0.0, 0.0)).collect();
+    builder.line(&points, 1.0);
+    let triangles = builder.indices.len() / 3;
+    let meshes = builder.build();
+    assert!(meshes.len() > 1);
+    assert!(meshes.iter().all(|m| m.vertices().len() <= MAX_VERTICES));
+    assert_eq!(
+      meshes.iter().map(|m| m.indices().len() / 3).sum::<usize>(),
+      triangles
+    );
+  }
+}
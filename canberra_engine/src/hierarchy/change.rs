@@ -0,0 +1,132 @@
+use std::{
+  any::TypeId,
+  collections::VecDeque,
+  ops::{Deref, DerefMut},
+};
+
+use uuid::Uuid;
+
+/// When a component was added and last written, in [`crate::Scene::change_tick`]s.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComponentTicks {
+  pub added: u64,
+  pub changed: u64,
+}
+
+impl ComponentTicks {
+  pub(crate) fn new(tick: u64) -> Self {
+    Self {
+      added: tick,
+      changed: tick,
+    }
+  }
+
+  /// Added after tick `since`.
+  pub fn is_added(&self, since: u64) -> bool {
+    self.added > since
+  }
+
+  /// Added or written after tick `since`.
+  pub fn is_changed(&self, since: u64) -> bool {
+    self.changed > since
+  }
+}
+
+/// A component borrowed for writing. Reading through it leaves the component alone; the
+/// first mutable dereference stamps it as changed, so [`crate::Changed`] only sees writes.
+pub struct Mut<'w, C> {
+  value: &'w mut C,
+  ticks: &'w mut ComponentTicks,
+  tick: u64,
+}
+
+impl<'w, C> Mut<'w, C> {
+  pub(crate) fn new(value: &'w mut C, ticks: &'w mut ComponentTicks, tick: u64) -> Self {
+    Self { value, ticks, tick }
+  }
+
+  /// The plain reference, stamped as changed.
+  pub fn into_inner(self) -> &'w mut C {
+    self.ticks.changed = self.tick;
+    self.value
+  }
+}
+
+impl<C> Deref for Mut<'_, C> {
+  type Target = C;
+
+  fn deref(&self) -> &C {
+    self.value
+  }
+}
+
+impl<C> DerefMut for Mut<'_, C> {
+  fn deref_mut(&mut self) -> &mut C {
+    self.ticks.changed = self.tick;
+    self.value
+  }
+}
+
+impl<C: std::fmt::Debug> std::fmt::Debug for Mut<'_, C> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    self.value.fmt(f)
+  }
+}
+
+/// Entity lifecycle event, sent by [`crate::Scene`] and [`crate::Entity`] edits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SceneEvent {
+  /// The entity, or an ancestor, was added to the scene.
+  Spawned(Uuid),
+  /// The entity, or an ancestor, was removed from the scene.
+  Despawned(Uuid),
+  ComponentAdded {
+    entity: Uuid,
+    type_id: TypeId,
+    name: &'static str,
+  },
+  ComponentRemoved {
+    entity: Uuid,
+    type_id: TypeId,
+    name: &'static str,
+  },
+}
+
+/// Position of one reader in [`SceneEvents`]; keep one per reader and read at least once a
+/// frame to see every event exactly once.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EventCursor {
+  next: u64,
+}
+
+/// Events sent this frame and the previous one; older ones are dropped.
+#[derive(Debug, Clone, Default)]
+pub struct SceneEvents {
+  events: VecDeque<(u64, SceneEvent)>,
+  /// Sequence number of the next event sent.
+  next: u64,
+  /// First sequence number sent this frame.
+  frame_start: u64,
+}
+
+impl SceneEvents {
+  pub(crate) fn send(&mut self, event: SceneEvent) {
+    self.events.push_back((self.next, event));
+    self.next += 1;
+  }
+
+  /// Events `cursor` has not seen, oldest first; advances it past them.
+  pub fn read(&self, cursor: &mut EventCursor) -> impl Iterator<Item = &SceneEvent> {
+    let start = self.events.partition_point(|(seq, _)| *seq < cursor.next);
+    cursor.next = self.next;
+    self.events.range(start..).map(|(_, event)| event)
+  }
+
+  /// Drops the events of the previous frame.
+  pub(crate) fn end_frame(&mut self) {
+    let frame_start = self.frame_start;
+    let expired = self.events.partition_point(|(seq, _)| *seq < frame_start);
+    self.events.drain(..expired);
+    self.frame_start = self.next;
+  }
+}
@@ -1,12 +1,24 @@
 use std::any::Any;
 
+use super::world::ComponentStorage;
+
+/// Data attached to an [`crate::Entity`]; usually implemented with `#[derive(Component)]`,
+/// which takes the editor UI from the type's [`crate::Inspect`] impl.
 #[typetag::serde(tag = "type")]
-pub trait Component: Any + std::fmt::Debug {
+pub trait Component: Any + std::fmt::Debug + ComponentStorage {
   fn name(&self) -> &'static str;
   fn as_any(&self) -> &dyn Any;
   fn as_any_mut(&mut self) -> &mut dyn Any;
 
-  fn inspect(&mut self, ui: &mut egui::Ui) {
+  /// Whether an entity may carry several components of this type; otherwise adding one
+  /// replaces the existing one.
+  fn multiple(&self) -> bool {
+    false
+  }
+
+  /// Draws the editor UI; returns whether it changed the component.
+  fn inspect(&mut self, ui: &mut egui::Ui) -> bool {
     ui.label(self.name());
+    false
   }
 }
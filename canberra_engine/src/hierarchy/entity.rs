@@ -1,15 +1,26 @@
-use std::{any::TypeId, collections::HashMap};
+use std::{
+  any::{Any, TypeId},
+  collections::HashMap,
+};
 
 use serde::{Deserialize, Serialize, de::Deserializer, ser::Serializer};
+use serde_json::Value;
 use uuid::Uuid;
 
-use crate::Component;
+use super::entity_ref;
+use crate::{Component, Result};
 
+/// An entity outside any [`crate::Scene`], with its descendants: built up before
+/// [`crate::Scene::add`], or returned by [`crate::Scene::despawn`]. Detached entities have
+/// no change ticks and send no events; spawning one stamps all its components as added.
 #[derive(Debug)]
 pub struct Entity {
   id: Uuid,
   pub name: String,
-  components: HashMap<TypeId, Box<dyn Component>>,
+  /// In the order they were added.
+  components: Vec<Box<dyn Component>>,
+  /// Position in `components` of the first component of each type.
+  first: HashMap<TypeId, usize>,
   children: Vec<Entity>,
 }
 
@@ -18,38 +29,147 @@ impl Entity {
     Self {
       id: Uuid::new_v4(),
       name: name.to_string(),
-      components: HashMap::new(),
+      components: Vec::new(),
+      first: HashMap::new(),
       children: Vec::new(),
     }
   }
 
+  pub(crate) fn from_parts(
+    id: Uuid,
+    name: String,
+    components: Vec<Box<dyn Component>>,
+    children: Vec<Entity>,
+  ) -> Self {
+    let mut entity = Self {
+      id,
+      name,
+      components,
+      first: HashMap::new(),
+      children,
+    };
+    entity.reindex();
+    entity
+  }
+
+  pub(crate) fn into_parts(self) -> (Uuid, String, Vec<Box<dyn Component>>, Vec<Entity>) {
+    (self.id, self.name, self.components, self.children)
+  }
+
   pub fn id(&self) -> Uuid {
     self.id
   }
 
-  pub fn add_component<C: Component + 'static>(&mut self, component: C) -> &mut Self {
-    self
-      .components
-      .insert(TypeId::of::<C>(), Box::new(component));
+  /// Adds `component`, replacing the one of the same type unless the type allows
+  /// [several per entity](Component::multiple).
+  pub fn add_component<C: Component>(&mut self, component: C) -> &mut Self {
+    self.insert_component(component);
     self
   }
 
+  /// Like [`Entity::add_component`], returning the component it replaced.
+  pub fn insert_component<C: Component>(&mut self, component: C) -> Option<C> {
+    if !component.multiple()
+      && let Some(existing) = self.get_component_mut::<C>()
+    {
+      return Some(std::mem::replace(existing, component));
+    }
+    self.push(Box::new(component));
+    None
+  }
+
+  /// [`Entity::insert_component`] for a component whose type is only known at runtime.
+  pub fn insert_boxed(&mut self, component: Box<dyn Component>) -> Option<Box<dyn Component>> {
+    if !component.multiple()
+      && let Some(index) = self.position(component.as_any().type_id())
+    {
+      return Some(std::mem::replace(&mut self.components[index], component));
+    }
+    self.push(component);
+    None
+  }
+
+  fn push(&mut self, component: Box<dyn Component>) {
+    let type_id = component.as_any().type_id();
+    self.first.entry(type_id).or_insert(self.components.len());
+    self.components.push(component);
+  }
+
+  fn reindex(&mut self) {
+    self.first.clear();
+    for (index, component) in self.components.iter().enumerate().rev() {
+      self.first.insert(component.as_any().type_id(), index);
+    }
+  }
+
+  /// Removes the first component of type `C`.
+  pub fn remove_component<C: Component>(&mut self) -> Option<C> {
+    let index = self.position(TypeId::of::<C>())?;
+    let component: Box<dyn Any> = self.remove_component_at(index)?;
+    component.downcast().ok().map(|c| *c)
+  }
+
+  /// Removes the component at `index` in [`Entity::iter`] order.
+  pub fn remove_component_at(&mut self, index: usize) -> Option<Box<dyn Component>> {
+    if index >= self.components.len() {
+      return None;
+    }
+    let component = self.components.remove(index);
+    self.reindex();
+    Some(component)
+  }
+
+  pub fn has<C: Component>(&self) -> bool {
+    self.position(TypeId::of::<C>()).is_some()
+  }
+
+  /// First component of type `C`.
   pub fn get_component<C: 'static>(&self) -> Option<&C> {
-    self
-      .components
-      .get(&TypeId::of::<C>())
-      .and_then(|c| c.as_any().downcast_ref::<C>())
+    let index = self.position(TypeId::of::<C>())?;
+    self.components[index].as_any().downcast_ref()
   }
 
   pub fn get_component_mut<C: 'static>(&mut self) -> Option<&mut C> {
-    self
-      .components
-      .get_mut(&TypeId::of::<C>())
-      .and_then(|c| c.as_any_mut().downcast_mut::<C>())
+    let index = self.position(TypeId::of::<C>())?;
+    self.components[index].as_any_mut().downcast_mut()
+  }
+
+  /// Every component of type `C`, for types that allow [several](Component::multiple).
+  pub fn get_components<C: 'static>(&self) -> impl Iterator<Item = &C> {
+    let start = self
+      .position(TypeId::of::<C>())
+      .unwrap_or(self.components.len());
+    self.components[start..]
+      .iter()
+      .filter_map(|c| c.as_any().downcast_ref::<C>())
+  }
+
+  pub fn get_components_mut<C: 'static>(&mut self) -> impl Iterator<Item = &mut C> {
+    let start = self
+      .position(TypeId::of::<C>())
+      .unwrap_or(self.components.len());
+    self.components[start..]
+      .iter_mut()
+      .filter_map(|c| c.as_any_mut().downcast_mut::<C>())
+  }
+
+  fn position(&self, type_id: TypeId) -> Option<usize> {
+    self.first.get(&type_id).copied()
   }
 
   pub fn add_child(&mut self, child: Entity) -> &mut Self {
-    self.children.push(child);
+    self.insert_child(self.children.len(), child)
+  }
+
+  /// Detaches the direct child `id` along with its subtree.
+  pub fn remove_child(&mut self, id: Uuid) -> Option<Entity> {
+    let index = self.children.iter().position(|c| c.id == id)?;
+    Some(self.children.remove(index))
+  }
+
+  /// Inserts `child` at `index` among the children, shifting later ones.
+  pub fn insert_child(&mut self, index: usize, child: Entity) -> &mut Self {
+    self.children.insert(index.min(self.children.len()), child);
     self
   }
 
@@ -61,29 +181,94 @@ impl Entity {
     &mut self.children
   }
 
+  /// Components in the order they were added.
   pub fn iter(&self) -> impl Iterator<Item = &dyn Component> {
-    self.components.iter().map(|(_, b)| b.as_ref())
+    self.components.iter().map(|c| c.as_ref())
   }
 
   pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut dyn Component> {
-    self.components.iter_mut().map(|(_, b)| b.as_mut())
+    self.components.iter_mut().map(|c| c.as_mut())
+  }
+
+  /// Deep copy of this entity and its descendants with fresh ids. Components are copied
+  /// through their serialized form; [`crate::EntityRef`]s to entities in the subtree are
+  /// pointed at the copies, so references between the copied entities stay within the copy.
+  pub fn duplicate(&self) -> Result<Entity> {
+    Ok(self.duplicate_mapped()?.0)
+  }
+
+  /// [`Entity::duplicate`], also returning each original id with the id of its copy,
+  /// depth-first.
+  pub(crate) fn duplicate_mapped(&self) -> Result<(Entity, Vec<(Uuid, Uuid)>)> {
+    Self::copy_of(serde_json::to_value(self)?)
+  }
+
+  /// Reads a serialized entity subtree as a copy with fresh ids; see
+  /// [`Entity::duplicate_mapped`].
+  pub(crate) fn copy_of(mut value: Value) -> Result<(Entity, Vec<(Uuid, Uuid)>)> {
+    let mut ids = Vec::new();
+    let mut stack = vec![&value];
+    while let Some(entity) = stack.pop() {
+      if let Some(id) = entity.get("id").and_then(Value::as_str)
+        && let Ok(id) = id.parse()
+      {
+        ids.push((id, Uuid::new_v4()));
+      }
+      if let Some(children) = entity.get("children").and_then(Value::as_array) {
+        stack.extend(children.iter().rev());
+      }
+    }
+    remap_ids(&mut value, &ids);
+    Ok((serde_json::from_value(value)?, ids))
+  }
+}
+
+/// Rewrites the ids of the entities in the serialized subtree `value`, and the
+/// [`crate::EntityRef`]s their components hold, from the first id of each pair in `ids`
+/// to the second. Other data is left alone, even strings that happen to spell an id.
+pub(crate) fn remap_ids(value: &mut Value, ids: &[(Uuid, Uuid)]) {
+  let ids: HashMap<Uuid, Uuid> = ids.iter().copied().collect();
+  remap_entity(value, &ids);
+}
+
+fn remap_entity(entity: &mut Value, ids: &HashMap<Uuid, Uuid>) {
+  if let Some(id) = entity.get_mut("id")
+    && let Some(to) = id
+      .as_str()
+      .and_then(|id| id.parse().ok())
+      .and_then(|id| ids.get(&id))
+  {
+    *id = Value::String(to.to_string());
+  }
+  if let Some(Value::Array(components)) = entity.get_mut("components") {
+    for component in components {
+      match entity_ref::remap_refs(component, ids).and_then(serde_json::to_value) {
+        Ok(remapped) => *component = remapped,
+        Err(e) => tracing::warn!("cannot remap entity references in a component: {e}"),
+      }
+    }
+  }
+  if let Some(Value::Array(children)) = entity.get_mut("children") {
+    for child in children {
+      remap_entity(child, ids);
+    }
   }
 }
 
 impl Serialize for Entity {
-  fn serialize<S: Serializer>(&self, s: S) -> Result<S::Ok, S::Error> {
+  fn serialize<S: Serializer>(&self, s: S) -> std::result::Result<S::Ok, S::Error> {
     #[derive(Serialize)]
     struct EntityFields<'a> {
       id: &'a Uuid,
       name: &'a str,
-      components: Vec<&'a Box<dyn Component>>,
+      components: Vec<&'a dyn Component>,
       children: &'a [Entity],
     }
 
     let fields = EntityFields {
       id: &self.id,
       name: &self.name,
-      components: self.components.values().collect(),
+      components: self.iter().collect(),
       children: &self.children,
     };
 
@@ -92,7 +277,7 @@ impl Serialize for Entity {
 }
 
 impl<'de> Deserialize<'de> for Entity {
-  fn deserialize<D: Deserializer<'de>>(d: D) -> Result<Self, D::Error> {
+  fn deserialize<D: Deserializer<'de>>(d: D) -> std::result::Result<Self, D::Error> {
     #[derive(Deserialize)]
     struct EntityFields {
       id: Uuid,
@@ -104,16 +289,119 @@ impl<'de> Deserialize<'de> for Entity {
 
     let raw = EntityFields::deserialize(d)?;
 
-    let mut components = HashMap::with_capacity(raw.components.len());
-    for component in raw.components {
-      components.insert(component.type_id(), component);
-    }
-
-    Ok(Self {
+    let mut entity = Self {
       id: raw.id,
       name: raw.name,
-      components,
+      components: Vec::with_capacity(raw.components.len()),
+      first: HashMap::new(),
       children: raw.children,
-    })
+    };
+    for component in raw.components {
+      entity.insert_boxed(component);
+    }
+    Ok(entity)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::{
+    EntityRef, Inspect, Prefab,
+    components::{PrefabInstance, Properties, Transform},
+  };
+
+  #[derive(Debug, Component, Inspect, serde::Serialize, serde::Deserialize)]
+  #[component(hidden)]
+  struct Follow {
+    target: EntityRef,
+  }
+
+  #[test]
+  fn lookups_follow_added_and_removed_components() {
+    let mut entity = Entity::new("e");
+    entity
+      .add_component(Properties::new())
+      .add_component(Transform::default());
+    assert!(entity.has::<Transform>());
+
+    let mut properties = Properties::new();
+    properties.insert("kind", "road");
+    entity.add_component(properties);
+    assert_eq!(entity.iter().count(), 2);
+    assert!(
+      entity
+        .get_component::<Properties>()
+        .unwrap()
+        .get("kind")
+        .is_some()
+    );
+
+    entity.remove_component::<Properties>();
+    assert!(!entity.has::<Properties>());
+    // Removing shifts later components down; their lookups must follow.
+    assert!(entity.get_component_mut::<Transform>().is_some());
+    assert_eq!(entity.remove_component_at(0).unwrap().name(), "Transform");
+    assert!(entity.get_component::<Transform>().is_none());
   }
-}
\ No newline at end of file
+
+  #[test]
+  fn deserialized_entities_index_their_components() {
+    let mut entity = Entity::new("e");
+    entity.add_component(Transform::default());
+    let copy: Entity = serde_json::from_value(serde_json::to_value(&entity).unwrap()).unwrap();
+    assert!(copy.has::<Transform>());
+  }
+
+  #[test]
+  fn duplicate_remaps_ids_and_refs_but_not_user_data() {
+    let mut root = Entity::new("root");
+    let mut child = Entity::new("child");
+    let outside = Uuid::new_v4();
+    let (root_id, child_id) = (root.id(), child.id());
+    child.add_component(Follow {
+      target: EntityRef::new(root_id),
+    });
+    let mut properties = Properties::new();
+    properties.insert("source", root_id.to_string());
+    properties.insert(child_id.to_string(), "key");
+    root
+      .add_component(properties)
+      .add_component(Follow {
+        target: EntityRef::new(outside),
+      })
+      .add_child(child);
+
+    let copy = root.duplicate().unwrap();
+    let copy_child = &copy.children()[0];
+    assert_ne!(copy.id(), root_id);
+    assert_ne!(copy_child.id(), child_id);
+    // References within the subtree follow the copy; those leaving it stay.
+    let target = |e: &Entity| e.get_component::<Follow>().unwrap().target.id();
+    assert_eq!(target(copy_child), Some(copy.id()));
+    assert_eq!(target(&copy), Some(outside));
+    let properties = copy.get_component::<Properties>().unwrap();
+    assert_eq!(properties.get("source").unwrap(), &root_id.to_string());
+    assert!(properties.get(&child_id.to_string()).is_some());
+  }
+
+  #[test]
+  fn duplicated_prefab_instances_track_their_own_entities() {
+    let mut root = Entity::new("root");
+    root.add_child(Entity::new("child"));
+    let prefab = Prefab::new("prefab", &root).unwrap();
+    let instance = prefab.instantiate().unwrap();
+    let copy = instance.duplicate().unwrap();
+
+    let info = copy.get_component::<PrefabInstance>().unwrap();
+    let prefab_child = prefab.root().unwrap().children()[0].id();
+    assert_eq!(
+      info.instance_of(prefab.root().unwrap().id()),
+      Some(copy.id())
+    );
+    assert_eq!(
+      info.instance_of(prefab_child),
+      Some(copy.children()[0].id())
+    );
+  }
+}
@@ -0,0 +1,182 @@
+use std::{cell::RefCell, collections::HashMap, sync::Arc};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{Component, Entity, EntityView, Inspect, InspectOptions, Scene};
+
+/// A component's link to another entity, e.g. a target to follow. Serialized as the
+/// target's id, so [`Entity::duplicate`] and prefab instantiation point it at the copy when
+/// the target is copied along. Resolve it with [`crate::Scene::resolve`]; references whose
+/// target is gone show up in [`crate::Scene::dangling_refs`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Hash)]
+pub struct EntityRef(Option<Uuid>);
+
+impl EntityRef {
+  pub fn new(target: Uuid) -> Self {
+    Self(Some(target))
+  }
+
+  /// A reference to nothing.
+  pub fn none() -> Self {
+    Self(None)
+  }
+
+  pub fn id(&self) -> Option<Uuid> {
+    self.0
+  }
+
+  pub fn is_none(&self) -> bool {
+    self.0.is_none()
+  }
+
+  pub fn set(&mut self, target: Option<Uuid>) {
+    self.0 = target;
+  }
+}
+
+impl From<Uuid> for EntityRef {
+  fn from(target: Uuid) -> Self {
+    Self::new(target)
+  }
+}
+
+impl From<&Entity> for EntityRef {
+  fn from(target: &Entity) -> Self {
+    Self::new(target.id())
+  }
+}
+
+impl From<EntityView<'_>> for EntityRef {
+  fn from(target: EntityView<'_>) -> Self {
+    Self::new(target.id())
+  }
+}
+
+/// An [`EntityRef`] whose target is not in the scene.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DanglingRef {
+  /// The entity holding the reference.
+  pub entity: Uuid,
+  /// Name of the component holding it.
+  pub component: &'static str,
+  pub target: Uuid,
+}
+
+thread_local! {
+  /// Targets of the references serialized during [`entity_refs`].
+  static COLLECTED: RefCell<Option<Vec<Uuid>>> = const { RefCell::new(None) };
+  /// New targets for the references deserialized during [`remap_refs`].
+  static REMAP: RefCell<Option<HashMap<Uuid, Uuid>>> = const { RefCell::new(None) };
+}
+
+/// `component` read back with its [`EntityRef`]s pointed from the first id of each pair in
+/// `ids` to the second.
+pub(crate) fn remap_refs(
+  component: &Value,
+  ids: &HashMap<Uuid, Uuid>,
+) -> serde_json::Result<Box<dyn Component>> {
+  REMAP.set(Some(ids.clone()));
+  let component = Box::<dyn Component>::deserialize(component);
+  REMAP.set(None);
+  component
+}
+
+/// Targets of the [`EntityRef`]s in `component`, found by serializing it.
+pub(crate) fn entity_refs(component: &dyn Component) -> Vec<Uuid> {
+  COLLECTED.set(Some(Vec::new()));
+  if let Err(e) = serde_json::to_writer(std::io::sink(), component) {
+    tracing::warn!(
+      "cannot scan {} for entity references: {e}",
+      component.name()
+    );
+  }
+  COLLECTED.take().unwrap_or_default()
+}
+
+impl Serialize for EntityRef {
+  fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+    if let Some(target) = self.0 {
+      COLLECTED.with_borrow_mut(|collected| {
+        if let Some(collected) = collected {
+          collected.push(target);
+        }
+      });
+    }
+    self.0.serialize(serializer)
+  }
+}
+
+impl<'de> Deserialize<'de> for EntityRef {
+  fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+    let target = Option::<Uuid>::deserialize(deserializer)?;
+    let target = target.map(|target| {
+      REMAP.with_borrow(|remap| {
+        remap
+          .as_ref()
+          .and_then(|remap| remap.get(&target).copied())
+          .unwrap_or(target)
+      })
+    });
+    Ok(Self(target))
+  }
+}
+
+/// An entity offered by [`EntityRef`] pickers.
+#[derive(Debug, Clone)]
+struct Choice {
+  id: Uuid,
+  name: String,
+  depth: usize,
+}
+
+fn choices_id() -> egui::Id {
+  egui::Id::new("canberra_engine::EntityRef choices")
+}
+
+/// Offers the entities of `scene` to the [`EntityRef`] pickers drawn after this call.
+pub(crate) fn set_choices(ctx: &egui::Context, scene: &Scene) {
+  fn add(entity: EntityView<'_>, depth: usize, choices: &mut Vec<Choice>) {
+    choices.push(Choice {
+      id: entity.id(),
+      name: entity.name().to_string(),
+      depth,
+    });
+    for child in entity.children() {
+      add(child, depth + 1, choices);
+    }
+  }
+  let mut choices = Vec::new();
+  for root in scene.roots() {
+    add(root, 0, &mut choices);
+  }
+  ctx.data_mut(|d| d.insert_temp(choices_id(), Arc::new(choices)));
+}
+
+impl Inspect for EntityRef {
+  fn inspect(&mut self, ui: &mut egui::Ui, _options: &InspectOptions) -> bool {
+    let choices: Arc<Vec<Choice>> = ui
+      .ctx()
+      .data(|d| d.get_temp(choices_id()))
+      .unwrap_or_default();
+    let selected = match self.0 {
+      None => egui::RichText::new("None"),
+      Some(id) => match choices.iter().find(|c| c.id == id) {
+        Some(choice) => egui::RichText::new(&choice.name),
+        None => egui::RichText::new(format!("Missing {id}")).color(ui.visuals().error_fg_color),
+      },
+    };
+    let before = self.0;
+    egui::ComboBox::from_id_salt(ui.next_auto_id())
+      .selected_text(selected)
+      .show_ui(ui, |ui| {
+        ui.selectable_value(&mut self.0, None, "None");
+        for choice in choices.iter() {
+          let label = format!("{}{}", "  ".repeat(choice.depth), choice.name);
+          ui.selectable_value(&mut self.0, Some(choice.id), label);
+        }
+      });
+    self.0 != before
+  }
+}
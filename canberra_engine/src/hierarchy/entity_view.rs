@@ -0,0 +1,242 @@
+use std::any::{Any, TypeId};
+
+use serde::{Serialize, Serializer};
+use uuid::Uuid;
+
+use super::{
+  change::{ComponentTicks, Mut},
+  world::World,
+};
+use crate::{Component, Entity, Result};
+
+/// An entity in a [`crate::Scene`], borrowed for reading.
+#[derive(Clone, Copy)]
+pub struct EntityView<'w> {
+  world: &'w World,
+  index: u32,
+}
+
+impl<'w> EntityView<'w> {
+  pub(crate) fn new(world: &'w World, index: u32) -> Self {
+    Self { world, index }
+  }
+
+  pub fn id(&self) -> Uuid {
+    self.world.id(self.index)
+  }
+
+  pub fn name(&self) -> &'w str {
+    self.world.name(self.index)
+  }
+
+  pub fn has<C: Component>(&self) -> bool {
+    self.get_component::<C>().is_some()
+  }
+
+  /// First component of type `C`.
+  pub fn get_component<C: Component>(&self) -> Option<&'w C> {
+    self.world.get(self.index)
+  }
+
+  /// Every component of type `C`, for types that allow [several](Component::multiple).
+  pub fn get_components<C: Component>(&self) -> impl Iterator<Item = &'w C> + use<'w, C> {
+    self
+      .world
+      .components_of(self.index, TypeId::of::<C>())
+      .filter_map(|c| c.as_any().downcast_ref())
+  }
+
+  /// Change ticks of the first component of type `C`.
+  pub fn component_ticks<C: Component>(&self) -> Option<ComponentTicks> {
+    self.world.ticks(self.index, TypeId::of::<C>())
+  }
+
+  /// Components in the order they were added.
+  pub fn iter(&self) -> impl Iterator<Item = &'w dyn Component> + use<'w> {
+    self.world.components(self.index)
+  }
+
+  pub fn parent(&self) -> Option<EntityView<'w>> {
+    let index = self.world.parent(self.index)?;
+    Some(Self::new(self.world, index))
+  }
+
+  pub fn children(
+    &self,
+  ) -> impl ExactSizeIterator<Item = EntityView<'w>> + DoubleEndedIterator + use<'w> {
+    let world = self.world;
+    world
+      .children(self.index)
+      .map(move |index| Self::new(world, index))
+  }
+
+  /// Calls `f` on this entity and its descendants, depth-first.
+  pub fn visit(&self, f: &mut impl FnMut(EntityView<'w>)) {
+    f(*self);
+    for child in self.children() {
+      child.visit(f);
+    }
+  }
+
+  /// A detached copy of the entity and its descendants with fresh ids; see
+  /// [`Entity::duplicate`].
+  pub fn duplicate(&self) -> Result<Entity> {
+    Ok(Entity::copy_of(serde_json::to_value(self)?)?.0)
+  }
+}
+
+impl std::fmt::Debug for EntityView<'_> {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("EntityView")
+      .field("id", &self.id())
+      .field("name", &self.name())
+      .finish_non_exhaustive()
+  }
+}
+
+/// Written like the [`Entity`] it was spawned from.
+impl Serialize for EntityView<'_> {
+  fn serialize<S: Serializer>(&self, s: S) -> std::result::Result<S::Ok, S::Error> {
+    #[derive(Serialize)]
+    struct EntityFields<'a> {
+      id: Uuid,
+      name: &'a str,
+      components: Vec<&'a dyn Component>,
+      children: Vec<EntityView<'a>>,
+    }
+
+    let fields = EntityFields {
+      id: self.id(),
+      name: self.name(),
+      components: self.iter().collect(),
+      children: self.children().collect(),
+    };
+
+    fields.serialize(s)
+  }
+}
+
+/// An entity in a [`crate::Scene`], borrowed for editing. Component edits send
+/// [`crate::SceneEvent`]s and stamp change ticks like their [`Entity`] counterparts
+/// describe.
+pub struct EntityViewMut<'w> {
+  world: &'w mut World,
+  index: u32,
+}
+
+impl<'w> EntityViewMut<'w> {
+  pub(crate) fn new(world: &'w mut World, index: u32) -> Self {
+    Self { world, index }
+  }
+
+  /// A read-only view of the same entity.
+  pub fn as_view(&self) -> EntityView<'_> {
+    EntityView::new(self.world, self.index)
+  }
+
+  pub fn id(&self) -> Uuid {
+    self.world.id(self.index)
+  }
+
+  pub fn name(&self) -> &str {
+    self.world.name(self.index)
+  }
+
+  pub fn name_mut(&mut self) -> &mut String {
+    self.world.name_mut(self.index)
+  }
+
+  /// Adds `component`, replacing the one of the same type unless the type allows
+  /// [several per entity](Component::multiple).
+  pub fn add_component<C: Component>(&mut self, component: C) -> &mut Self {
+    self.insert_component(component);
+    self
+  }
+
+  /// Like [`EntityViewMut::add_component`], returning the component it replaced.
+  pub fn insert_component<C: Component>(&mut self, component: C) -> Option<C> {
+    if !component.multiple()
+      && let Some(mut existing) = self.get_component_mut::<C>()
+    {
+      return Some(std::mem::replace(&mut *existing, component));
+    }
+    self.world.add(self.index, Box::new(component));
+    None
+  }
+
+  /// [`EntityViewMut::insert_component`] for a component whose type is only known at
+  /// runtime.
+  pub fn insert_boxed(&mut self, component: Box<dyn Component>) -> Option<Box<dyn Component>> {
+    if !component.multiple()
+      && let Some(position) = self
+        .world
+        .position(self.index, component.as_any().type_id())
+    {
+      return Some(self.world.replace(self.index, position, component));
+    }
+    self.world.add(self.index, component);
+    None
+  }
+
+  /// Removes the first component of type `C`.
+  pub fn remove_component<C: Component>(&mut self) -> Option<C> {
+    let position = self.world.position(self.index, TypeId::of::<C>())?;
+    let component: Box<dyn Any> = self.world.remove(self.index, position)?;
+    component.downcast().ok().map(|c| *c)
+  }
+
+  /// Removes the component at `index` in [`EntityViewMut::iter`] order.
+  pub fn remove_component_at(&mut self, index: usize) -> Option<Box<dyn Component>> {
+    self.world.remove(self.index, index)
+  }
+
+  /// Swaps in `component` for the one at `index` in [`EntityViewMut::iter`] order, which
+  /// must be of the same type, and stamps it as changed.
+  pub(crate) fn replace_component_at(
+    &mut self,
+    index: usize,
+    component: Box<dyn Component>,
+  ) -> Box<dyn Component> {
+    self.world.replace(self.index, index, component)
+  }
+
+  pub fn has<C: Component>(&self) -> bool {
+    self.get_component::<C>().is_some()
+  }
+
+  /// First component of type `C`.
+  pub fn get_component<C: Component>(&self) -> Option<&C> {
+    self.world.get(self.index)
+  }
+
+  /// First component of type `C`, stamped as changed once written.
+  pub fn get_component_mut<C: Component>(&mut self) -> Option<Mut<'_, C>> {
+    self.world.get_mut(self.index)
+  }
+
+  /// Change ticks of the first component of type `C`.
+  pub fn component_ticks<C: Component>(&self) -> Option<ComponentTicks> {
+    self.world.ticks(self.index, TypeId::of::<C>())
+  }
+
+  /// Components in the order they were added.
+  pub fn iter(&self) -> impl Iterator<Item = &dyn Component> {
+    self.world.components(self.index)
+  }
+
+  /// Components in the order they were added, all stamped as changed.
+  pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut dyn Component> {
+    self.world.components_mut(self.index, true).into_iter()
+  }
+
+  /// Like [`EntityViewMut::iter_mut`], leaving the change ticks alone; callers stamp the
+  /// components they write with [`EntityViewMut::set_changed_at`].
+  pub(crate) fn iter_mut_untracked(&mut self) -> impl Iterator<Item = &mut dyn Component> {
+    self.world.components_mut(self.index, false).into_iter()
+  }
+
+  /// Stamps the component at `index` in [`EntityViewMut::iter`] order as changed.
+  pub(crate) fn set_changed_at(&mut self, index: usize) {
+    self.world.set_changed(self.index, index);
+  }
+}
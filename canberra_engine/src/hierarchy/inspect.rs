@@ -0,0 +1,218 @@
+use std::ops::RangeInclusive;
+
+use glam::{EulerRot, Quat, Vec2, Vec3};
+
+/// Editor UI for a value; derive it with `#[derive(Inspect)]` or write it by hand for a
+/// custom layout. Components deriving `Component` show their `Inspect` UI.
+pub trait Inspect {
+  /// Draws widgets editing `self`, honoring the `options` that apply to the type. Returns
+  /// whether `self` was changed.
+  fn inspect(&mut self, ui: &mut egui::Ui, options: &InspectOptions) -> bool;
+}
+
+/// Per-field settings from `#[inspect(...)]` attributes.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct InspectOptions {
+  /// Clamp for numbers, in degrees when [`InspectOptions::degrees`] is set.
+  pub range: Option<RangeInclusive<f64>>,
+  /// Drag speed for numbers, per pixel.
+  pub speed: Option<f64>,
+  /// Decimals shown for numbers.
+  pub decimals: Option<usize>,
+  pub suffix: Option<&'static str>,
+  /// Shows a value stored in radians as degrees.
+  pub degrees: bool,
+  /// Edits an `[f32; 3]` or `[f32; 4]` as a color.
+  pub color: bool,
+}
+
+const DRAG_WIDTH: f32 = 60.0;
+/// Labels of the X, Y and Z components of vectors and rotations.
+const AXES: [(&str, egui::Color32); 3] = [
+  ("X", egui::Color32::from_rgb(210, 70, 70)),
+  ("Y", egui::Color32::from_rgb(70, 190, 70)),
+  ("Z", egui::Color32::from_rgb(70, 110, 210)),
+];
+
+/// Drags `value` with `options` applied; returns the new value when it changed.
+fn drag(ui: &mut egui::Ui, value: f64, integer: bool, options: &InspectOptions) -> Option<f64> {
+  let mut shown = if options.degrees {
+    value.to_degrees()
+  } else {
+    value
+  };
+  let default_speed = if integer { 0.1 } else { 0.01 };
+  let mut widget = egui::DragValue::new(&mut shown).speed(options.speed.unwrap_or(default_speed));
+  if let Some(range) = options.range.clone() {
+    widget = widget.range(range);
+  }
+  if integer {
+    widget = widget.max_decimals(0);
+  } else if let Some(decimals) = options.decimals {
+    widget = widget.max_decimals(decimals);
+  }
+  match (options.suffix, options.degrees) {
+    (Some(suffix), _) => widget = widget.suffix(suffix),
+    (None, true) => widget = widget.suffix("°"),
+    (None, false) => {}
+  }
+  let changed = ui
+    .add_sized([DRAG_WIDTH, ui.available_height()], widget)
+    .changed();
+  changed.then(|| {
+    if options.degrees {
+      shown.to_radians()
+    } else {
+      shown
+    }
+  })
+}
+
+macro_rules! impl_inspect_number {
+  ($integer:expr => $($ty:ty),+) => {
+    $(
+      impl Inspect for $ty {
+        fn inspect(&mut self, ui: &mut egui::Ui, options: &InspectOptions) -> bool {
+          let value = drag(ui, *self as f64, $integer, options);
+          if let Some(value) = value {
+            *self = value as $ty;
+          }
+          value.is_some()
+        }
+      }
+    )+
+  };
+}
+
+impl_inspect_number!(false => f32, f64);
+impl_inspect_number!(true => u8, u16, u32, u64, usize, i8, i16, i32, i64, isize);
+
+impl Inspect for bool {
+  fn inspect(&mut self, ui: &mut egui::Ui, _options: &InspectOptions) -> bool {
+    ui.checkbox(self, "").changed()
+  }
+}
+
+impl Inspect for String {
+  fn inspect(&mut self, ui: &mut egui::Ui, _options: &InspectOptions) -> bool {
+    ui.text_edit_singleline(self).changed()
+  }
+}
+
+impl Inspect for Vec2 {
+  fn inspect(&mut self, ui: &mut egui::Ui, options: &InspectOptions) -> bool {
+    ui.horizontal(|ui| self.x.inspect(ui, options) | self.y.inspect(ui, options))
+      .inner
+  }
+}
+
+/// Drags three values in a row, each after its axis label.
+fn axes(ui: &mut egui::Ui, values: [&mut f32; 3], options: &InspectOptions) -> bool {
+  ui.horizontal(|ui| {
+    AXES
+      .into_iter()
+      .zip(values)
+      .fold(false, |changed, ((label, color), value)| {
+        ui.colored_label(color, label);
+        value.inspect(ui, options) | changed
+      })
+  })
+  .inner
+}
+
+impl Inspect for Vec3 {
+  fn inspect(&mut self, ui: &mut egui::Ui, options: &InspectOptions) -> bool {
+    let Vec3 { x, y, z } = self;
+    axes(ui, [x, y, z], options)
+  }
+}
+
+/// Edited as XYZ Euler angles in degrees.
+impl Inspect for Quat {
+  fn inspect(&mut self, ui: &mut egui::Ui, options: &InspectOptions) -> bool {
+    let (mut x, mut y, mut z) = self.to_euler(EulerRot::XYZ);
+    let options = InspectOptions {
+      degrees: true,
+      ..options.clone()
+    };
+    let changed = axes(ui, [&mut x, &mut y, &mut z], &options);
+    // Only write back edits: the Euler round trip is lossy.
+    if changed {
+      *self = Quat::from_euler(EulerRot::XYZ, x, y, z);
+    }
+    changed
+  }
+}
+
+/// One element under another, each with the same options.
+impl<T: Inspect> Inspect for Vec<T> {
+  fn inspect(&mut self, ui: &mut egui::Ui, options: &InspectOptions) -> bool {
+    ui.vertical(|ui| {
+      self
+        .iter_mut()
+        .fold(false, |changed, v| v.inspect(ui, options) | changed)
+    })
+    .inner
+  }
+}
+
+impl Inspect for [f32; 3] {
+  fn inspect(&mut self, ui: &mut egui::Ui, options: &InspectOptions) -> bool {
+    if options.color {
+      ui.color_edit_button_rgb(self).changed()
+    } else {
+      ui.horizontal(|ui| {
+        self
+          .iter_mut()
+          .fold(false, |changed, v| v.inspect(ui, options) | changed)
+      })
+      .inner
+    }
+  }
+}
+
+impl Inspect for [f32; 4] {
+  fn inspect(&mut self, ui: &mut egui::Ui, options: &InspectOptions) -> bool {
+    if options.color {
+      ui.color_edit_button_rgba_unmultiplied(self).changed()
+    } else {
+      ui.horizontal(|ui| {
+        self
+          .iter_mut()
+          .fold(false, |changed, v| v.inspect(ui, options) | changed)
+      })
+      .inner
+    }
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::components::{GeoPosition, Lod, Material, Mesh, Transform};
+
+  /// Draws `value` in a headless frame and returns whether it reported a change.
+  fn draw(value: &mut impl Inspect) -> bool {
+    let mut changed = false;
+    let _ = egui::Context::default().run_ui(egui::RawInput::default(), |ui| {
+      changed = value.inspect(ui, &InspectOptions::default());
+    });
+    changed
+  }
+
+  #[test]
+  fn derived_components_draw_unchanged() {
+    let mut transform = Transform {
+      rotation: Quat::from_rotation_y(0.3),
+      ..Default::default()
+    };
+    let before = transform.clone();
+    assert!(!draw(&mut transform));
+    assert_eq!(transform, before);
+    assert!(!draw(&mut Material::default()));
+    assert!(!draw(
+      &mut Lod::generate(&Mesh::cube(), &[(1.0, 0.1)]).unwrap()
+    ));
+    assert!(!draw(&mut GeoPosition::new(149.13, -35.28, 0.0)));
+  }
+}
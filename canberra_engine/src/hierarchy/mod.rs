@@ -1,4 +1,27 @@
+mod change;
 mod component;
 mod entity;
+mod entity_ref;
+mod entity_view;
+mod inspect;
+mod query;
+mod registry;
+mod world;
 
-pub use self::{component::Component, entity::Entity};
+pub use self::{
+  change::{ComponentTicks, EventCursor, Mut, SceneEvent, SceneEvents},
+  component::Component,
+  entity::Entity,
+  entity_ref::{DanglingRef, EntityRef},
+  entity_view::{EntityView, EntityViewMut},
+  inspect::{Inspect, InspectOptions},
+  query::{
+    Added, Changed, Query, QueryFilter, QueryTerm, ReadOnlyQuery, ReadOnlyTerm, With, Without,
+  },
+  registry::{ComponentInfo, ComponentRegistration, ComponentRegistry, GLOBAL_COMPONENT_REGISTRY},
+};
+pub(crate) use self::{
+  entity::remap_ids,
+  entity_ref::{entity_refs, set_choices},
+  world::World,
+};
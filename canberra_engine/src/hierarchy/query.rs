@@ -0,0 +1,474 @@
+use std::{
+  any::{Any, TypeId},
+  marker::PhantomData,
+};
+
+use uuid::Uuid;
+
+use super::{
+  change::{ComponentTicks, Mut},
+  world::{AnyColumn, Column, Columns, World},
+};
+use crate::Component;
+
+/// One element of a [`Query`]: `&C`, `&mut C`, `Option<&C>`, `Option<&mut C>`, or `Uuid`
+/// for the entity's id. Terms see the entity's first component of their type. Plain
+/// references skip entities lacking the component; `&mut` yields a [`Mut`], which stamps
+/// the component as changed when written through.
+pub trait QueryTerm {
+  type Item<'w>;
+  /// What the term borrows from the world for the length of a query.
+  type Fetch<'w>;
+
+  /// Component type the term borrows; unique within a query.
+  fn key() -> TypeId;
+
+  /// Whether entities lacking the [`QueryTerm::key`] component are skipped.
+  fn required() -> bool;
+
+  /// Borrows the term's column for a world with `len` entity indices, whose writes are
+  /// stamped with `tick`.
+  fn prepare(column: Option<&mut Box<dyn AnyColumn>>, len: usize, tick: u64) -> Self::Fetch<'_>;
+
+  fn fetch<'w>(fetch: &mut Self::Fetch<'w>, index: u32, id: Uuid) -> Option<Self::Item<'w>>;
+}
+
+/// A [`QueryTerm`] that only reads, so it can be fetched from a shared world.
+pub trait ReadOnlyTerm: QueryTerm {
+  type State<'w>: Copy;
+
+  fn state(world: &World) -> Self::State<'_>;
+
+  fn get<'w>(state: Self::State<'w>, index: u32, id: Uuid) -> Option<Self::Item<'w>>;
+}
+
+fn column<C: Component>(column: Option<&mut Box<dyn AnyColumn>>) -> Option<&Column<C>> {
+  let column: &dyn Any = &**column?;
+  column.downcast_ref()
+}
+
+/// Mutable borrows of a column's first rows by entity index, and the write tick.
+pub struct FetchMut<'w, C> {
+  rows: Vec<Option<(&'w mut C, &'w mut ComponentTicks)>>,
+  tick: u64,
+}
+
+fn column_mut<C: Component>(
+  column: Option<&mut Box<dyn AnyColumn>>,
+  len: usize,
+  tick: u64,
+) -> FetchMut<'_, C> {
+  let rows = column
+    .and_then(|column| {
+      let column: &mut dyn Any = &mut **column;
+      column.downcast_mut::<Column<C>>()
+    })
+    .map_or_else(Vec::new, |column| column.by_entity_mut(len));
+  FetchMut { rows, tick }
+}
+
+fn take_mut<'w, C>(fetch: &mut FetchMut<'w, C>, index: u32) -> Option<Mut<'w, C>> {
+  let (component, ticks) = fetch.rows.get_mut(index as usize)?.take()?;
+  Some(Mut::new(component, ticks, fetch.tick))
+}
+
+impl<C: Component> QueryTerm for &C {
+  type Item<'w> = &'w C;
+  type Fetch<'w> = Option<&'w Column<C>>;
+
+  fn key() -> TypeId {
+    TypeId::of::<C>()
+  }
+
+  fn required() -> bool {
+    true
+  }
+
+  fn prepare(column: Option<&mut Box<dyn AnyColumn>>, _: usize, _: u64) -> Option<&Column<C>> {
+    self::column(column)
+  }
+
+  fn fetch<'w>(fetch: &mut Self::Fetch<'w>, index: u32, _: Uuid) -> Option<Self::Item<'w>> {
+    (*fetch)?.get(index)
+  }
+}
+
+impl<C: Component> ReadOnlyTerm for &C {
+  type State<'w> = Option<&'w Column<C>>;
+
+  fn state(world: &World) -> Option<&Column<C>> {
+    world.column()
+  }
+
+  fn get<'w>(state: Self::State<'w>, index: u32, _: Uuid) -> Option<Self::Item<'w>> {
+    state?.get(index)
+  }
+}
+
+impl<C: Component> QueryTerm for &mut C {
+  type Item<'w> = Mut<'w, C>;
+  type Fetch<'w> = FetchMut<'w, C>;
+
+  fn key() -> TypeId {
+    TypeId::of::<C>()
+  }
+
+  fn required() -> bool {
+    true
+  }
+
+  fn prepare(column: Option<&mut Box<dyn AnyColumn>>, len: usize, tick: u64) -> Self::Fetch<'_> {
+    column_mut(column, len, tick)
+  }
+
+  fn fetch<'w>(fetch: &mut Self::Fetch<'w>, index: u32, _: Uuid) -> Option<Self::Item<'w>> {
+    take_mut(fetch, index)
+  }
+}
+
+impl<C: Component> QueryTerm for Option<&C> {
+  type Item<'w> = Option<&'w C>;
+  type Fetch<'w> = Option<&'w Column<C>>;
+
+  fn key() -> TypeId {
+    TypeId::of::<C>()
+  }
+
+  fn required() -> bool {
+    false
+  }
+
+  fn prepare(column: Option<&mut Box<dyn AnyColumn>>, _: usize, _: u64) -> Option<&Column<C>> {
+    self::column(column)
+  }
+
+  fn fetch<'w>(fetch: &mut Self::Fetch<'w>, index: u32, _: Uuid) -> Option<Self::Item<'w>> {
+    Some(fetch.and_then(|column| column.get(index)))
+  }
+}
+
+impl<C: Component> ReadOnlyTerm for Option<&C> {
+  type State<'w> = Option<&'w Column<C>>;
+
+  fn state(world: &World) -> Option<&Column<C>> {
+    world.column()
+  }
+
+  fn get<'w>(state: Self::State<'w>, index: u32, _: Uuid) -> Option<Self::Item<'w>> {
+    Some(state.and_then(|column| column.get(index)))
+  }
+}
+
+impl<C: Component> QueryTerm for Option<&mut C> {
+  type Item<'w> = Option<Mut<'w, C>>;
+  type Fetch<'w> = FetchMut<'w, C>;
+
+  fn key() -> TypeId {
+    TypeId::of::<C>()
+  }
+
+  fn required() -> bool {
+    false
+  }
+
+  fn prepare(column: Option<&mut Box<dyn AnyColumn>>, len: usize, tick: u64) -> Self::Fetch<'_> {
+    column_mut(column, len, tick)
+  }
+
+  fn fetch<'w>(fetch: &mut Self::Fetch<'w>, index: u32, _: Uuid) -> Option<Self::Item<'w>> {
+    Some(take_mut(fetch, index))
+  }
+}
+
+impl QueryTerm for Uuid {
+  type Item<'w> = Uuid;
+  type Fetch<'w> = ();
+
+  fn key() -> TypeId {
+    TypeId::of::<Uuid>()
+  }
+
+  fn required() -> bool {
+    false
+  }
+
+  fn prepare(_: Option<&mut Box<dyn AnyColumn>>, _: usize, _: u64) {}
+
+  fn fetch<'w>(_: &mut Self::Fetch<'w>, _: u32, id: Uuid) -> Option<Self::Item<'w>> {
+    Some(id)
+  }
+}
+
+impl ReadOnlyTerm for Uuid {
+  type State<'w> = ();
+
+  fn state(_: &World) {}
+
+  fn get<'w>(_: Self::State<'w>, _: u32, id: Uuid) -> Option<Self::Item<'w>> {
+    Some(id)
+  }
+}
+
+/// What [`crate::Scene::query_mut`] fetches per entity: a [`QueryTerm`] or a tuple of up
+/// to eight. A component type may appear only once; repeating one panics.
+pub trait Query {
+  type Item<'w>;
+  type Fetch<'w>;
+
+  /// Adds the component types an entity must have to match.
+  fn required(out: &mut Vec<TypeId>);
+
+  /// Borrows the columns of the terms, for a world with `len` entity indices whose writes
+  /// are stamped with `tick`.
+  fn prepare(columns: &mut Columns, len: usize, tick: u64) -> Self::Fetch<'_>;
+
+  fn fetch<'w>(fetch: &mut Self::Fetch<'w>, index: u32, id: Uuid) -> Option<Self::Item<'w>>;
+}
+
+/// A [`Query`] of read-only terms, usable with [`crate::Scene::query`].
+pub trait ReadOnlyQuery: Query {
+  type State<'w>: Copy;
+
+  fn state(world: &World) -> Self::State<'_>;
+
+  fn get<'w>(state: Self::State<'w>, index: u32, id: Uuid) -> Option<Self::Item<'w>>;
+}
+
+impl<T: QueryTerm> Query for T {
+  type Item<'w> = T::Item<'w>;
+  type Fetch<'w> = T::Fetch<'w>;
+
+  fn required(out: &mut Vec<TypeId>) {
+    if T::required() {
+      out.push(T::key());
+    }
+  }
+
+  fn prepare(columns: &mut Columns, len: usize, tick: u64) -> T::Fetch<'_> {
+    T::prepare(columns.get_mut(&T::key()), len, tick)
+  }
+
+  fn fetch<'w>(fetch: &mut T::Fetch<'w>, index: u32, id: Uuid) -> Option<T::Item<'w>> {
+    <T as QueryTerm>::fetch(fetch, index, id)
+  }
+}
+
+impl<T: ReadOnlyTerm> ReadOnlyQuery for T {
+  type State<'w> = T::State<'w>;
+
+  fn state(world: &World) -> T::State<'_> {
+    <T as ReadOnlyTerm>::state(world)
+  }
+
+  fn get<'w>(state: T::State<'w>, index: u32, id: Uuid) -> Option<T::Item<'w>> {
+    <T as ReadOnlyTerm>::get(state, index, id)
+  }
+}
+
+macro_rules! impl_query {
+  ($($term:ident),+) => {
+    #[allow(non_snake_case)]
+    impl<$($term: QueryTerm),+> Query for ($($term,)+) {
+      type Item<'w> = ($($term::Item<'w>,)+);
+      type Fetch<'w> = ($($term::Fetch<'w>,)+);
+
+      fn required(out: &mut Vec<TypeId>) {
+        $(<$term as Query>::required(out);)+
+      }
+
+      fn prepare(columns: &mut Columns, len: usize, tick: u64) -> Self::Fetch<'_> {
+        let [$($term),+] = columns.get_disjoint_mut([$(&$term::key()),+]);
+        ($($term::prepare($term, len, tick),)+)
+      }
+
+      fn fetch<'w>(
+        fetch: &mut Self::Fetch<'w>,
+        index: u32,
+        id: Uuid,
+      ) -> Option<Self::Item<'w>> {
+        let ($($term,)+) = fetch;
+        Some(($(<$term as QueryTerm>::fetch($term, index, id)?,)+))
+      }
+    }
+
+    #[allow(non_snake_case)]
+    impl<$($term: ReadOnlyTerm),+> ReadOnlyQuery for ($($term,)+) {
+      type State<'w> = ($($term::State<'w>,)+);
+
+      fn state(world: &World) -> Self::State<'_> {
+        ($(<$term as ReadOnlyTerm>::state(world),)+)
+      }
+
+      fn get<'w>(state: Self::State<'w>, index: u32, id: Uuid) -> Option<Self::Item<'w>> {
+        let ($($term,)+) = state;
+        Some(($(<$term as ReadOnlyTerm>::get($term, index, id)?,)+))
+      }
+    }
+  };
+}
+
+impl_query!(A);
+impl_query!(A, B);
+impl_query!(A, B, C);
+impl_query!(A, B, C, D);
+impl_query!(A, B, C, D, E);
+impl_query!(A, B, C, D, E, F);
+impl_query!(A, B, C, D, E, F, G);
+impl_query!(A, B, C, D, E, F, G, H);
+
+/// Narrows a query without fetching anything: [`Added`], [`Changed`], [`With`],
+/// [`Without`], or a tuple of up to four, all of which must pass.
+pub trait QueryFilter {
+  /// Whether an entity passes given the ticks of its first component of each type, for
+  /// changes after tick `since`.
+  fn matches(ticks: &dyn Fn(TypeId) -> Option<ComponentTicks>, since: u64) -> bool;
+}
+
+impl QueryFilter for () {
+  fn matches(_: &dyn Fn(TypeId) -> Option<ComponentTicks>, _: u64) -> bool {
+    true
+  }
+}
+
+/// Passes entities whose `C` was added since the reader last ran.
+pub struct Added<C>(PhantomData<C>);
+
+/// Passes entities whose `C` was added or written since the reader last ran.
+pub struct Changed<C>(PhantomData<C>);
+
+/// Passes entities that have a `C`.
+pub struct With<C>(PhantomData<C>);
+
+/// Passes entities that lack a `C`.
+pub struct Without<C>(PhantomData<C>);
+
+impl<C: Component> QueryFilter for Added<C> {
+  fn matches(ticks: &dyn Fn(TypeId) -> Option<ComponentTicks>, since: u64) -> bool {
+    ticks(TypeId::of::<C>()).is_some_and(|t| t.is_added(since))
+  }
+}
+
+impl<C: Component> QueryFilter for Changed<C> {
+  fn matches(ticks: &dyn Fn(TypeId) -> Option<ComponentTicks>, since: u64) -> bool {
+    ticks(TypeId::of::<C>()).is_some_and(|t| t.is_changed(since))
+  }
+}
+
+impl<C: Component> QueryFilter for With<C> {
+  fn matches(ticks: &dyn Fn(TypeId) -> Option<ComponentTicks>, _: u64) -> bool {
+    ticks(TypeId::of::<C>()).is_some()
+  }
+}
+
+impl<C: Component> QueryFilter for Without<C> {
+  fn matches(ticks: &dyn Fn(TypeId) -> Option<ComponentTicks>, _: u64) -> bool {
+    ticks(TypeId::of::<C>()).is_none()
+  }
+}
+
+macro_rules! impl_query_filter {
+  ($($filter:ident),+) => {
+    impl<$($filter: QueryFilter),+> QueryFilter for ($($filter,)+) {
+      fn matches(ticks: &dyn Fn(TypeId) -> Option<ComponentTicks>, since: u64) -> bool {
+        $($filter::matches(ticks, since))&&+
+      }
+    }
+  };
+}
+
+impl_query_filter!(A);
+impl_query_filter!(A, B);
+impl_query_filter!(A, B, C);
+impl_query_filter!(A, B, C, D);
+
+#[cfg(test)]
+mod tests {
+  use glam::Vec3;
+
+  use super::*;
+  use crate::{
+    Entity, Scene,
+    components::{Properties, Transform},
+  };
+
+  /// A scene with one entity holding a `Transform` and one holding `Properties`, past the
+  /// frame they were spawned in.
+  fn scene() -> (Scene, Uuid, Uuid) {
+    let mut scene = Scene::new();
+    let mut moving = Entity::new("moving");
+    moving.add_component(Transform::default());
+    let mut annotated = Entity::new("annotated");
+    annotated.add_component(Properties::new());
+    let ids = (moving.id(), annotated.id());
+    scene.add(moving);
+    scene.add(annotated);
+    scene.end_frame();
+    (scene, ids.0, ids.1)
+  }
+
+  fn ids<F: QueryFilter>(scene: &Scene) -> Vec<Uuid> {
+    scene.query_filtered::<Uuid, F>().collect()
+  }
+
+  #[test]
+  fn with_and_without_test_presence() {
+    let (scene, moving, annotated) = scene();
+    assert_eq!(ids::<With<Transform>>(&scene), [moving]);
+    assert_eq!(ids::<Without<Transform>>(&scene), [annotated]);
+    assert!(ids::<(With<Transform>, With<Properties>)>(&scene).is_empty());
+  }
+
+  #[test]
+  fn added_sees_components_added_since_the_last_frame() {
+    let (mut scene, _, annotated) = scene();
+    assert!(ids::<Added<Transform>>(&scene).is_empty());
+
+    scene
+      .find_mut(annotated)
+      .unwrap()
+      .add_component(Transform::default());
+    assert_eq!(ids::<Added<Transform>>(&scene), [annotated]);
+    assert_eq!(ids::<Changed<Transform>>(&scene), [annotated]);
+
+    scene.end_frame();
+    assert!(ids::<Added<Transform>>(&scene).is_empty());
+  }
+
+  #[test]
+  fn changed_sees_writes_not_mutable_borrows() {
+    let (mut scene, moving, _) = scene();
+    for transform in scene.query_mut::<&mut Transform>() {
+      assert_eq!(transform.scale, Vec3::ONE);
+    }
+    let mut entity = scene.find_mut(moving).unwrap();
+    let _ = entity.get_component_mut::<Transform>().unwrap().position;
+    assert!(ids::<Changed<Transform>>(&scene).is_empty());
+
+    for mut transform in scene.query_mut::<&mut Transform>() {
+      transform.position = Vec3::X;
+    }
+    assert_eq!(ids::<Changed<Transform>>(&scene), [moving]);
+    assert!(ids::<Added<Transform>>(&scene).is_empty());
+  }
+
+  #[test]
+  fn scenes_keep_separate_change_ticks() {
+    let (mut scene, moving, _) = scene();
+    let tick = scene.change_tick();
+    let mut other = Scene::new();
+    for _ in 0..10 {
+      other.end_frame();
+    }
+    assert_eq!(scene.change_tick(), tick);
+
+    // Changes to the other scene never show up here, and this scene's window is intact.
+    other.add(Entity::new("other"));
+    scene
+      .find_mut(moving)
+      .unwrap()
+      .get_component_mut::<Transform>()
+      .unwrap()
+      .position = Vec3::Y;
+    assert_eq!(ids::<Changed<Transform>>(&scene), [moving]);
+  }
+}
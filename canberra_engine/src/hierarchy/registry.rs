@@ -0,0 +1,150 @@
+use std::{any::TypeId, sync::LazyLock};
+
+use crate::Component;
+
+/// Registry of every component type derived with `#[derive(Component)]`, from this crate
+/// or any crate linked into the application, except those marked `#[component(hidden)]`.
+pub static GLOBAL_COMPONENT_REGISTRY: LazyLock<ComponentRegistry> =
+  LazyLock::new(ComponentRegistry::collect);
+
+/// Static record submitted by `#[derive(Component)]`, carrying what the registry lists
+/// so that no instance needs to be created to read it.
+#[doc(hidden)]
+pub struct ComponentRegistration {
+  name: &'static str,
+  category: &'static str,
+  multiple: bool,
+  type_id: fn() -> TypeId,
+  create: fn() -> Box<dyn Component>,
+}
+
+impl ComponentRegistration {
+  pub const fn new<C: Component>(
+    name: &'static str,
+    category: &'static str,
+    multiple: bool,
+    create: fn() -> Box<dyn Component>,
+  ) -> Self {
+    Self {
+      name,
+      category,
+      multiple,
+      type_id: TypeId::of::<C>,
+      create,
+    }
+  }
+}
+
+inventory::collect!(ComponentRegistration);
+
+/// A registered component type.
+#[derive(Debug, Clone, Copy)]
+pub struct ComponentInfo {
+  /// [`Component::name`] of the type.
+  pub name: &'static str,
+  /// Groups related components in menus, e.g. "Camera" or "Rendering".
+  pub category: &'static str,
+  pub type_id: TypeId,
+  /// Whether an entity may carry several; see [`Component::multiple`].
+  pub multiple: bool,
+  create: fn() -> Box<dyn Component>,
+}
+
+impl ComponentInfo {
+  /// A new instance with the type's default values.
+  pub fn create(&self) -> Box<dyn Component> {
+    (self.create)()
+  }
+}
+
+/// Component types that can be listed and created by name at runtime, for the editor's
+/// "Add Component" menu and for scripts.
+#[derive(Debug, Clone)]
+pub struct ComponentRegistry {
+  /// Sorted by category, then name.
+  infos: Vec<ComponentInfo>,
+}
+
+impl ComponentRegistry {
+  fn collect() -> Self {
+    let mut infos: Vec<_> = inventory::iter::<ComponentRegistration>
+      .into_iter()
+      .map(|registration| ComponentInfo {
+        name: registration.name,
+        category: registration.category,
+        type_id: (registration.type_id)(),
+        multiple: registration.multiple,
+        create: registration.create,
+      })
+      .collect();
+    infos.sort_by_key(|info| (info.category, info.name));
+    Self { infos }
+  }
+
+  /// Registered components, sorted by category, then name.
+  pub fn iter(&self) -> impl Iterator<Item = &ComponentInfo> {
+    self.infos.iter()
+  }
+
+  /// Distinct categories, sorted.
+  pub fn categories(&self) -> impl Iterator<Item = &'static str> {
+    let mut last = None;
+    self
+      .infos
+      .iter()
+      .map(|info| info.category)
+      .filter(move |&category| last.replace(category) != Some(category))
+  }
+
+  pub fn get(&self, name: &str) -> Option<&ComponentInfo> {
+    self.infos.iter().find(|info| info.name == name)
+  }
+
+  pub fn get_by_type(&self, type_id: TypeId) -> Option<&ComponentInfo> {
+    self.infos.iter().find(|info| info.type_id == type_id)
+  }
+
+  /// A default instance of the component named `name`.
+  pub fn create(&self, name: &str) -> Option<Box<dyn Component>> {
+    self.get(name).map(ComponentInfo::create)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::components::{Children, Lod, Mesh, Parent, PrefabInstance, Transform};
+
+  #[test]
+  fn derived_components_are_registered_by_default() {
+    let registry = &*GLOBAL_COMPONENT_REGISTRY;
+    let transform = registry.get("Transform").unwrap();
+    assert_eq!(transform.category, "Spatial");
+    assert_eq!(transform.type_id, TypeId::of::<Transform>());
+    assert!(!transform.multiple);
+    assert_eq!(
+      registry.get_by_type(TypeId::of::<Lod>()).unwrap().name,
+      "Lod"
+    );
+    assert!(registry.categories().any(|c| c == "Rendering"));
+  }
+
+  #[test]
+  fn hidden_components_are_left_out() {
+    let registry = &*GLOBAL_COMPONENT_REGISTRY;
+    for type_id in [
+      TypeId::of::<Parent>(),
+      TypeId::of::<Children>(),
+      TypeId::of::<PrefabInstance>(),
+    ] {
+      assert!(registry.get_by_type(type_id).is_none());
+    }
+  }
+
+  #[test]
+  fn create_uses_the_constructor() {
+    let mesh = GLOBAL_COMPONENT_REGISTRY.create("Mesh").unwrap();
+    let mesh = mesh.as_any().downcast_ref::<Mesh>().unwrap();
+    assert_eq!(mesh.indices().len(), Mesh::cube().indices().len());
+  }
+}
@@ -0,0 +1,856 @@
+use std::{
+  any::{Any, TypeId},
+  collections::HashMap,
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use uuid::Uuid;
+
+use super::{
+  change::{ComponentTicks, Mut, SceneEvent, SceneEvents},
+  entity_view::EntityView,
+  query::{Query, QueryFilter, ReadOnlyQuery},
+};
+use crate::{
+  Component, Entity,
+  components::{Children, Parent},
+};
+
+/// Marks an entity without a component of the column's type in [`Column::first`].
+const NONE: u32 = u32::MAX;
+
+/// Every component of one type, packed together with its change ticks and owner.
+pub struct Column<C> {
+  values: Vec<C>,
+  ticks: Vec<ComponentTicks>,
+  /// Entity index owning each row.
+  owners: Vec<u32>,
+  /// Row of each entity's first component of this type, by entity index; [`NONE`] for
+  /// entities without one.
+  first: Vec<u32>,
+}
+
+impl<C: Component> Column<C> {
+  fn new() -> Self {
+    Self {
+      values: Vec::new(),
+      ticks: Vec::new(),
+      owners: Vec::new(),
+      first: Vec::new(),
+    }
+  }
+
+  /// The first component of the entity at `entity`.
+  pub(crate) fn get(&self, entity: u32) -> Option<&C> {
+    Some(&self.values[self.row(entity)?])
+  }
+
+  pub(crate) fn get_mut(&mut self, entity: u32) -> Option<(&mut C, &mut ComponentTicks)> {
+    let row = self.row(entity)?;
+    Some((&mut self.values[row], &mut self.ticks[row]))
+  }
+
+  /// Each entity's first component and its ticks, by entity index for `len` indices.
+  pub(crate) fn by_entity_mut(&mut self, len: usize) -> Vec<Option<(&mut C, &mut ComponentTicks)>> {
+    let mut out: Vec<_> = std::iter::repeat_with(|| None).take(len).collect();
+    let rows = self
+      .values
+      .iter_mut()
+      .zip(&mut self.ticks)
+      .zip(&self.owners);
+    for (row, (item, &owner)) in rows.enumerate() {
+      if self.first[owner as usize] as usize == row {
+        out[owner as usize] = Some(item);
+      }
+    }
+    out
+  }
+}
+
+/// A [`Column`] of any component type.
+pub trait AnyColumn: Any {
+  fn len(&self) -> usize;
+
+  /// Entities with a component in the column, each once, in row order.
+  fn entities(&self) -> Vec<u32>;
+
+  /// Row of the first component of the entity at `entity`.
+  fn row(&self, entity: u32) -> Option<usize>;
+
+  fn component(&self, row: usize) -> &dyn Component;
+
+  fn ticks(&self, row: usize) -> ComponentTicks;
+
+  fn component_mut(&mut self, row: usize) -> (&mut dyn Component, &mut ComponentTicks);
+
+  /// The components at `rows`, which must be distinct, in the same order.
+  fn components_mut(&mut self, rows: &[usize]) -> Vec<(&mut dyn Component, &mut ComponentTicks)>;
+
+  /// Appends `component`, which must be of the column's type, and returns its row.
+  fn push(&mut self, owner: u32, component: Box<dyn Component>, ticks: ComponentTicks) -> usize;
+
+  /// Swaps `component`, which must be of the column's type, in for the one at `row`.
+  fn replace(&mut self, row: usize, component: Box<dyn Component>) -> Box<dyn Component>;
+
+  /// Removes the component at `row` by moving the last row into its place, and returns it
+  /// along with the owner of the moved row, if one moved.
+  fn swap_remove(&mut self, row: usize) -> (Box<dyn Component>, Option<u32>);
+
+  /// Records `row` as the first of the entity at `entity`.
+  fn set_first(&mut self, entity: u32, row: Option<usize>);
+}
+
+impl<C: Component> AnyColumn for Column<C> {
+  fn len(&self) -> usize {
+    self.values.len()
+  }
+
+  fn entities(&self) -> Vec<u32> {
+    self
+      .owners
+      .iter()
+      .enumerate()
+      .filter(|&(row, &owner)| self.first[owner as usize] as usize == row)
+      .map(|(_, &owner)| owner)
+      .collect()
+  }
+
+  fn row(&self, entity: u32) -> Option<usize> {
+    let row = *self.first.get(entity as usize)?;
+    (row != NONE).then_some(row as usize)
+  }
+
+  fn component(&self, row: usize) -> &dyn Component {
+    &self.values[row]
+  }
+
+  fn ticks(&self, row: usize) -> ComponentTicks {
+    self.ticks[row]
+  }
+
+  fn component_mut(&mut self, row: usize) -> (&mut dyn Component, &mut ComponentTicks) {
+    (&mut self.values[row], &mut self.ticks[row])
+  }
+
+  fn components_mut(&mut self, rows: &[usize]) -> Vec<(&mut dyn Component, &mut ComponentTicks)> {
+    let mut order: Vec<usize> = (0..rows.len()).collect();
+    order.sort_by_key(|&i| rows[i]);
+    let mut out: Vec<_> = std::iter::repeat_with(|| None).take(rows.len()).collect();
+    let mut values = self.values.as_mut_slice();
+    let mut ticks = self.ticks.as_mut_slice();
+    let mut start = 0;
+    for i in order {
+      let (value, rest) = std::mem::take(&mut values)[rows[i] - start..]
+        .split_first_mut()
+        .expect("row in column");
+      values = rest;
+      let (tick, rest) = std::mem::take(&mut ticks)[rows[i] - start..]
+        .split_first_mut()
+        .expect("row in column");
+      ticks = rest;
+      start = rows[i] + 1;
+      out[i] = Some((value as &mut dyn Component, tick));
+    }
+    out.into_iter().flatten().collect()
+  }
+
+  fn push(&mut self, owner: u32, component: Box<dyn Component>, ticks: ComponentTicks) -> usize {
+    let component: Box<dyn Any> = component;
+    let row = self.values.len();
+    self.values.push(
+      *component
+        .downcast()
+        .expect("component of the column's type"),
+    );
+    self.ticks.push(ticks);
+    self.owners.push(owner);
+    let owner = owner as usize;
+    if self.first.len() <= owner {
+      self.first.resize(owner + 1, NONE);
+    }
+    if self.first[owner] == NONE {
+      self.first[owner] = row as u32;
+    }
+    row
+  }
+
+  fn replace(&mut self, row: usize, component: Box<dyn Component>) -> Box<dyn Component> {
+    let component: Box<dyn Any> = component;
+    let component = *component
+      .downcast()
+      .expect("component of the column's type");
+    Box::new(std::mem::replace(&mut self.values[row], component))
+  }
+
+  fn swap_remove(&mut self, row: usize) -> (Box<dyn Component>, Option<u32>) {
+    let owner = self.owners[row] as usize;
+    if self.first[owner] as usize == row {
+      self.first[owner] = NONE;
+    }
+    let last = self.values.len() - 1;
+    let value = self.values.swap_remove(row);
+    self.ticks.swap_remove(row);
+    self.owners.swap_remove(row);
+    let moved = (row != last).then(|| self.owners[row]);
+    if let Some(moved) = moved
+      && self.first[moved as usize] as usize == last
+    {
+      self.first[moved as usize] = row as u32;
+    }
+    (Box::new(value), moved)
+  }
+
+  fn set_first(&mut self, entity: u32, row: Option<usize>) {
+    if let Some(first) = self.first.get_mut(entity as usize) {
+      *first = row.map_or(NONE, |row| row as u32);
+    }
+  }
+}
+
+/// Makes the [`Column`] a component type is stored in; implemented for every
+/// [`Component`].
+pub trait ComponentStorage {
+  fn new_column(&self) -> Box<dyn AnyColumn>;
+}
+
+impl<C: Component> ComponentStorage for C {
+  fn new_column(&self) -> Box<dyn AnyColumn> {
+    Box::new(Column::<C>::new())
+  }
+}
+
+/// Columns by component type.
+pub type Columns = HashMap<TypeId, Box<dyn AnyColumn>>;
+
+/// An entity's identity and the order of its components.
+struct Meta {
+  id: Uuid,
+  name: String,
+  /// Components in the order they were added, by type and row. [`Parent`] and
+  /// [`Children`] are kept by the world and left out.
+  components: Vec<(TypeId, usize)>,
+}
+
+/// Entity and component storage of a [`crate::Scene`]. Entities are indices into the
+/// world, found by id; components live in one [`Column`] per type; the hierarchy is
+/// made of [`Parent`] and [`Children`] components.
+pub struct World {
+  metas: Vec<Option<Meta>>,
+  /// Indices of despawned entities, reused by later spawns.
+  free: Vec<u32>,
+  indices: HashMap<Uuid, u32>,
+  columns: Columns,
+  roots: Vec<u32>,
+  events: SceneEvents,
+  /// Stamps component writes. Advanced after every system run and every frame, so a
+  /// tick orders writes against the runs that might have seen them.
+  tick: u64,
+}
+
+impl World {
+  pub(crate) fn new() -> Self {
+    Self {
+      metas: Vec::new(),
+      free: Vec::new(),
+      indices: HashMap::new(),
+      columns: Columns::new(),
+      roots: Vec::new(),
+      events: SceneEvents::default(),
+      tick: 1,
+    }
+  }
+
+  /// The tick component writes are currently stamped with.
+  pub(crate) fn change_tick(&self) -> u64 {
+    self.tick
+  }
+
+  /// Moves writes after this call past every tick handed out before it.
+  pub(crate) fn advance_change_tick(&mut self) {
+    self.tick += 1;
+  }
+
+  pub(crate) fn index(&self, id: Uuid) -> Option<u32> {
+    self.indices.get(&id).copied()
+  }
+
+  fn meta(&self, index: u32) -> &Meta {
+    self.metas[index as usize].as_ref().expect("live entity")
+  }
+
+  fn meta_mut(&mut self, index: u32) -> &mut Meta {
+    self.metas[index as usize].as_mut().expect("live entity")
+  }
+
+  pub(crate) fn id(&self, index: u32) -> Uuid {
+    self.meta(index).id
+  }
+
+  pub(crate) fn name(&self, index: u32) -> &str {
+    &self.meta(index).name
+  }
+
+  pub(crate) fn name_mut(&mut self, index: u32) -> &mut String {
+    &mut self.meta_mut(index).name
+  }
+
+  pub(crate) fn roots(&self) -> &[u32] {
+    &self.roots
+  }
+
+  pub(crate) fn parent(&self, index: u32) -> Option<u32> {
+    self.index(self.get::<Parent>(index)?.entity())
+  }
+
+  pub(crate) fn children(
+    &self,
+    index: u32,
+  ) -> impl ExactSizeIterator<Item = u32> + DoubleEndedIterator + '_ {
+    let children = self
+      .get::<Children>(index)
+      .map_or(&[][..], Children::as_slice);
+    children.iter().map(|id| self.indices[id])
+  }
+
+  /// Every entity, roots and descendants depth-first.
+  pub(crate) fn descendants(&self) -> impl Iterator<Item = u32> + '_ {
+    let mut stack: Vec<u32> = self.roots.iter().rev().copied().collect();
+    std::iter::from_fn(move || {
+      let index = stack.pop()?;
+      stack.extend(self.children(index).rev());
+      Some(index)
+    })
+  }
+
+  pub(crate) fn events(&self) -> &SceneEvents {
+    &self.events
+  }
+
+  pub(crate) fn end_frame(&mut self) {
+    self.events.end_frame();
+  }
+
+  /// Adds `entity` and its descendants under `parent`, or as a root, at position `at`
+  /// among its siblings, sending [`SceneEvent::Spawned`] for each. An id already in the
+  /// world is replaced by a fresh one.
+  pub(crate) fn spawn(&mut self, entity: Entity, parent: Option<u32>, at: usize) -> u32 {
+    let (mut id, name, components, children) = entity.into_parts();
+    if self.indices.contains_key(&id) {
+      tracing::warn!("entity {id} is already in the scene; spawning it with a new id");
+      id = Uuid::new_v4();
+    }
+    let meta = Meta {
+      id,
+      name,
+      components: Vec::with_capacity(components.len()),
+    };
+    let index = match self.free.pop() {
+      Some(index) => {
+        self.metas[index as usize] = Some(meta);
+        index
+      }
+      None => {
+        self.metas.push(Some(meta));
+        (self.metas.len() - 1) as u32
+      }
+    };
+    self.indices.insert(id, index);
+    self.attach(index, parent, at);
+    for component in components {
+      let type_id = component.as_any().type_id();
+      let row = self.push(index, component, ComponentTicks::new(self.tick));
+      self.meta_mut(index).components.push((type_id, row));
+    }
+    self.events.send(SceneEvent::Spawned(id));
+    for child in children {
+      self.spawn(child, Some(index), usize::MAX);
+    }
+    index
+  }
+
+  /// Removes the entity at `index` and its descendants, sending [`SceneEvent::Despawned`]
+  /// for each, and returns them as a detached subtree.
+  pub(crate) fn despawn(&mut self, index: u32) -> Entity {
+    self.detach(index);
+    self.take(index)
+  }
+
+  /// Links `index` into the hierarchy at position `at` among its new siblings.
+  fn attach(&mut self, index: u32, parent: Option<u32>, at: usize) {
+    let Some(parent) = parent else {
+      self.roots.insert(at.min(self.roots.len()), index);
+      return;
+    };
+    let id = self.id(index);
+    let parent_id = self.id(parent);
+    self.push(
+      index,
+      Box::new(Parent::new(parent_id)),
+      ComponentTicks::new(self.tick),
+    );
+    match self.get_mut::<Children>(parent) {
+      Some(mut children) => children.insert(at, id),
+      None => {
+        let mut children = Children::default();
+        children.insert(at, id);
+        self.push(parent, Box::new(children), ComponentTicks::new(self.tick));
+      }
+    }
+  }
+
+  /// Unlinks `index` from its parent, or from the roots.
+  fn detach(&mut self, index: u32) {
+    let Some(parent) = self.parent(index) else {
+      self.roots.retain(|&root| root != index);
+      return;
+    };
+    let id = self.id(index);
+    self.remove_untracked::<Parent>(index);
+    let mut children = self
+      .get_mut::<Children>(parent)
+      .expect("parent lists its children");
+    children.remove(id);
+    if children.is_empty() {
+      self.remove_untracked::<Children>(parent);
+    }
+  }
+
+  /// Empties and frees `index` and its descendants, which must be detached.
+  fn take(&mut self, index: u32) -> Entity {
+    let id = self.id(index);
+    self.events.send(SceneEvent::Despawned(id));
+    let children: Vec<u32> = self.children(index).collect();
+    self.remove_untracked::<Children>(index);
+    let mut components = Vec::with_capacity(self.meta(index).components.len());
+    while let Some((type_id, row)) = self.meta_mut(index).components.pop() {
+      components.push(self.remove_row(index, type_id, row));
+    }
+    components.reverse();
+    let children = children
+      .into_iter()
+      .map(|child| {
+        self.remove_untracked::<Parent>(child);
+        self.take(child)
+      })
+      .collect();
+
+    let meta = self.metas[index as usize].take().expect("live entity");
+    self.indices.remove(&id);
+    self.free.push(index);
+    Entity::from_parts(id, meta.name, components, children)
+  }
+
+  /// Stores `component` for the entity at `index` without listing it among the entity's
+  /// components, and returns its row.
+  fn push(&mut self, index: u32, component: Box<dyn Component>, ticks: ComponentTicks) -> usize {
+    let type_id = component.as_any().type_id();
+    self
+      .columns
+      .entry(type_id)
+      .or_insert_with(|| component.new_column())
+      .push(index, component, ticks)
+  }
+
+  /// Removes the component at `row` of the `type_id` column, which must already be
+  /// unlisted from the entity at `index`, and keeps the rows listed by the entities in
+  /// step with the column.
+  fn remove_row(&mut self, index: u32, type_id: TypeId, row: usize) -> Box<dyn Component> {
+    let column = self
+      .columns
+      .get_mut(&type_id)
+      .expect("column of listed type");
+    let (component, moved) = column.swap_remove(row);
+    if let Some(owner) = moved {
+      let from = column.len();
+      let meta = self.metas[owner as usize].as_mut().expect("live entity");
+      if let Some(entry) = meta.components.iter_mut().find(|e| **e == (type_id, from)) {
+        entry.1 = row;
+      }
+    }
+    let first = self.metas[index as usize]
+      .as_ref()
+      .and_then(|meta| meta.components.iter().find(|(t, _)| *t == type_id))
+      .map(|&(_, row)| row);
+    column.set_first(index, first);
+    component
+  }
+
+  /// Removes the `C` the world keeps for the entity at `index` outside its listed
+  /// components.
+  fn remove_untracked<C: Component>(&mut self, index: u32) {
+    let type_id = TypeId::of::<C>();
+    if let Some(row) = self.columns.get(&type_id).and_then(|c| c.row(index)) {
+      self.remove_row(index, type_id, row);
+    }
+  }
+
+  pub(crate) fn column<C: Component>(&self) -> Option<&Column<C>> {
+    let column: &dyn Any = self.columns.get(&TypeId::of::<C>())?.as_ref();
+    column.downcast_ref()
+  }
+
+  fn column_mut<C: Component>(&mut self) -> Option<&mut Column<C>> {
+    let column: &mut dyn Any = self.columns.get_mut(&TypeId::of::<C>())?.as_mut();
+    column.downcast_mut()
+  }
+
+  pub(crate) fn get<C: Component>(&self, index: u32) -> Option<&C> {
+    self.column::<C>()?.get(index)
+  }
+
+  /// The first `C` of the entity at `index`, stamped as changed once written.
+  pub(crate) fn get_mut<C: Component>(&mut self, index: u32) -> Option<Mut<'_, C>> {
+    let tick = self.tick;
+    let (component, ticks) = self.column_mut::<C>()?.get_mut(index)?;
+    Some(Mut::new(component, ticks, tick))
+  }
+
+  /// Stamps the component at `position` among the entity's components as changed.
+  pub(crate) fn set_changed(&mut self, index: u32, position: usize) {
+    let (type_id, row) = self.meta(index).components[position];
+    let column = self
+      .columns
+      .get_mut(&type_id)
+      .expect("column of listed type");
+    column.component_mut(row).1.changed = self.tick;
+  }
+
+  pub(crate) fn ticks(&self, index: u32, type_id: TypeId) -> Option<ComponentTicks> {
+    let column = self.columns.get(&type_id)?;
+    Some(column.ticks(column.row(index)?))
+  }
+
+  /// Position of the first component of type `type_id` among the entity's components.
+  pub(crate) fn position(&self, index: u32, type_id: TypeId) -> Option<usize> {
+    let components = &self.meta(index).components;
+    components.iter().position(|(t, _)| *t == type_id)
+  }
+
+  /// The entity's components in the order they were added.
+  pub(crate) fn components(&self, index: u32) -> impl Iterator<Item = &dyn Component> {
+    self
+      .meta(index)
+      .components
+      .iter()
+      .map(|(type_id, row)| self.columns[type_id].component(*row))
+  }
+
+  /// The entity's components of type `type_id`, in the order they were added.
+  pub(crate) fn components_of(
+    &self,
+    index: u32,
+    type_id: TypeId,
+  ) -> impl Iterator<Item = &dyn Component> {
+    self
+      .meta(index)
+      .components
+      .iter()
+      .filter(move |(t, _)| *t == type_id)
+      .map(|(type_id, row)| self.columns[type_id].component(*row))
+  }
+
+  /// The entity's components in the order they were added, stamped as changed when
+  /// `stamp` is set.
+  pub(crate) fn components_mut(&mut self, index: u32, stamp: bool) -> Vec<&mut dyn Component> {
+    let listed = &self.metas[index as usize]
+      .as_ref()
+      .expect("live entity")
+      .components;
+    let mut by_type: HashMap<TypeId, (Vec<usize>, Vec<usize>)> = HashMap::new();
+    for (position, (type_id, row)) in listed.iter().enumerate() {
+      let (positions, rows) = by_type.entry(*type_id).or_default();
+      positions.push(position);
+      rows.push(*row);
+    }
+    let mut out: Vec<_> = std::iter::repeat_with(|| None).take(listed.len()).collect();
+    let tick = self.tick;
+    for (type_id, column) in &mut self.columns {
+      let Some((positions, rows)) = by_type.get(type_id) else {
+        continue;
+      };
+      for (&position, (component, ticks)) in positions.iter().zip(column.components_mut(rows)) {
+        if stamp {
+          ticks.changed = tick;
+        }
+        out[position] = Some(component);
+      }
+    }
+    out.into_iter().flatten().collect()
+  }
+
+  /// Adds `component` after the entity's other components, sending
+  /// [`SceneEvent::ComponentAdded`].
+  pub(crate) fn add(&mut self, index: u32, component: Box<dyn Component>) {
+    let type_id = component.as_any().type_id();
+    let name = component.name();
+    let row = self.push(index, component, ComponentTicks::new(self.tick));
+    let meta = self.meta_mut(index);
+    meta.components.push((type_id, row));
+    let entity = meta.id;
+    self.events.send(SceneEvent::ComponentAdded {
+      entity,
+      type_id,
+      name,
+    });
+  }
+
+  /// Swaps `component` in for the one at `position` among the entity's components, which
+  /// must be of the same type, and stamps it as changed.
+  pub(crate) fn replace(
+    &mut self,
+    index: u32,
+    position: usize,
+    component: Box<dyn Component>,
+  ) -> Box<dyn Component> {
+    let (type_id, row) = self.meta(index).components[position];
+    let column = self
+      .columns
+      .get_mut(&type_id)
+      .expect("column of listed type");
+    column.component_mut(row).1.changed = self.tick;
+    column.replace(row, component)
+  }
+
+  /// Removes the component at `position` among the entity's components, sending
+  /// [`SceneEvent::ComponentRemoved`].
+  pub(crate) fn remove(&mut self, index: u32, position: usize) -> Option<Box<dyn Component>> {
+    let meta = self.meta_mut(index);
+    if position >= meta.components.len() {
+      return None;
+    }
+    let (type_id, row) = meta.components.remove(position);
+    let entity = meta.id;
+    let component = self.remove_row(index, type_id, row);
+    self.events.send(SceneEvent::ComponentRemoved {
+      entity,
+      type_id,
+      name: component.name(),
+    });
+    Some(component)
+  }
+
+  /// Entities that may match `Q`: those having the fewest-held of its required component
+  /// types, or every entity when it requires none.
+  fn candidates<Q: Query>(&self) -> Vec<u32> {
+    let mut required = Vec::new();
+    Q::required(&mut required);
+    if required.is_empty() {
+      return self.descendants().collect();
+    }
+    let columns: Option<Vec<_>> = required.iter().map(|t| self.columns.get(t)).collect();
+    columns
+      .and_then(|columns| columns.into_iter().min_by_key(|c| c.len()))
+      .map_or_else(Vec::new, |column| column.entities())
+  }
+
+  pub(crate) fn query<Q: ReadOnlyQuery, F: QueryFilter>(
+    &self,
+    since: u64,
+  ) -> impl Iterator<Item = Q::Item<'_>> {
+    let state = Q::state(self);
+    self
+      .candidates::<Q>()
+      .into_iter()
+      .filter(move |&index| F::matches(&|type_id| self.ticks(index, type_id), since))
+      .filter_map(move |index| Q::get(state, index, self.id(index)))
+  }
+
+  pub(crate) fn query_mut<Q: Query, F: QueryFilter>(
+    &mut self,
+    since: u64,
+  ) -> impl Iterator<Item = Q::Item<'_>> {
+    let matching: Vec<(u32, Uuid)> = self
+      .candidates::<Q>()
+      .into_iter()
+      .filter(|&index| F::matches(&|type_id| self.ticks(index, type_id), since))
+      .map(|index| (index, self.id(index)))
+      .collect();
+    let mut fetch = Q::prepare(&mut self.columns, self.metas.len(), self.tick);
+    matching
+      .into_iter()
+      .filter_map(move |(index, id)| Q::fetch(&mut fetch, index, id))
+  }
+}
+
+impl Serialize for World {
+  fn serialize<S: Serializer>(&self, s: S) -> std::result::Result<S::Ok, S::Error> {
+    s.collect_seq(self.roots.iter().map(|&index| EntityView::new(self, index)))
+  }
+}
+
+impl<'de> Deserialize<'de> for World {
+  fn deserialize<D: Deserializer<'de>>(d: D) -> std::result::Result<Self, D::Error> {
+    let mut world = Self::new();
+    for entity in Vec::<Entity>::deserialize(d)? {
+      world.spawn(entity, None, usize::MAX);
+    }
+    world.events = SceneEvents::default();
+    Ok(world)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use glam::Vec3;
+
+  use super::*;
+  use crate::{
+    EventCursor, Inspect, Scene,
+    components::{Children, Parent, Properties, Transform},
+  };
+
+  #[derive(Debug, Clone, PartialEq, Component, Inspect, serde::Serialize, serde::Deserialize)]
+  #[component(multiple, hidden)]
+  struct Note {
+    text: String,
+  }
+
+  fn note(text: &str) -> Note {
+    Note { text: text.into() }
+  }
+
+  fn tree() -> (Scene, Uuid, Uuid, Uuid) {
+    let mut scene = Scene::new();
+    let mut root = Entity::new("root");
+    let mut a = Entity::new("a");
+    let b = Entity::new("b");
+    let (root_id, a_id, b_id) = (root.id(), a.id(), b.id());
+    a.add_component(Transform::default());
+    root.add_child(a).add_child(b);
+    scene.add(root);
+    (scene, root_id, a_id, b_id)
+  }
+
+  #[test]
+  fn spawn_links_parent_and_children() {
+    let (scene, root, a, b) = tree();
+    let root_view = scene.find(root).unwrap();
+    let children: Vec<Uuid> = root_view.children().map(|c| c.id()).collect();
+    assert_eq!(children, [a, b]);
+    assert_eq!(
+      root_view.get_component::<Children>().unwrap().as_slice(),
+      [a, b]
+    );
+    assert_eq!(
+      scene
+        .find(b)
+        .unwrap()
+        .get_component::<Parent>()
+        .unwrap()
+        .entity(),
+      root
+    );
+    assert!(root_view.get_component::<Parent>().is_none());
+    // Relations are storage, not part of the entity's own component list.
+    assert_eq!(root_view.iter().count(), 0);
+  }
+
+  #[test]
+  fn despawn_returns_the_subtree_and_unlinks_it() {
+    let (mut scene, root, a, b) = tree();
+    let taken = scene.despawn(a).unwrap();
+    assert_eq!(taken.id(), a);
+    assert!(taken.has::<Transform>());
+    assert!(scene.find(a).is_none());
+    assert_eq!(
+      scene
+        .find(root)
+        .unwrap()
+        .get_component::<Children>()
+        .unwrap()
+        .as_slice(),
+      [b]
+    );
+    assert_eq!(scene.query::<&Transform>().count(), 0);
+
+    scene.despawn(b);
+    assert!(!scene.find(root).unwrap().has::<Children>());
+
+    // Freed indices are reused without disturbing the survivors.
+    let c = Entity::new("c");
+    let c_id = c.id();
+    scene.add_child(root, c);
+    assert_eq!(scene.find(c_id).unwrap().parent().unwrap().id(), root);
+  }
+
+  #[test]
+  fn removing_a_row_keeps_moved_rows_reachable() {
+    let mut scene = Scene::new();
+    let mut first = Entity::new("first");
+    first.add_component(note("one")).add_component(note("two"));
+    let mut second = Entity::new("second");
+    second.add_component(note("three"));
+    let (first_id, second_id) = (first.id(), second.id());
+    scene.add(first);
+    scene.add(second);
+
+    // Swap-removing the first row moves the second entity's note into it.
+    scene.find_mut(first_id).unwrap().remove_component_at(0);
+    let texts = |scene: &Scene, id| -> Vec<String> {
+      let entity = scene.find(id).unwrap();
+      entity
+        .get_components::<Note>()
+        .map(|n| n.text.clone())
+        .collect()
+    };
+    assert_eq!(texts(&scene, first_id), ["two"]);
+    assert_eq!(texts(&scene, second_id), ["three"]);
+
+    scene
+      .find_mut(second_id)
+      .unwrap()
+      .remove_component::<Note>();
+    assert_eq!(texts(&scene, first_id), ["two"]);
+    assert!(!scene.find(second_id).unwrap().has::<Note>());
+  }
+
+  #[test]
+  fn components_keep_their_order_across_columns() {
+    let mut entity = Entity::new("e");
+    entity
+      .add_component(note("one"))
+      .add_component(Properties::new())
+      .add_component(note("two"));
+    let id = entity.id();
+    let mut scene = Scene::new();
+    scene.add(entity);
+
+    let names: Vec<&str> = scene.find(id).unwrap().iter().map(|c| c.name()).collect();
+    assert_eq!(names, ["Note", "Properties", "Note"]);
+  }
+
+  #[test]
+  fn queries_visit_only_matching_entities() {
+    let (mut scene, _, a, _) = tree();
+    let ids: Vec<Uuid> = scene
+      .query::<(Uuid, &Transform)>()
+      .map(|(id, _)| id)
+      .collect();
+    assert_eq!(ids, [a]);
+    assert_eq!(scene.query::<(Uuid, Option<&Transform>)>().count(), 3);
+
+    for mut transform in scene.query_mut::<&mut Transform>() {
+      transform.position = Vec3::X;
+    }
+    let a = scene.find(a).unwrap();
+    assert_eq!(a.get_component::<Transform>().unwrap().position, Vec3::X);
+  }
+
+  #[test]
+  fn serialization_round_trips_the_hierarchy() {
+    let (scene, root, a, b) = tree();
+    let json = scene.to_json().unwrap();
+    let loaded = Scene::from_json(&json).unwrap();
+    let children: Vec<Uuid> = loaded
+      .find(root)
+      .unwrap()
+      .children()
+      .map(|c| c.id())
+      .collect();
+    assert_eq!(children, [a, b]);
+    assert!(loaded.find(a).unwrap().has::<Transform>());
+    assert!(!json.contains("\"Parent\""));
+    let mut cursor = EventCursor::default();
+    assert_eq!(loaded.events().read(&mut cursor).count(), 0);
+  }
+}
@@ -1,21 +1,49 @@
 mod application;
+mod background;
 pub mod components;
 pub mod editor;
 mod error;
+pub mod geo;
 mod hierarchy;
+mod picking;
+mod prefab;
 pub(crate) mod renderer;
 mod scene;
+mod simplify;
 mod types;
 pub(crate) mod window;
 
+// Lets the derive macros name this crate as `::canberra_engine` from inside it too.
+extern crate self as canberra_engine;
+
+pub use canberra_derive::{Component, Inspect};
+#[doc(hidden)]
+pub use egui as __egui;
+#[doc(hidden)]
+pub use inventory as __inventory;
+#[doc(hidden)]
+pub use typetag as __typetag;
+
 pub use self::{
-  application::{Application, ApplicationState},
+  application::{
+    Application, ApplicationState, FrameSample, Input, PROFILER_HISTORY, Profiler, Schedule, Stage,
+    System, SystemContext,
+  },
+  background::{Atmosphere, Background, Gradient, SKYBOX_FACES, Skybox, SkyboxImage},
   error::{Error, Result},
-  hierarchy::{Component, Entity},
+  hierarchy::{
+    Added, Changed, Component, ComponentInfo, ComponentRegistration, ComponentRegistry,
+    ComponentTicks, DanglingRef, Entity, EntityRef, EntityView, EntityViewMut, EventCursor,
+    GLOBAL_COMPONENT_REGISTRY, Inspect, InspectOptions, Mut, Query, QueryFilter, QueryTerm,
+    ReadOnlyQuery, ReadOnlyTerm, SceneEvent, SceneEvents, With, Without,
+  },
+  picking::Pick,
+  prefab::Prefab,
   renderer::{
-    AssetManager, GLOBAL_SHADER_REGISTRY, MeshHandle, ShaderHandle, ShaderRegistry,
-    register_shaders,
+    AssetManager, FrameGraph, GLOBAL_SHADER_REGISTRY, GraphResource, MeshHandle, PassContext,
+    PostPassHandle, PostSettings, RenderGraph, RenderSettings, RenderStats, ShaderHandle,
+    ShaderRegistry, TextureDesc, TextureSize, ToneMapping, register_shaders,
   },
   scene::Scene,
-  types::{Shader, Vertex},
+  types::{Aabb, Frustum, Ray, Shader, Vertex},
 };
@@ -0,0 +1,165 @@
+use glam::{Mat4, Vec3};
+use uuid::Uuid;
+
+use crate::{
+  Aabb, EntityView, Ray, Scene,
+  components::{Lod, Mesh, RenderLayers, Terrain, Transform, Visibility},
+};
+
+/// Closest surface hit by a [`Ray`].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Pick {
+  pub entity: Uuid,
+  /// Hit point in world space.
+  pub point: Vec3,
+  /// Distance from the ray origin.
+  pub distance: f32,
+}
+
+pub(crate) fn pick(scene: &Scene, ray: &Ray) -> Option<Pick> {
+  let layers = scene
+    .active_camera()
+    .map_or(RenderLayers::DEFAULT, |(camera, _)| camera.layers);
+  let mut best = None;
+  for entity in scene.roots() {
+    pick_entity(entity, Mat4::IDENTITY, true, layers, ray, &mut best);
+  }
+  best
+}
+
+fn pick_entity(
+  entity: EntityView<'_>,
+  parent_world: Mat4,
+  parent_visible: bool,
+  layers: RenderLayers,
+  ray: &Ray,
+  best: &mut Option<Pick>,
+) {
+  let local = entity
+    .get_component::<Transform>()
+    .map(|t| t.matrix())
+    .unwrap_or(Mat4::IDENTITY);
+  let world = parent_world * local;
+  let visible = Visibility::of(entity).resolve(parent_visible);
+  let shown = visible && layers.intersects(RenderLayers::of(entity));
+
+  let mesh = match (
+    entity.get_component::<Terrain>(),
+    entity.get_component::<Lod>(),
+  ) {
+    (Some(terrain), _) => Some(terrain.mesh(0)),
+    (None, Some(lod)) => lod.levels.first().map(|l| &l.mesh),
+    (None, None) => entity.get_component::<Mesh>(),
+  };
+  let nearest = best.map_or(f32::INFINITY, |b| b.distance);
+  if shown
+    && let Some(t) = mesh.and_then(|m| intersect_mesh(m, world, ray, nearest))
+    && t < nearest
+  {
+    *best = Some(Pick {
+      entity: entity.id(),
+      point: ray.at(t),
+      distance: t,
+    });
+  }
+
+  for child in entity.children() {
+    pick_entity(child, world, visible, layers, ray, best);
+  }
+}
+
+/// Nearest hit distance along `ray` against either face of the mesh's triangles. Meshes
+/// whose bounds the ray misses, or only enters beyond `nearest`, are skipped untested.
+fn intersect_mesh(mesh: &Mesh, world: Mat4, ray: &Ray, nearest: f32) -> Option<f32> {
+  // Intersect in mesh space; `direction` keeps world scale so `t` stays a world distance.
+  let inv = world.inverse();
+  let origin = inv.transform_point3(ray.origin);
+  let direction = inv.transform_vector3(ray.direction);
+  let bounds = mesh.bounds()?;
+  if intersect_aabb(origin, direction, &bounds).is_none_or(|t| t >= nearest) {
+    return None;
+  }
+
+  let pos = |i: u16| Vec3::from(mesh.vertices()[i as usize].position);
+  mesh
+    .indices()
+    .chunks_exact(3)
+    .filter_map(|tri| intersect_triangle(origin, direction, pos(tri[0]), pos(tri[1]), pos(tri[2])))
+    .min_by(f32::total_cmp)
+}
+
+/// Slab test: distance at which the ray enters `aabb`, 0 when it starts inside.
+fn intersect_aabb(origin: Vec3, direction: Vec3, aabb: &Aabb) -> Option<f32> {
+  let inv = direction.recip();
+  let t0 = (aabb.min - origin) * inv;
+  let t1 = (aabb.max - origin) * inv;
+  let near = t0.min(t1).max_element().max(0.0);
+  let far = t0.max(t1).min_element();
+  (near <= far).then_some(near)
+}
+
+/// Möller–Trumbore ray/triangle intersection.
+fn intersect_triangle(origin: Vec3, direction: Vec3, a: Vec3, b: Vec3, c: Vec3) -> Option<f32> {
+  let e1 = b - a;
+  let e2 = c - a;
+  let p = direction.cross(e2);
+  let det = e1.dot(p);
+  if det.abs() < 1e-8 {
+    return None;
+  }
+  let inv_det = 1.0 / det;
+  let s = origin - a;
+  let u = s.dot(p) * inv_det;
+  if !(0.0..=1.0).contains(&u) {
+    return None;
+  }
+  let q = s.cross(e1);
+  let v = direction.dot(q) * inv_det;
+  if v < 0.0 || u + v > 1.0 {
+    return None;
+  }
+  let t = e2.dot(q) * inv_det;
+  (t >= 0.0).then_some(t)
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::Entity;
+
+  fn cube_at(scene: &mut Scene, name: &str, position: Vec3) -> Uuid {
+    let mut entity = Entity::new(name);
+    entity.add_component(Transform::from_translation(position));
+    entity.add_component(Mesh::cube());
+    let id = entity.id();
+    scene.add(entity);
+    id
+  }
+
+  #[test]
+  fn picks_the_nearest_mesh() {
+    let mut scene = Scene::new();
+    let far = cube_at(&mut scene, "far", Vec3::new(0.0, 0.0, -20.0));
+    let near = cube_at(&mut scene, "near", Vec3::new(0.0, 0.0, -10.0));
+    cube_at(&mut scene, "aside", Vec3::new(5.0, 0.0, -5.0));
+
+    let hit = pick(&scene, &Ray::new(Vec3::ZERO, Vec3::NEG_Z)).unwrap();
+    assert_eq!(hit.entity, near);
+    assert!((hit.distance - 9.0).abs() < 1e-5);
+    assert!((hit.point - Vec3::new(0.0, 0.0, -9.0)).length() < 1e-5);
+
+    let hit = pick(&scene, &Ray::new(Vec3::new(0.0, 5.0, -20.0), Vec3::NEG_Y)).unwrap();
+    assert_eq!(hit.entity, far);
+    assert!(pick(&scene, &Ray::new(Vec3::ZERO, Vec3::Z)).is_none());
+  }
+
+  #[test]
+  fn slab_test() {
+    let aabb = Aabb::new(Vec3::splat(-1.0), Vec3::splat(1.0));
+    let hit = |origin: Vec3, direction: Vec3| intersect_aabb(origin, direction, &aabb);
+    assert_eq!(hit(Vec3::new(0.0, 0.0, 5.0), Vec3::NEG_Z), Some(4.0));
+    assert_eq!(hit(Vec3::ZERO, Vec3::X), Some(0.0));
+    assert_eq!(hit(Vec3::new(0.0, 0.0, 5.0), Vec3::Z), None);
+    assert_eq!(hit(Vec3::new(0.0, 2.0, 5.0), Vec3::NEG_Z), None);
+  }
+}
@@ -0,0 +1,61 @@
+// Bloom: bright parts of the scene are downsampled into a mip chain, then upsampled
+// and accumulated back up, giving a wide glow at the cost of a few small passes.
+
+// 13-tap downsample (Jimenez, "Next Generation Post Processing in Call of Duty"),
+// which keeps fireflies from flickering as they move across texels.
+fn downsample(uv: vec2<f32>) -> vec3<f32> {
+  let t = input_texel();
+  let a = textureSample(input, input_sampler, uv + t * vec2<f32>(-2.0, -2.0)).rgb;
+  let b = textureSample(input, input_sampler, uv + t * vec2<f32>( 0.0, -2.0)).rgb;
+  let c = textureSample(input, input_sampler, uv + t * vec2<f32>( 2.0, -2.0)).rgb;
+  let d = textureSample(input, input_sampler, uv + t * vec2<f32>(-2.0,  0.0)).rgb;
+  let e = textureSample(input, input_sampler, uv).rgb;
+  let f = textureSample(input, input_sampler, uv + t * vec2<f32>( 2.0,  0.0)).rgb;
+  let g = textureSample(input, input_sampler, uv + t * vec2<f32>(-2.0,  2.0)).rgb;
+  let h = textureSample(input, input_sampler, uv + t * vec2<f32>( 0.0,  2.0)).rgb;
+  let i = textureSample(input, input_sampler, uv + t * vec2<f32>( 2.0,  2.0)).rgb;
+  let j = textureSample(input, input_sampler, uv + t * vec2<f32>(-1.0, -1.0)).rgb;
+  let k = textureSample(input, input_sampler, uv + t * vec2<f32>( 1.0, -1.0)).rgb;
+  let l = textureSample(input, input_sampler, uv + t * vec2<f32>(-1.0,  1.0)).rgb;
+  let m = textureSample(input, input_sampler, uv + t * vec2<f32>( 1.0,  1.0)).rgb;
+  return e * 0.125
+    + (a + c + g + i) * 0.03125
+    + (b + d + f + h) * 0.0625
+    + (j + k + l + m) * 0.125;
+}
+
+// Soft-knee threshold: fades in over `bloom_knee` below `bloom_threshold`.
+fn threshold(color: vec3<f32>) -> vec3<f32> {
+  let brightness = max(color.r, max(color.g, color.b));
+  let knee       = max(post.bloom_threshold * post.bloom_knee, 1e-5);
+  var soft       = clamp(brightness - post.bloom_threshold + knee, 0.0, 2.0 * knee);
+  soft           = soft * soft / (4.0 * knee);
+  let weight     = max(soft, brightness - post.bloom_threshold) / max(brightness, 1e-5);
+  return color * weight;
+}
+
+@fragment
+fn fs_prefilter(in: PostOut) -> @location(0) vec4<f32> {
+  return vec4<f32>(threshold(downsample(in.uv)), 1.0);
+}
+
+@fragment
+fn fs_downsample(in: PostOut) -> @location(0) vec4<f32> {
+  return vec4<f32>(downsample(in.uv), 1.0);
+}
+
+// 3x3 tent filter; additively blended onto the next larger mip.
+@fragment
+fn fs_upsample(in: PostOut) -> @location(0) vec4<f32> {
+  let t   = input_texel();
+  var sum = textureSample(input, input_sampler, in.uv).rgb * 4.0;
+  sum += textureSample(input, input_sampler, in.uv + t * vec2<f32>(-1.0,  0.0)).rgb * 2.0;
+  sum += textureSample(input, input_sampler, in.uv + t * vec2<f32>( 1.0,  0.0)).rgb * 2.0;
+  sum += textureSample(input, input_sampler, in.uv + t * vec2<f32>( 0.0, -1.0)).rgb * 2.0;
+  sum += textureSample(input, input_sampler, in.uv + t * vec2<f32>( 0.0,  1.0)).rgb * 2.0;
+  sum += textureSample(input, input_sampler, in.uv + t * vec2<f32>(-1.0, -1.0)).rgb;
+  sum += textureSample(input, input_sampler, in.uv + t * vec2<f32>( 1.0, -1.0)).rgb;
+  sum += textureSample(input, input_sampler, in.uv + t * vec2<f32>(-1.0,  1.0)).rgb;
+  sum += textureSample(input, input_sampler, in.uv + t * vec2<f32>( 1.0,  1.0)).rgb;
+  return vec4<f32>(sum / 16.0, 1.0);
+}
@@ -0,0 +1,42 @@
+// Shared prelude of every post pass, built-in or registered. Passes supply `fs_*` entry
+// points; the full-screen triangle and bindings are declared here.
+
+struct Post {
+  time:            f32,
+  exposure:        f32,
+  contrast:        f32,
+  saturation:      f32,
+  bloom_threshold: f32,
+  bloom_knee:      f32,
+  bloom_intensity: f32,
+  tone_mapping:    u32,
+}
+
+@group(0) @binding(0) var input:         texture_2d<f32>;
+@group(0) @binding(1) var input_sampler: sampler;
+@group(0) @binding(2) var<uniform> post: Post;
+@group(0) @binding(3) var bloom:         texture_2d<f32>;
+
+struct PostOut {
+  @builtin(position) position: vec4<f32>,
+  @location(0)       uv:       vec2<f32>,
+}
+
+// One triangle covering the screen; `uv` is 0..1 with y pointing down.
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> PostOut {
+  let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+  var out: PostOut;
+  out.position = vec4<f32>(uv.x * 2.0 - 1.0, 1.0 - uv.y * 2.0, 0.0, 1.0);
+  out.uv       = uv;
+  return out;
+}
+
+// Size of one texel of `input`, in uv units.
+fn input_texel() -> vec2<f32> {
+  return 1.0 / vec2<f32>(textureDimensions(input));
+}
+
+fn luminance(color: vec3<f32>) -> f32 {
+  return dot(color, vec3<f32>(0.2126, 0.7152, 0.0722));
+}
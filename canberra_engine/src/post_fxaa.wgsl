@@ -0,0 +1,46 @@
+// FXAA (Lottes), the compact variant: blurs along the local edge direction found from
+// the luma of the four diagonal neighbours.
+
+const FXAA_REDUCE_MIN: f32 = 1.0 / 128.0;
+const FXAA_REDUCE_MUL: f32 = 1.0 / 8.0;
+const FXAA_SPAN_MAX:   f32 = 8.0;
+
+// The input is linear; the square root approximates the perceptual luma FXAA expects.
+fn luma(color: vec3<f32>) -> f32 {
+  return sqrt(luminance(color));
+}
+
+@fragment
+fn fs_main(in: PostOut) -> @location(0) vec4<f32> {
+  let t = input_texel();
+  let center = textureSample(input, input_sampler, in.uv).rgb;
+  let nw = luma(textureSample(input, input_sampler, in.uv + t * vec2<f32>(-1.0, -1.0)).rgb);
+  let ne = luma(textureSample(input, input_sampler, in.uv + t * vec2<f32>( 1.0, -1.0)).rgb);
+  let sw = luma(textureSample(input, input_sampler, in.uv + t * vec2<f32>(-1.0,  1.0)).rgb);
+  let se = luma(textureSample(input, input_sampler, in.uv + t * vec2<f32>( 1.0,  1.0)).rgb);
+  let m  = luma(center);
+
+  let luma_min = min(m, min(min(nw, ne), min(sw, se)));
+  let luma_max = max(m, max(max(nw, ne), max(sw, se)));
+
+  var dir = vec2<f32>(-((nw + ne) - (sw + se)), (nw + sw) - (ne + se));
+  let reduce = max((nw + ne + sw + se) * 0.25 * FXAA_REDUCE_MUL, FXAA_REDUCE_MIN);
+  let scale  = 1.0 / (min(abs(dir.x), abs(dir.y)) + reduce);
+  dir = clamp(dir * scale, vec2<f32>(-FXAA_SPAN_MAX), vec2<f32>(FXAA_SPAN_MAX)) * t;
+
+  let a = 0.5 * (
+    textureSample(input, input_sampler, in.uv + dir * (1.0 / 3.0 - 0.5)).rgb +
+    textureSample(input, input_sampler, in.uv + dir * (2.0 / 3.0 - 0.5)).rgb
+  );
+  let b = a * 0.5 + 0.25 * (
+    textureSample(input, input_sampler, in.uv + dir * -0.5).rgb +
+    textureSample(input, input_sampler, in.uv + dir *  0.5).rgb
+  );
+
+  // The wide sample strayed across another edge: fall back to the narrow one.
+  let lb = luma(b);
+  if lb < luma_min || lb > luma_max {
+    return vec4<f32>(a, 1.0);
+  }
+  return vec4<f32>(b, 1.0);
+}
@@ -0,0 +1,62 @@
+// Resolves the HDR image for display: adds bloom, grades, then tone maps into 0..1.
+
+const TONE_MAPPING_NONE:   u32 = 0u;
+const TONE_MAPPING_ACES:   u32 = 1u;
+const TONE_MAPPING_FILMIC: u32 = 2u;
+
+const MIDDLE_GREY: f32 = 0.18;
+
+// ACES fitted curve (Stephen Hill), including the sRGB <-> ACEScg transforms.
+fn aces(color: vec3<f32>) -> vec3<f32> {
+  let input_matrix = mat3x3<f32>(
+    vec3<f32>(0.59719, 0.07600, 0.02840),
+    vec3<f32>(0.35458, 0.90834, 0.13383),
+    vec3<f32>(0.04823, 0.01566, 0.83777),
+  );
+  let output_matrix = mat3x3<f32>(
+    vec3<f32>( 1.60475, -0.10208, -0.00327),
+    vec3<f32>(-0.53108,  1.10813, -0.07276),
+    vec3<f32>(-0.07367, -0.00605,  1.07602),
+  );
+  let v = input_matrix * color;
+  let a = v * (v + 0.0245786) - 0.000090537;
+  let b = v * (0.983729 * v + 0.4329510) + 0.238081;
+  return clamp(output_matrix * (a / b), vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+// Uncharted 2 filmic curve (Hable), normalised to a white point of 11.2.
+fn hable(x: vec3<f32>) -> vec3<f32> {
+  let a = 0.15;
+  let b = 0.50;
+  let c = 0.10;
+  let d = 0.20;
+  let e = 0.02;
+  let f = 0.30;
+  return ((x * (a * x + c * b) + d * e) / (x * (a * x + b) + d * f)) - e / f;
+}
+
+fn filmic(color: vec3<f32>) -> vec3<f32> {
+  let white = hable(vec3<f32>(11.2));
+  return clamp(hable(color * 2.0) / white, vec3<f32>(0.0), vec3<f32>(1.0));
+}
+
+// Contrast pivots around middle grey in log space so it works on unbounded values.
+fn grade(color: vec3<f32>) -> vec3<f32> {
+  let c = MIDDLE_GREY * pow(max(color, vec3<f32>(0.0)) / MIDDLE_GREY, vec3<f32>(post.contrast));
+  return max(mix(vec3<f32>(luminance(c)), c, post.saturation), vec3<f32>(0.0));
+}
+
+@fragment
+fn fs_main(in: PostOut) -> @location(0) vec4<f32> {
+  let scene = textureSample(input, input_sampler, in.uv).rgb;
+  let glow  = textureSample(bloom, input_sampler, in.uv).rgb;
+  let color = grade((scene + glow * post.bloom_intensity) * post.exposure);
+
+  var mapped: vec3<f32>;
+  switch post.tone_mapping {
+    case TONE_MAPPING_ACES:   { mapped = aces(color); }
+    case TONE_MAPPING_FILMIC: { mapped = filmic(color); }
+    default:                  { mapped = clamp(color, vec3<f32>(0.0), vec3<f32>(1.0)); }
+  }
+  return vec4<f32>(mapped, 1.0);
+}
@@ -0,0 +1,515 @@
+use std::collections::HashMap;
+
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use crate::{
+  Component, Entity, Result, Scene,
+  components::{PrefabInstance, PropertyOverride},
+  hierarchy,
+};
+
+/// Serialized type name of [`PrefabInstance`], which is bookkeeping rather than content.
+const INSTANCE_TYPE: &str = "PrefabInstance";
+
+/// A reusable entity subtree, kept in its serialized form. Instances get fresh ids and a
+/// [`PrefabInstance`] on their root; see [`crate::Scene::update_prefab`] for how edits to
+/// the prefab reach them.
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
+pub struct Prefab {
+  id: Uuid,
+  pub name: String,
+  root: Value,
+}
+
+impl Prefab {
+  pub fn new(name: &str, root: &Entity) -> Result<Self> {
+    Ok(Self {
+      id: Uuid::new_v4(),
+      name: name.to_string(),
+      root: serde_json::to_value(root)?,
+    })
+  }
+
+  pub fn id(&self) -> Uuid {
+    self.id
+  }
+
+  pub fn from_json(src: &str) -> Result<Self> {
+    Ok(serde_json::from_str(src)?)
+  }
+
+  pub fn to_json(&self) -> Result<String> {
+    Ok(serde_json::to_string_pretty(self)?)
+  }
+
+  /// A copy of the template, with the prefab's own entity ids.
+  pub fn root(&self) -> Result<Entity> {
+    Ok(serde_json::from_value(self.root.clone())?)
+  }
+
+  /// Replaces the template. Instances already in a scene only follow through
+  /// [`crate::Scene::update_prefab`].
+  pub fn set_root(&mut self, root: &Entity) -> Result<()> {
+    self.root = serde_json::to_value(root)?;
+    Ok(())
+  }
+
+  /// A new copy of the template with fresh ids, marked as an instance of this prefab.
+  pub fn instantiate(&self) -> Result<Entity> {
+    let (mut root, entities) = self.root()?.duplicate_mapped()?;
+    root.add_component(PrefabInstance::new(self.id, entities));
+    Ok(root)
+  }
+}
+
+/// Brings the instance rooted at `instance`, last made from `old`, up to date with `new`.
+/// Properties that differ from `old` are recorded as overrides and keep their values,
+/// except those in `revert`.
+pub(crate) fn propagate(
+  old: &Prefab,
+  new: &Prefab,
+  scene: &mut Scene,
+  instance: Uuid,
+  revert: &[PropertyOverride],
+) -> Result<()> {
+  let Some(entity) = scene.find(instance) else {
+    return Ok(());
+  };
+  let Some(mut info) = entity.get_component::<PrefabInstance>().cloned() else {
+    return Ok(());
+  };
+  let current = serde_json::to_value(entity)?;
+  let current_by_id = index(&current);
+  let old_by_id = index(&old.root);
+
+  // Compared with instance ids written back as prefab ids, so that references between the
+  // instance's entities do not read as overrides.
+  let mut unmapped = current.clone();
+  let to_prefab: Vec<(Uuid, Uuid)> = info.entities().map(|(p, i)| (i, p)).collect();
+  hierarchy::remap_ids(&mut unmapped, &to_prefab);
+  let unmapped_by_id = index(&unmapped);
+  for &(_, prefab_id) in &to_prefab {
+    let old = old_by_id.get(&prefab_id);
+    if let (Some(old), Some(current)) = (old, unmapped_by_id.get(&prefab_id)) {
+      record_overrides(prefab_id, old, current, &mut info.overrides);
+    }
+  }
+  info.overrides.retain(|o| !revert.contains(o));
+
+  let mut rebuild = Rebuild {
+    info: &info,
+    old_by_id,
+    current_by_id,
+    entities: Vec::new(),
+  };
+  let mut target = rebuild.entity(&new.root);
+  let entities = rebuild.entities;
+  // References between prefab entities point at their instance counterparts.
+  hierarchy::remap_ids(&mut target, &entities);
+  reconcile(scene, serde_json::from_value(target)?)?;
+
+  info.set_entities(entities);
+  if let Some(mut entity) = scene.find_mut(instance)
+    && let Some(mut existing) = entity.get_component_mut::<PrefabInstance>()
+  {
+    *existing = info;
+  }
+  Ok(())
+}
+
+/// Serialized entities of the subtree `root`, by id.
+fn index(root: &Value) -> HashMap<Uuid, &Value> {
+  let mut entities = HashMap::new();
+  let mut stack = vec![root];
+  while let Some(entity) = stack.pop() {
+    if let Some(id) = id_of(entity) {
+      entities.insert(id, entity);
+    }
+    stack.extend(children_of(entity));
+  }
+  entities
+}
+
+fn id_of(entity: &Value) -> Option<Uuid> {
+  entity.get("id")?.as_str()?.parse().ok()
+}
+
+fn children_of(entity: &Value) -> impl Iterator<Item = &Value> {
+  entity
+    .get("children")
+    .and_then(Value::as_array)
+    .into_iter()
+    .flatten()
+}
+
+/// Serialized components of `entity` with their type name and index among components of
+/// that type, leaving out the [`PrefabInstance`].
+fn components_of(entity: &Value) -> Vec<(&str, usize, &Value)> {
+  let mut components: Vec<(&str, usize, &Value)> = Vec::new();
+  let all = entity.get("components").and_then(Value::as_array);
+  for component in all.into_iter().flatten() {
+    let ty = component.get("type").and_then(Value::as_str).unwrap_or("");
+    if ty == INSTANCE_TYPE {
+      continue;
+    }
+    let index = components.iter().filter(|(t, _, _)| *t == ty).count();
+    components.push((ty, index, component));
+  }
+  components
+}
+
+fn find_component<'a>(
+  components: &[(&str, usize, &'a Value)],
+  ty: &str,
+  index: usize,
+) -> Option<&'a Value> {
+  components
+    .iter()
+    .find(|(t, i, _)| *t == ty && *i == index)
+    .map(|&(_, _, c)| c)
+}
+
+/// Adds an override for every property of `current` that differs from `old`, its prefab
+/// entity.
+fn record_overrides(
+  entity: Uuid,
+  old: &Value,
+  current: &Value,
+  overrides: &mut Vec<PropertyOverride>,
+) {
+  let mut add = |component: Option<&str>, index: usize, path: String| {
+    let o = PropertyOverride {
+      entity,
+      component: component.map(str::to_string),
+      index,
+      path,
+    };
+    if !overrides.contains(&o) {
+      overrides.push(o);
+    }
+  };
+
+  if old.get("name") != current.get("name") {
+    add(None, 0, String::new());
+  }
+  let current_components = components_of(current);
+  for (ty, index, old_component) in components_of(old) {
+    match find_component(&current_components, ty, index) {
+      None => add(Some(ty), index, String::new()),
+      Some(component) => {
+        let mut paths = Vec::new();
+        diff(old_component, component, String::new(), &mut paths);
+        for path in paths {
+          add(Some(ty), index, path);
+        }
+      }
+    }
+  }
+}
+
+/// JSON pointers of the leaves where `b` differs from `a`. Arrays count as leaves, so a
+/// vector or color is overridden as a whole.
+fn diff(a: &Value, b: &Value, path: String, out: &mut Vec<String>) {
+  match (a, b) {
+    (Value::Object(a), Value::Object(b)) => {
+      for (key, a) in a {
+        let path = format!("{path}/{}", key.replace('~', "~0").replace('/', "~1"));
+        match b.get(key) {
+          Some(b) => diff(a, b, path, out),
+          None => out.push(path),
+        }
+      }
+    }
+    _ if a != b => out.push(path),
+    _ => {}
+  }
+}
+
+/// Builds the serialized instance subtree for a prefab subtree.
+struct Rebuild<'a> {
+  info: &'a PrefabInstance,
+  old_by_id: HashMap<Uuid, &'a Value>,
+  current_by_id: HashMap<Uuid, &'a Value>,
+  /// Prefab and instance ids of the entities built so far.
+  entities: Vec<(Uuid, Uuid)>,
+}
+
+impl Rebuild<'_> {
+  fn entity(&mut self, prefab: &Value) -> Value {
+    let prefab_id = id_of(prefab).unwrap_or_default();
+    let id = self
+      .info
+      .instance_of(prefab_id)
+      .unwrap_or_else(Uuid::new_v4);
+    self.entities.push((prefab_id, id));
+    let current = self.current_by_id.get(&id).copied();
+    let old = self.old_by_id.get(&prefab_id).copied();
+    let info = self.info;
+    let overridden = |component: Option<&str>, index: usize| -> Vec<&str> {
+      info
+        .overrides
+        .iter()
+        .filter(|o| {
+          o.entity == prefab_id && o.component.as_deref() == component && o.index == index
+        })
+        .map(|o| o.path.as_str())
+        .collect()
+    };
+
+    let name = match current {
+      Some(current) if !overridden(None, 0).is_empty() => current.get("name"),
+      _ => prefab.get("name"),
+    };
+
+    let current_components = current.map(components_of).unwrap_or_default();
+    let mut components = Vec::new();
+    for (ty, index, component) in components_of(prefab) {
+      let current = find_component(&current_components, ty, index);
+      let paths = overridden(Some(ty), index);
+      if paths.contains(&"") {
+        // Overridden as a whole, including being removed from the instance.
+        components.extend(current.cloned());
+        continue;
+      }
+      let mut component = component.clone();
+      for path in paths {
+        if let (Some(value), Some(slot)) = (
+          current.and_then(|c| c.pointer(path)),
+          component.pointer_mut(path),
+        ) {
+          *slot = value.clone();
+        }
+      }
+      components.push(component);
+    }
+    // Components added to the instance itself.
+    let old_components = old.map(components_of).unwrap_or_default();
+    for &(ty, index, component) in &current_components {
+      if find_component(&old_components, ty, index).is_none() {
+        components.push(component.clone());
+      }
+    }
+
+    let mut children: Vec<Value> = children_of(prefab).map(|c| self.entity(c)).collect();
+    // Entities added to the instance itself.
+    if let Some(current) = current {
+      let made = |id| info.entities().any(|(_, i)| i == id);
+      children.extend(
+        children_of(current)
+          .filter(|c| id_of(c).is_some_and(|id| !made(id)))
+          .cloned(),
+      );
+    }
+
+    let mut entity = Map::new();
+    entity.insert("id".into(), Value::String(id.to_string()));
+    entity.insert("name".into(), name.cloned().unwrap_or_default());
+    entity.insert("components".into(), Value::Array(components));
+    entity.insert("children".into(), Value::Array(children));
+    Value::Object(entity)
+  }
+}
+
+/// Makes the scene's entity with the id of `target` match it, touching only the entities
+/// and components that differ so that change ticks and lifecycle events stay meaningful.
+fn reconcile(scene: &mut Scene, mut target: Entity) -> Result<()> {
+  let id = target.id();
+  let Some(mut entity) = scene.find_mut(id) else {
+    return Ok(());
+  };
+  if entity.name() != target.name {
+    *entity.name_mut() = std::mem::take(&mut target.name);
+  }
+  let current = content_of(entity.iter())?;
+  let wanted = content_of(target.iter())?;
+  if current != wanted {
+    let mut incoming = Vec::new();
+    while let Some(component) = target.remove_component_at(0) {
+      incoming.push(component);
+    }
+    let same_types = current.len() == wanted.len()
+      && current
+        .iter()
+        .zip(&wanted)
+        .all(|((_, a), (_, b))| a.get("type") == b.get("type"));
+    if same_types {
+      // Only the values changed: swap them in place so the components read as changed
+      // rather than removed and added again.
+      for (((index, a), (_, b)), component) in current.iter().zip(&wanted).zip(incoming) {
+        if a != b {
+          entity.replace_component_at(*index, component);
+        }
+      }
+    } else {
+      for &(index, _) in current.iter().rev() {
+        entity.remove_component_at(index);
+      }
+      for component in incoming {
+        entity.insert_boxed(component);
+      }
+    }
+  }
+
+  let wanted: Vec<Uuid> = target.children().iter().map(Entity::id).collect();
+  let existing: Vec<Uuid> = entity.as_view().children().map(|c| c.id()).collect();
+  for &child in existing.iter().filter(|id| !wanted.contains(id)) {
+    scene.despawn(child);
+  }
+  for (position, child) in wanted.into_iter().enumerate() {
+    let Some(child) = target.remove_child(child) else {
+      continue;
+    };
+    if existing.contains(&child.id()) {
+      reconcile(scene, child)?;
+    } else {
+      scene.insert_child(id, position, child);
+    }
+  }
+  Ok(())
+}
+
+/// Serialized `components` and their indices, leaving out the [`PrefabInstance`].
+fn content_of<'a>(
+  components: impl Iterator<Item = &'a dyn Component>,
+) -> Result<Vec<(usize, Value)>> {
+  components
+    .enumerate()
+    .filter(|(_, c)| !c.as_any().is::<PrefabInstance>())
+    .map(|(index, c)| Ok((index, serde_json::to_value(c)?)))
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+  use glam::Vec3;
+  use serde_json::json;
+
+  use super::*;
+  use crate::components::{Properties, Transform};
+
+  /// Names of the components of `id`, leaving out the [`PrefabInstance`].
+  fn names(scene: &Scene, id: Uuid) -> Vec<&'static str> {
+    let entity = scene.find(id).unwrap();
+    let content = entity.iter().filter(|c| !c.as_any().is::<PrefabInstance>());
+    content.map(|c| c.name()).collect()
+  }
+
+  fn child_names(scene: &Scene, id: Uuid) -> Vec<String> {
+    let entity = scene.find(id).unwrap();
+    entity.children().map(|c| c.name().to_string()).collect()
+  }
+
+  /// A scene holding a prefab of a `Transform`ed root with one child, and an instance.
+  fn scene() -> (Scene, Uuid, Uuid) {
+    let mut root = Entity::new("root");
+    root
+      .add_component(Transform::default())
+      .add_component(Properties::new())
+      .add_child(Entity::new("child"));
+    let mut scene = Scene::new();
+    let prefab = scene.add_prefab(Prefab::new("prefab", &root).unwrap());
+    let instance = scene.instantiate(prefab).unwrap();
+    (scene, prefab, instance)
+  }
+
+  #[test]
+  fn diff_points_at_changed_leaves() {
+    let a = json!({ "type": "T", "position": [0, 0, 0], "nested": { "a~b": 1, "c/d": 2 } });
+    let b = json!({ "type": "T", "position": [1, 0, 0], "nested": { "a~b": 1, "c/d": 3 } });
+    let mut paths = Vec::new();
+    diff(&a, &b, String::new(), &mut paths);
+    paths.sort();
+    assert_eq!(paths, ["/nested/c~1d", "/position"]);
+  }
+
+  #[test]
+  fn overrides_are_recorded_and_kept_on_propagate() {
+    let (mut scene, prefab, instance) = scene();
+    scene
+      .find_mut(instance)
+      .unwrap()
+      .get_component_mut::<Transform>()
+      .unwrap()
+      .position = Vec3::X;
+
+    scene
+      .edit_prefab(prefab, |root| {
+        let transform = root.get_component_mut::<Transform>().unwrap();
+        transform.position = Vec3::Y;
+        transform.scale = Vec3::splat(2.0);
+      })
+      .unwrap();
+
+    let entity = scene.find(instance).unwrap();
+    let transform = entity.get_component::<Transform>().unwrap();
+    assert_eq!(transform.position, Vec3::X);
+    assert_eq!(transform.scale, Vec3::splat(2.0));
+    let info = entity.get_component::<PrefabInstance>().unwrap().clone();
+    let prefab_root = scene.prefab(prefab).unwrap().root().unwrap().id();
+    assert_eq!(info.overrides.len(), 1);
+    assert_eq!(info.overrides[0].entity, prefab_root);
+    assert_eq!(info.overrides[0].component.as_deref(), Some("Transform"));
+    assert_eq!(info.overrides[0].path, "/position");
+
+    // The override survives later updates too, and reverting it restores the prefab value.
+    scene
+      .edit_prefab(prefab, |root| root.name = "renamed".into())
+      .unwrap();
+    let override_ = info.overrides[0].clone();
+    assert_eq!(scene.find(instance).unwrap().name(), "renamed");
+    let transform = |scene: &Scene| {
+      let entity = scene.find(instance).unwrap();
+      entity.get_component::<Transform>().unwrap().position
+    };
+    assert_eq!(transform(&scene), Vec3::X);
+    scene.revert_override(instance, &override_).unwrap();
+    assert_eq!(transform(&scene), Vec3::Y);
+  }
+
+  #[test]
+  fn children_added_and_removed_in_the_prefab_follow() {
+    let (mut scene, prefab, instance) = scene();
+    scene.add_child(instance, Entity::new("own"));
+
+    scene
+      .edit_prefab(prefab, |root| {
+        root.add_child(Entity::new("added"));
+      })
+      .unwrap();
+    assert_eq!(child_names(&scene, instance), ["child", "added", "own"]);
+
+    scene
+      .edit_prefab(prefab, |root| {
+        let child = root.children()[0].id();
+        root.remove_child(child);
+      })
+      .unwrap();
+    assert_eq!(child_names(&scene, instance), ["added", "own"]);
+  }
+
+  #[test]
+  fn reordered_components_follow_and_keep_overrides() {
+    let (mut scene, prefab, instance) = scene();
+    scene
+      .find_mut(instance)
+      .unwrap()
+      .get_component_mut::<Transform>()
+      .unwrap()
+      .position = Vec3::X;
+
+    scene
+      .edit_prefab(prefab, |root| {
+        let transform = root.remove_component::<Transform>().unwrap();
+        root.add_component(transform);
+      })
+      .unwrap();
+
+    assert_eq!(names(&scene, instance), ["Properties", "Transform"]);
+    let entity = scene.find(instance).unwrap();
+    assert_eq!(
+      entity.get_component::<Transform>().unwrap().position,
+      Vec3::X
+    );
+  }
+}
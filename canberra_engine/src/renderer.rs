@@ -1,39 +1,99 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, ops::Range};
 
-use glam::Mat4;
+use glam::{Mat4, Vec2, Vec3};
 
 use crate::{
-  Entity, Scene, Vertex,
-  components::{Material, Mesh, Transform},
+  EntityView, Frustum, Scene, Vertex,
+  background::Background,
+  components::{BlendMode, GlobeCamera, Material, Mesh, RenderLayers},
+  geo,
 };
 
 mod asset_manager;
+mod background_uniform;
 mod camera_uniform;
+mod draw_lists;
+mod draw_uniforms;
+mod gpu_background;
 mod gpu_mesh;
+mod gpu_polyline;
+mod gpu_timer;
+mod line_uniform_data;
+mod marker_batch;
 mod object_uniform_data;
+mod post_process;
+mod render_graph;
+mod render_settings;
 mod shader_registry;
 
+pub(crate) use self::{
+  asset_manager::MeshSource,
+  background_uniform::BackgroundUniform,
+  camera_uniform::CameraUniform,
+  draw_lists::{CollectContext, DrawLists},
+  draw_uniforms::DrawUniforms,
+  gpu_background::GpuBackground,
+  gpu_mesh::GpuMesh,
+  gpu_polyline::{GpuPolyline, LineSegment},
+  gpu_timer::GpuTimer,
+  line_uniform_data::LineUniformData,
+  marker_batch::{MarkerInstance, build_markers},
+  object_uniform_data::ObjectUniformData,
+  post_process::{HDR_FORMAT, PostProcess},
+};
 pub use self::{
   asset_manager::{AssetManager, MeshHandle},
-  shader_registry::{GLOBAL_SHADER_REGISTRY, ShaderHandle, ShaderRegistry, register_shaders},
-};
-pub(crate) use self::{
-  camera_uniform::CameraUniform, gpu_mesh::GpuMesh, object_uniform_data::ObjectUniformData,
+  draw_lists::RenderStats,
+  render_graph::{FrameGraph, GraphResource, PassContext, RenderGraph, TextureDesc, TextureSize},
+  render_settings::{PostSettings, RenderSettings, ToneMapping},
+  shader_registry::{
+    GLOBAL_SHADER_REGISTRY, PostPassHandle, ShaderHandle, ShaderRegistry, register_shaders,
+  },
 };
 
 const DEPTH_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Depth32Float;
 const OBJECT_STRIDE: u64 = 256;
-const MAX_OBJECTS: u64 = 256;
+/// Draws the per-draw uniform buffers have room for before they first grow.
+const INITIAL_OBJECTS: u64 = 256;
+const INITIAL_MARKERS: u64 = 1024;
+
+/// Pipelines built for one sample count.
+struct Pipelines {
+  meshes: HashMap<(ShaderHandle, BlendMode), wgpu::RenderPipeline>,
+  line: wgpu::RenderPipeline,
+  marker: wgpu::RenderPipeline,
+  background: wgpu::RenderPipeline,
+}
+
+/// Layouts and shader modules the [`Pipelines`] are rebuilt from.
+struct PipelineSources {
+  mesh_layout: wgpu::PipelineLayout,
+  line_layout: wgpu::PipelineLayout,
+  line_module: wgpu::ShaderModule,
+  marker_layout: wgpu::PipelineLayout,
+  marker_module: wgpu::ShaderModule,
+  background_layout: wgpu::PipelineLayout,
+  background_module: wgpu::ShaderModule,
+}
 
 pub struct Renderer {
-  pipelines: HashMap<ShaderHandle, wgpu::RenderPipeline>,
+  pipelines: Pipelines,
+  sources: PipelineSources,
+  /// Shader registry generation the mesh pipelines were built at.
+  shader_generation: u64,
+  graph: RenderGraph,
+  post: PostProcess,
+  post_settings: PostSettings,
+  sample_count: u32,
+  clear_color: wgpu::Color,
   camera_buffer: wgpu::Buffer,
   camera_bind_group: wgpu::BindGroup,
-  object_buffer: wgpu::Buffer,
-  object_bind_group: wgpu::BindGroup,
-  depth_texture: wgpu::Texture,
-  depth_view: wgpu::TextureView,
+  objects: DrawUniforms,
+  lines: DrawUniforms,
+  marker_buffer: wgpu::Buffer,
+  background: GpuBackground,
   asset_manager: AssetManager,
+  stats: RenderStats,
 }
 
 impl Renderer {
@@ -42,6 +102,7 @@ impl Renderer {
     surface_format: wgpu::TextureFormat,
     width: u32,
     height: u32,
+    settings: &RenderSettings,
   ) -> Self {
     let camera_bgl = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
       label: Some("camera_bgl"),
@@ -87,183 +148,491 @@ impl Renderer {
       }],
     });
 
-    let object_buffer = device.create_buffer(&wgpu::BufferDescriptor {
-      label: Some("object_buffer"),
-      size: MAX_OBJECTS * OBJECT_STRIDE,
-      usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
-      mapped_at_creation: false,
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+      label: Some("pipeline_layout"),
+      bind_group_layouts: &[Some(&camera_bgl), Some(&object_bgl)],
+      immediate_size: 0,
     });
 
-    let object_bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
-      label: Some("object_bg"),
-      layout: &object_bgl,
-      entries: &[wgpu::BindGroupEntry {
+    let line_bgl = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+      label: Some("line_bgl"),
+      entries: &[wgpu::BindGroupLayoutEntry {
         binding: 0,
-        resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
-          buffer: &object_buffer,
-          offset: 0,
-          size: wgpu::BufferSize::new(ObjectUniformData::size()),
-        }),
+        visibility: wgpu::ShaderStages::VERTEX_FRAGMENT,
+        ty: wgpu::BindingType::Buffer {
+          ty: wgpu::BufferBindingType::Uniform,
+          has_dynamic_offset: true,
+          min_binding_size: wgpu::BufferSize::new(LineUniformData::size()),
+        },
+        count: None,
       }],
     });
 
-    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
-      label: Some("pipeline_layout"),
-      bind_group_layouts: &[Some(&camera_bgl), Some(&object_bgl)],
+    let line_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+      label: Some("line_pipeline_layout"),
+      bind_group_layouts: &[Some(&camera_bgl), Some(&line_bgl)],
       immediate_size: 0,
     });
+    let line_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+      label: Some("Polyline"),
+      source: wgpu::ShaderSource::Wgsl(include_str!("shader_line.wgsl").into()),
+    });
+    let marker_pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+      label: Some("marker_pipeline_layout"),
+      bind_group_layouts: &[Some(&camera_bgl)],
+      immediate_size: 0,
+    });
+    let marker_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+      label: Some("Marker"),
+      source: wgpu::ShaderSource::Wgsl(include_str!("shader_marker.wgsl").into()),
+    });
+    let marker_buffer = Self::make_marker_buffer(device, INITIAL_MARKERS);
+    let background = GpuBackground::new(device);
+    let background_pipeline_layout =
+      device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+        label: Some("background_pipeline_layout"),
+        bind_group_layouts: &[Some(&background.layout)],
+        immediate_size: 0,
+      });
+    let background_module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+      label: Some("Background"),
+      source: wgpu::ShaderSource::Wgsl(include_str!("shader_background.wgsl").into()),
+    });
 
-    let registry = GLOBAL_SHADER_REGISTRY.load();
-    let pipelines = registry
-      .shaders
-      .iter()
-      .map(|(handle, shader)| {
-        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
-          label: Some(&shader.name),
-          source: wgpu::ShaderSource::Wgsl(shader.wgsl.clone().into()),
-        });
-        (
-          handle.clone(),
-          make_pipeline(device, &module, &pipeline_layout, surface_format),
-        )
-      })
-      .collect();
-
-    let (depth_texture, depth_view) = Self::make_depth_texture(device, width.max(1), height.max(1));
+    let sources = PipelineSources {
+      mesh_layout: pipeline_layout,
+      line_layout: line_pipeline_layout,
+      line_module,
+      marker_layout: marker_pipeline_layout,
+      marker_module,
+      background_layout: background_pipeline_layout,
+      background_module,
+    };
+    let (width, height) = (width.max(1), height.max(1));
+    let sample_count = settings.msaa_samples;
+    let [r, g, b, a] = settings.clear_color.map(f64::from);
 
     Self {
-      pipelines,
+      pipelines: sources.build(device, sample_count),
+      shader_generation: GLOBAL_SHADER_REGISTRY.load().generation(),
+      graph: RenderGraph::new(width, height),
+      sources,
+      post: PostProcess::new(device, surface_format),
+      post_settings: settings.post.clone(),
+      sample_count,
+      clear_color: wgpu::Color { r, g, b, a },
       camera_buffer,
       camera_bind_group,
-      object_buffer,
-      object_bind_group,
-      depth_texture,
-      depth_view,
+      objects: DrawUniforms::new(
+        device,
+        "object_buffer",
+        object_bgl,
+        ObjectUniformData::size(),
+        INITIAL_OBJECTS,
+      ),
+      lines: DrawUniforms::new(
+        device,
+        "line_buffer",
+        line_bgl,
+        LineUniformData::size(),
+        INITIAL_OBJECTS,
+      ),
+      marker_buffer,
+      background,
       asset_manager: AssetManager::new(),
+      stats: RenderStats::default(),
     }
   }
 
-  pub fn resize(&mut self, device: &wgpu::Device, width: u32, height: u32) {
-    let (t, v) = Self::make_depth_texture(device, width.max(1), height.max(1));
-    self.depth_texture = t;
-    self.depth_view = v;
+  /// Applies the runtime-adjustable part of `settings`, rebuilding pipelines and render
+  /// targets when the sample count changes.
+  pub fn apply_settings(&mut self, device: &wgpu::Device, settings: &RenderSettings) {
+    let [r, g, b, a] = settings.clear_color.map(f64::from);
+    self.clear_color = wgpu::Color { r, g, b, a };
+    self.post_settings = settings.post.clone();
+    if settings.msaa_samples != self.sample_count {
+      self.sample_count = settings.msaa_samples;
+      self.pipelines = self.sources.build(device, self.sample_count);
+    }
   }
 
-  pub fn render(
-    &mut self,
-    device: &wgpu::Device,
-    scene: &Scene,
-    queue: &wgpu::Queue,
-    view: &wgpu::TextureView,
-    encoder: &mut wgpu::CommandEncoder,
-    aspect: f32,
+  /// Sample counts usable for the scene on `device`: the HDR color and depth formats
+  /// must both support them.
+  pub fn supported_sample_counts(adapter: &wgpu::Adapter, device: &wgpu::Device) -> Vec<u32> {
+    let flags = |format: wgpu::TextureFormat| {
+      if device
+        .features()
+        .contains(wgpu::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+      {
+        adapter.get_texture_format_features(format).flags
+      } else {
+        format.guaranteed_format_features(device.features()).flags
+      }
+    };
+    let (color, depth) = (flags(HDR_FORMAT), flags(DEPTH_FORMAT));
+    color
+      .supported_sample_counts()
+      .into_iter()
+      .filter(|&n| depth.sample_count_supported(n))
+      .collect()
+  }
+
+  pub fn resize(&mut self, width: u32, height: u32) {
+    self.graph.resize(width, height);
+  }
+
+  /// Prepares `scene` for drawing into `view` and returns the frame's graph, holding the
+  /// scene and post-processing passes; callers may add their own passes, such as
+  /// overlays, before executing it.
+  pub fn render<'a>(
+    &'a mut self,
+    device: &'a wgpu::Device,
+    scene: &'a Scene,
+    queue: &'a wgpu::Queue,
+    view: &'a wgpu::TextureView,
+    viewport: Vec2,
     time: f32,
-  ) {
-    let view_proj = scene.camera_view_proj(aspect);
+  ) -> FrameGraph<'a> {
+    self.refresh_shaders(device);
+    self.asset_manager.begin_frame();
+    let view_proj = scene.camera_view_proj(viewport.x / viewport.y.max(1.0));
+    let proj_scale = scene
+      .active_camera()
+      .map(|(camera, _)| 1.0 / (camera.fov_y * 0.5).tan())
+      .unwrap_or(1.0);
     queue.write_buffer(
       &self.camera_buffer,
       0,
-      bytemuck::cast_slice(&[CameraUniform::new(view_proj.to_cols_array_2d(), time)]),
+      bytemuck::cast_slice(&[CameraUniform::new(
+        view_proj.to_cols_array_2d(),
+        time,
+        proj_scale,
+        viewport.into(),
+      )]),
     );
 
-    let mut renderables: Vec<(Mat4, &Entity)> = Vec::new();
-    for root in &scene.entities {
-      collect_renderables(root, Mat4::IDENTITY, &mut renderables);
-    }
+    let camera_pos = scene
+      .active_camera()
+      .map(|(_, world)| world.transform_point3(Vec3::ZERO))
+      .unwrap_or_default();
+    let background = BackgroundUniform::new(
+      &scene.background,
+      sky_inv_view_proj(scene, viewport).to_cols_array_2d(),
+      camera_pos,
+    );
+    self
+      .background
+      .update(device, queue, &scene.background, &background);
+    let globe = scene.roots().any(|e| e.has::<GlobeCamera>());
+    let context = CollectContext {
+      frustum: Frustum::from_view_proj(view_proj),
+      camera_pos,
+      proj_scale,
+      viewport_height: viewport.y,
+      horizon_eye: geo::horizon_eye(scene),
+      // The globe places geo-anchored entities itself; the flat frame is projected here.
+      geo_reference: scene.geo_reference.filter(|_| !globe),
+      layers: scene
+        .active_camera()
+        .map_or(RenderLayers::DEFAULT, |(camera, _)| camera.layers),
+    };
+    let DrawLists {
+      meshes,
+      polylines,
+      markers,
+      stats,
+    } = DrawLists::collect(scene, &context);
+    self.stats = stats;
 
-    let mut object_data = vec![0u8; renderables.len() * OBJECT_STRIDE as usize];
-    for (i, (world_mat, entity)) in renderables.iter().enumerate() {
-      let color = entity
-        .get_component::<Material>()
-        .map(|m| m.color)
-        .unwrap_or([1.0, 1.0, 1.0, 1.0]);
-      let uniform = ObjectUniformData {
+    // Transparent meshes go last, farthest first, so each blends over what is behind it.
+    let (opaque, mut transparent): (Vec<_>, Vec<_>) = meshes
+      .into_iter()
+      .partition(|(_, entity, _, _, _)| !blend_mode(*entity).is_transparent());
+    let depth = |(world, _, mesh, _, _): &(Mat4, EntityView, &Mesh, f32, MeshSource)| {
+      let center = mesh.bounds().map_or(Vec3::ZERO, |b| b.center());
+      world.transform_point3(center).distance_squared(camera_pos)
+    };
+    transparent.sort_by(|a, b| depth(b).total_cmp(&depth(a)));
+    let opaque_count = opaque.len();
+    let mut renderables = [opaque, transparent].concat();
+    renderables.truncate(self.objects.reserve(device, renderables.len()));
+    let opaque_count = opaque_count.min(renderables.len());
+    let object_data: Vec<_> = renderables
+      .iter()
+      .map(|(world_mat, entity, _, fade, _)| ObjectUniformData {
         model: world_mat.to_cols_array_2d(),
-        color,
-      };
-      let offset = i * OBJECT_STRIDE as usize;
-      let bytes = bytemuck::bytes_of(&uniform);
-      object_data[offset..offset + bytes.len()].copy_from_slice(bytes);
+        color: entity
+          .get_component::<Material>()
+          .map(|m| m.color)
+          .unwrap_or([1.0, 1.0, 1.0, 1.0]),
+        fade: *fade,
+        _pad: [0.0; 3],
+      })
+      .collect();
+    self.objects.write(queue, &object_data);
+
+    let mut polylines = polylines;
+    polylines.truncate(self.lines.reserve(device, polylines.len()));
+    let line_data: Vec<_> = polylines
+      .iter()
+      .map(|(world_mat, polyline, _)| LineUniformData::new(world_mat.to_cols_array_2d(), polyline))
+      .collect();
+    self.lines.write(queue, &line_data);
+
+    let marker_instances = build_markers(&markers, view_proj, viewport);
+    let marker_bytes: &[u8] = bytemuck::cast_slice(&marker_instances);
+    if marker_bytes.len() as u64 > self.marker_buffer.size() {
+      let capacity = (marker_instances.len() as u64).next_power_of_two();
+      self.marker_buffer = Self::make_marker_buffer(device, capacity);
     }
-    if !object_data.is_empty() {
-      queue.write_buffer(&self.object_buffer, 0, &object_data);
+    if !marker_bytes.is_empty() {
+      queue.write_buffer(&self.marker_buffer, 0, marker_bytes);
     }
+    let (marker_count, marker_bytes) = (marker_instances.len() as u32, marker_bytes.len() as u64);
 
-    let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
-      label: Some("Render Pass"),
-      color_attachments: &[Some(wgpu::RenderPassColorAttachment {
-        view,
-        resolve_target: None,
-        depth_slice: None,
-        ops: wgpu::Operations {
-          load: wgpu::LoadOp::Clear(wgpu::Color {
-            r: 0.1,
-            g: 0.2,
-            b: 0.3,
-            a: 1.0,
-          }),
-          store: wgpu::StoreOp::Store,
-        },
-      })],
-      depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
-        view: &self.depth_view,
-        depth_ops: Some(wgpu::Operations {
-          load: wgpu::LoadOp::Clear(1.0),
-          store: wgpu::StoreOp::Store,
-        }),
-        stencil_ops: None,
-      }),
-      occlusion_query_set: None,
-      timestamp_writes: None,
-      multiview_mask: None,
+    let draws_background = !matches!(scene.background, Background::Clear);
+    let polyline_segments = polylines
+      .iter()
+      .map(|(_, p, _)| p.points.len().saturating_sub(1))
+      .filter(|&n| n > 0);
+    self.stats.draw_calls = renderables.len()
+      + polyline_segments.clone().count()
+      + usize::from(marker_count > 0)
+      + usize::from(draws_background);
+    self.stats.triangles = renderables
+      .iter()
+      .map(|(_, _, mesh, _, _)| mesh.indices().len() / 3)
+      .sum::<usize>()
+      + polyline_segments.sum::<usize>() * (GpuPolyline::VERTICES / 3) as usize
+      + 2 * marker_count as usize
+      + usize::from(draws_background);
+
+    let Self {
+      pipelines,
+      graph,
+      post,
+      post_settings,
+      sample_count,
+      clear_color,
+      camera_bind_group,
+      objects,
+      lines,
+      marker_buffer,
+      background: gpu_background,
+      asset_manager,
+      ..
+    } = self;
+    let mut frame = graph.begin_frame(view);
+    let hdr = frame.create_texture(
+      TextureDesc::new("hdr", HDR_FORMAT).with_usage(wgpu::TextureUsages::TEXTURE_BINDING),
+    );
+    let depth = frame.create_texture(
+      TextureDesc::new("depth_texture", DEPTH_FORMAT).with_sample_count(*sample_count),
+    );
+    let msaa = (*sample_count > 1).then(|| {
+      frame.create_texture(
+        TextureDesc::new("msaa_texture", HDR_FORMAT).with_sample_count(*sample_count),
+      )
     });
+    let scene_writes: Vec<_> = [hdr, depth].into_iter().chain(msaa).collect();
 
-    for (i, (_, entity)) in renderables.iter().enumerate() {
-      let shader = entity
-        .get_component::<Material>()
-        .map(|m| m.shader)
-        .unwrap_or_default();
-      let pipeline = self.pipelines.get(&shader).unwrap_or_else(|| {
-        self
-          .pipelines
-          .get(&ShaderHandle::default())
-          .expect("No default pipeline")
+    frame.add_pass("scene", &[], &scene_writes, move |ctx| {
+      let hdr_view = ctx.view(hdr);
+      let msaa_view = msaa.map(|msaa| ctx.view(msaa));
+      let mut pass = ctx.encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+        label: Some("Render Pass"),
+        color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+          view: msaa_view.unwrap_or(hdr_view),
+          resolve_target: msaa_view.map(|_| hdr_view),
+          depth_slice: None,
+          ops: wgpu::Operations {
+            load: wgpu::LoadOp::Clear(*clear_color),
+            // Only the resolved image is needed once the pass ends.
+            store: if msaa_view.is_some() {
+              wgpu::StoreOp::Discard
+            } else {
+              wgpu::StoreOp::Store
+            },
+          },
+        })],
+        depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+          view: ctx.view(depth),
+          depth_ops: Some(wgpu::Operations {
+            load: wgpu::LoadOp::Clear(1.0),
+            store: wgpu::StoreOp::Store,
+          }),
+          stencil_ops: None,
+        }),
+        occlusion_query_set: None,
+        timestamp_writes: None,
+        multiview_mask: None,
       });
-      pass.set_pipeline(pipeline);
-      pass.set_bind_group(0, &self.camera_bind_group, &[]);
 
-      let mesh = entity.get_component::<Mesh>().unwrap();
-      let (_, gpu_mesh) = self.asset_manager.get_or_upload(device, mesh);
+      if draws_background {
+        pass.set_pipeline(&pipelines.background);
+        pass.set_bind_group(0, &gpu_background.bind_group, &[]);
+        pass.draw(0..3, 0..1);
+      }
+
+      let draw_meshes =
+        |pass: &mut wgpu::RenderPass, assets: &mut AssetManager, range: Range<usize>| {
+          for i in range {
+            let (_, entity, mesh, _, source) = renderables[i];
+            let (shader, blend) = entity
+              .get_component::<Material>()
+              .map(|m| (m.shader, m.blend))
+              .unwrap_or_default();
+            let pipeline = pipelines.meshes.get(&(shader, blend)).unwrap_or_else(|| {
+              pipelines
+                .meshes
+                .get(&(ShaderHandle::default(), blend))
+                .expect("No default pipeline")
+            });
+            pass.set_pipeline(pipeline);
+            pass.set_bind_group(0, &*camera_bind_group, &[]);
 
-      let dynamic_offset = (i as u64 * OBJECT_STRIDE) as u32;
-      pass.set_bind_group(1, &self.object_bind_group, &[dynamic_offset]);
-      pass.set_vertex_buffer(0, gpu_mesh.vertex_buffer.slice(..));
-      pass.set_index_buffer(gpu_mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
-      pass.draw_indexed(0..gpu_mesh.index_count, 0, 0..1);
+            let (_, gpu_mesh) = assets.get_or_upload(device, source, mesh);
+
+            pass.set_bind_group(1, objects.bind_group(), &[DrawUniforms::offset(i)]);
+            pass.set_vertex_buffer(0, gpu_mesh.vertex_buffer.slice(..));
+            pass.set_index_buffer(gpu_mesh.index_buffer.slice(..), wgpu::IndexFormat::Uint16);
+            pass.draw_indexed(0..gpu_mesh.index_count, 0, 0..1);
+          }
+        };
+      draw_meshes(&mut pass, asset_manager, 0..opaque_count);
+
+      pass.set_pipeline(&pipelines.line);
+      pass.set_bind_group(0, &*camera_bind_group, &[]);
+      for (i, (_, polyline, source)) in polylines.iter().enumerate() {
+        let gpu_polyline = asset_manager.get_or_upload_polyline(device, *source, polyline);
+        if gpu_polyline.segment_count == 0 {
+          continue;
+        }
+        pass.set_bind_group(1, lines.bind_group(), &[DrawUniforms::offset(i)]);
+        pass.set_vertex_buffer(0, gpu_polyline.instance_buffer.slice(..));
+        pass.draw(0..GpuPolyline::VERTICES, 0..gpu_polyline.segment_count);
+      }
+
+      if marker_count > 0 {
+        pass.set_pipeline(&pipelines.marker);
+        pass.set_bind_group(0, &*camera_bind_group, &[]);
+        pass.set_vertex_buffer(0, marker_buffer.slice(..marker_bytes));
+        pass.draw(0..6, 0..marker_count);
+      }
+
+      draw_meshes(&mut pass, asset_manager, opaque_count..renderables.len());
+    });
+
+    post.add_passes(&mut frame, queue, hdr, post_settings, time);
+    frame
+  }
+
+  /// Rebuilds the pipelines of shaders registered or replaced since they were built.
+  fn refresh_shaders(&mut self, device: &wgpu::Device) {
+    let generation = GLOBAL_SHADER_REGISTRY.load().generation();
+    if generation != self.shader_generation {
+      self.pipelines = self.sources.build(device, self.sample_count);
+      self.shader_generation = generation;
     }
+    self.post.refresh(device);
   }
 
-  fn make_depth_texture(
-    device: &wgpu::Device,
-    width: u32,
-    height: u32,
-  ) -> (wgpu::Texture, wgpu::TextureView) {
-    let texture = device.create_texture(&wgpu::TextureDescriptor {
-      label: Some("depth_texture"),
-      size: wgpu::Extent3d {
-        width,
-        height,
-        depth_or_array_layers: 1,
+  /// Culling and draw statistics from the last [`Renderer::render`].
+  pub fn stats(&self) -> RenderStats {
+    self.stats
+  }
+
+  /// Device features [`Renderer::enable_gpu_timing`] needs.
+  pub const GPU_TIMING_FEATURES: wgpu::Features = GpuTimer::FEATURES;
+
+  /// Times each render graph pass on the GPU from now on; returns `false` when the
+  /// device lacks timestamp queries inside encoders.
+  pub fn enable_gpu_timing(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> bool {
+    self.graph.enable_timing(device, queue)
+  }
+
+  /// GPU milliseconds per pass of a recent frame, or `None` without GPU timing.
+  pub fn pass_timings(&self) -> Option<&[(&'static str, f32)]> {
+    self.graph.pass_timings()
+  }
+
+  pub fn assets(&self) -> &AssetManager {
+    &self.asset_manager
+  }
+
+  fn make_marker_buffer(device: &wgpu::Device, capacity: u64) -> wgpu::Buffer {
+    device.create_buffer(&wgpu::BufferDescriptor {
+      label: Some("marker_buffer"),
+      size: capacity * std::mem::size_of::<MarkerInstance>() as u64,
+      usage: wgpu::BufferUsages::VERTEX | wgpu::BufferUsages::COPY_DST,
+      mapped_at_creation: false,
+    })
+  }
+}
+
+impl PipelineSources {
+  fn build(&self, device: &wgpu::Device, sample_count: u32) -> Pipelines {
+    let registry = GLOBAL_SHADER_REGISTRY.load();
+    let meshes = registry
+      .shaders
+      .iter()
+      .flat_map(|(handle, shader)| {
+        let module = device.create_shader_module(wgpu::ShaderModuleDescriptor {
+          label: Some(&shader.name),
+          source: wgpu::ShaderSource::Wgsl(shader.wgsl.clone().into()),
+        });
+        BlendMode::ALL.map(|blend| {
+          let pipeline = make_pipeline(
+            device,
+            &module,
+            &self.mesh_layout,
+            HDR_FORMAT,
+            sample_count,
+            blend,
+          );
+          ((*handle, blend), pipeline)
+        })
+      })
+      .collect();
+    let line = make_instanced_pipeline(
+      device,
+      "line_pipeline",
+      &self.line_module,
+      &self.line_layout,
+      HDR_FORMAT,
+      sample_count,
+      wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<LineSegment>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &LineSegment::ATTRIBUTES,
       },
-      mip_level_count: 1,
-      sample_count: 1,
-      dimension: wgpu::TextureDimension::D2,
-      format: DEPTH_FORMAT,
-      usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
-      view_formats: &[],
-    });
-    let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
-    (texture, view)
+    );
+    let marker = make_instanced_pipeline(
+      device,
+      "marker_pipeline",
+      &self.marker_module,
+      &self.marker_layout,
+      HDR_FORMAT,
+      sample_count,
+      wgpu::VertexBufferLayout {
+        array_stride: std::mem::size_of::<MarkerInstance>() as wgpu::BufferAddress,
+        step_mode: wgpu::VertexStepMode::Instance,
+        attributes: &MarkerInstance::ATTRIBUTES,
+      },
+    );
+    let background = make_background_pipeline(
+      device,
+      &self.background_module,
+      &self.background_layout,
+      HDR_FORMAT,
+      sample_count,
+    );
+    Pipelines {
+      meshes,
+      line,
+      marker,
+      background,
+    }
   }
 }
 
@@ -271,8 +640,27 @@ fn make_pipeline(
   device: &wgpu::Device,
   shader: &wgpu::ShaderModule,
   pipeline_layout: &wgpu::PipelineLayout,
-  surface_format: wgpu::TextureFormat,
+  format: wgpu::TextureFormat,
+  sample_count: u32,
+  blend: BlendMode,
 ) -> wgpu::RenderPipeline {
+  let blend_state = match blend {
+    BlendMode::Opaque => wgpu::BlendState::REPLACE,
+    BlendMode::AlphaBlend => wgpu::BlendState::ALPHA_BLENDING,
+    BlendMode::Additive => wgpu::BlendState {
+      color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::SrcAlpha,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+      },
+      alpha: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::Zero,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+      },
+    },
+    BlendMode::Premultiplied => wgpu::BlendState::PREMULTIPLIED_ALPHA_BLENDING,
+  };
   device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
     label: Some("render_pipeline"),
     layout: Some(pipeline_layout),
@@ -301,8 +689,8 @@ fn make_pipeline(
       module: shader,
       entry_point: Some("fs_main"),
       targets: &[Some(wgpu::ColorTargetState {
-        format: surface_format,
-        blend: Some(wgpu::BlendState::REPLACE),
+        format,
+        blend: Some(blend_state),
         write_mask: wgpu::ColorWrites::ALL,
       })],
       compilation_options: Default::default(),
@@ -318,31 +706,138 @@ fn make_pipeline(
     },
     depth_stencil: Some(wgpu::DepthStencilState {
       format: DEPTH_FORMAT,
-      depth_write_enabled: Some(true),
+      // Transparent surfaces are depth-tested but must not hide what is drawn after them.
+      depth_write_enabled: Some(!blend.is_transparent()),
       depth_compare: Some(wgpu::CompareFunction::Less),
       stencil: wgpu::StencilState::default(),
       bias: wgpu::DepthBiasState::default(),
     }),
-    multisample: wgpu::MultisampleState::default(),
+    multisample: wgpu::MultisampleState {
+      count: sample_count,
+      ..Default::default()
+    },
     multiview_mask: None,
     cache: None,
   })
 }
 
-fn collect_renderables<'a>(
-  entity: &'a Entity,
-  parent_world: Mat4,
-  out: &mut Vec<(Mat4, &'a Entity)>,
-) {
-  let local = entity
-    .get_component::<Transform>()
-    .map(|t| t.matrix())
-    .unwrap_or(Mat4::IDENTITY);
-  let world = parent_world * local;
-  if entity.get_component::<Mesh>().is_some() {
-    out.push((world, entity));
-  }
-  for child in entity.children() {
-    collect_renderables(child, world, out);
-  }
+/// Pipeline for shaders that expand per-instance data into screen-space quads.
+fn make_instanced_pipeline(
+  device: &wgpu::Device,
+  label: &str,
+  shader: &wgpu::ShaderModule,
+  pipeline_layout: &wgpu::PipelineLayout,
+  format: wgpu::TextureFormat,
+  sample_count: u32,
+  instances: wgpu::VertexBufferLayout,
+) -> wgpu::RenderPipeline {
+  device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+    label: Some(label),
+    layout: Some(pipeline_layout),
+    vertex: wgpu::VertexState {
+      module: shader,
+      entry_point: Some("vs_main"),
+      buffers: &[instances],
+      compilation_options: Default::default(),
+    },
+    fragment: Some(wgpu::FragmentState {
+      module: shader,
+      entry_point: Some("fs_main"),
+      targets: &[Some(wgpu::ColorTargetState {
+        format,
+        blend: Some(wgpu::BlendState::REPLACE),
+        write_mask: wgpu::ColorWrites::ALL,
+      })],
+      compilation_options: Default::default(),
+    }),
+    primitive: wgpu::PrimitiveState {
+      topology: wgpu::PrimitiveTopology::TriangleList,
+      strip_index_format: None,
+      front_face: wgpu::FrontFace::Ccw,
+      // Quads are built in screen space, so their winding is arbitrary.
+      cull_mode: None,
+      polygon_mode: wgpu::PolygonMode::Fill,
+      unclipped_depth: false,
+      conservative: false,
+    },
+    depth_stencil: Some(wgpu::DepthStencilState {
+      format: DEPTH_FORMAT,
+      depth_write_enabled: Some(true),
+      depth_compare: Some(wgpu::CompareFunction::LessEqual),
+      stencil: wgpu::StencilState::default(),
+      bias: wgpu::DepthBiasState::default(),
+    }),
+    multisample: wgpu::MultisampleState {
+      count: sample_count,
+      ..Default::default()
+    },
+    multiview_mask: None,
+    cache: None,
+  })
+}
+
+/// Full-screen pipeline for the background; it lies at the far plane and leaves depth
+/// untouched, so everything drawn afterwards covers it.
+fn make_background_pipeline(
+  device: &wgpu::Device,
+  shader: &wgpu::ShaderModule,
+  pipeline_layout: &wgpu::PipelineLayout,
+  format: wgpu::TextureFormat,
+  sample_count: u32,
+) -> wgpu::RenderPipeline {
+  device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+    label: Some("background_pipeline"),
+    layout: Some(pipeline_layout),
+    vertex: wgpu::VertexState {
+      module: shader,
+      entry_point: Some("vs_main"),
+      buffers: &[],
+      compilation_options: Default::default(),
+    },
+    fragment: Some(wgpu::FragmentState {
+      module: shader,
+      entry_point: Some("fs_main"),
+      targets: &[Some(wgpu::ColorTargetState {
+        format,
+        blend: Some(wgpu::BlendState::REPLACE),
+        write_mask: wgpu::ColorWrites::ALL,
+      })],
+      compilation_options: Default::default(),
+    }),
+    primitive: wgpu::PrimitiveState::default(),
+    depth_stencil: Some(wgpu::DepthStencilState {
+      format: DEPTH_FORMAT,
+      depth_write_enabled: Some(false),
+      depth_compare: Some(wgpu::CompareFunction::Always),
+      stencil: wgpu::StencilState::default(),
+      bias: wgpu::DepthBiasState::default(),
+    }),
+    multisample: wgpu::MultisampleState {
+      count: sample_count,
+      ..Default::default()
+    },
+    multiview_mask: None,
+    cache: None,
+  })
+}
+
+/// Maps clip space to view directions using only the camera's rotation, which keeps
+/// the background precise however far the camera is from the origin.
+fn sky_inv_view_proj(scene: &Scene, viewport: Vec2) -> Mat4 {
+  scene
+    .active_camera()
+    .map(|(camera, world)| {
+      let mut camera = camera.clone();
+      camera.aspect = viewport.x / viewport.y.max(1.0);
+      let (_, rotation, _) = world.to_scale_rotation_translation();
+      (camera.projection_matrix() * Mat4::from_quat(rotation.inverse())).inverse()
+    })
+    .unwrap_or(Mat4::IDENTITY)
+}
+
+fn blend_mode(entity: EntityView<'_>) -> BlendMode {
+  entity
+    .get_component::<Material>()
+    .map(|m| m.blend)
+    .unwrap_or_default()
 }
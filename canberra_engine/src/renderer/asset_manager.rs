@@ -1,13 +1,20 @@
 use std::{
+  any::TypeId,
   collections::{
-    HashMap,
+    HashMap, HashSet,
     hash_map::{DefaultHasher, Entry},
   },
   hash::{Hash, Hasher},
+  mem,
 };
 
-use super::GpuMesh;
-use crate::components::Mesh;
+use uuid::Uuid;
+
+use super::{GpuMesh, GpuPolyline};
+use crate::{
+  Component, EntityView,
+  components::{Mesh, Polyline},
+};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct MeshHandle(u64);
@@ -15,30 +22,83 @@ pub struct MeshHandle(u64);
 impl MeshHandle {
   fn from_mesh(mesh: &Mesh) -> Self {
     let mut h = DefaultHasher::new();
-    bytemuck::cast_slice::<_, u8>(&mesh.vertices).hash(&mut h);
-    bytemuck::cast_slice::<_, u8>(&mesh.indices).hash(&mut h);
+    bytemuck::cast_slice::<_, u8>(mesh.vertices()).hash(&mut h);
+    bytemuck::cast_slice::<_, u8>(mesh.indices()).hash(&mut h);
     Self(h.finish())
   }
 }
 
+/// The component a drawn mesh or polyline comes from, as of its last change. Its GPU
+/// buffers are reused while the component is unchanged instead of re-hashing the vertices
+/// every frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) struct MeshSource {
+  entity: Uuid,
+  component: TypeId,
+  /// Level within a `Terrain` or `Lod`; 0 for a `Mesh` or `Polyline`.
+  level: usize,
+  changed: u64,
+}
+
+impl MeshSource {
+  pub(crate) fn new<C: Component>(entity: EntityView<'_>, level: usize) -> Self {
+    Self {
+      entity: entity.id(),
+      component: TypeId::of::<C>(),
+      level,
+      changed: entity.component_ticks::<C>().map_or(0, |t| t.changed),
+    }
+  }
+}
+
 pub struct AssetManager {
   meshes: HashMap<MeshHandle, GpuMesh>,
+  /// Handles of the meshes drawn this frame and the previous one; older entries are
+  /// dropped so despawned and changed sources do not pile up.
+  sources: HashMap<MeshSource, MeshHandle>,
+  previous_sources: HashMap<MeshSource, MeshHandle>,
+  /// Polylines drawn this frame and the previous one, rotated like `sources`.
+  polylines: HashMap<MeshSource, GpuPolyline>,
+  previous_polylines: HashMap<MeshSource, GpuPolyline>,
 }
 
 impl AssetManager {
   pub fn new() -> Self {
     Self {
       meshes: HashMap::with_capacity(64),
+      sources: HashMap::with_capacity(64),
+      previous_sources: HashMap::with_capacity(64),
+      polylines: HashMap::new(),
+      previous_polylines: HashMap::new(),
     }
   }
 
-  /// Returns the handle for `mesh`, uploading it to the GPU exactly once.
+  /// Forgets the sources not drawn since the previous call, and frees the meshes none of
+  /// the remaining sources refer to along with the polylines not drawn since then.
+  pub(crate) fn begin_frame(&mut self) {
+    self.previous_polylines = mem::take(&mut self.polylines);
+    mem::swap(&mut self.sources, &mut self.previous_sources);
+    self.sources.clear();
+    let referenced: HashSet<MeshHandle> = self.previous_sources.values().copied().collect();
+    self.meshes.retain(|handle, _| referenced.contains(handle));
+  }
+
+  /// Returns the handle for `mesh`, uploading it to the GPU exactly once. The mesh is only
+  /// hashed when `source` has changed since it was last drawn.
   pub(crate) fn get_or_upload(
     &mut self,
     device: &wgpu::Device,
+    source: MeshSource,
     mesh: &Mesh,
   ) -> (MeshHandle, &GpuMesh) {
-    let handle = MeshHandle::from_mesh(mesh);
+    let cached = self
+      .sources
+      .get(&source)
+      .or_else(|| self.previous_sources.get(&source))
+      .copied()
+      .filter(|handle| self.meshes.contains_key(handle));
+    let handle = cached.unwrap_or_else(|| MeshHandle::from_mesh(mesh));
+    self.sources.insert(source, handle);
     let gpu_mesh = match self.meshes.entry(handle) {
       Entry::Occupied(e) => e.into_mut(),
       Entry::Vacant(e) => e.insert(GpuMesh::upload(device, mesh)),
@@ -46,6 +106,25 @@ impl AssetManager {
     (handle, gpu_mesh)
   }
 
+  /// Returns the segment buffer for `polyline`, uploading its points again only when
+  /// `source` has changed since it was last drawn.
+  pub(crate) fn get_or_upload_polyline(
+    &mut self,
+    device: &wgpu::Device,
+    source: MeshSource,
+    polyline: &Polyline,
+  ) -> &GpuPolyline {
+    match self.polylines.entry(source) {
+      Entry::Occupied(e) => e.into_mut(),
+      Entry::Vacant(e) => e.insert(
+        self
+          .previous_polylines
+          .remove(&source)
+          .unwrap_or_else(|| GpuPolyline::upload(device, polyline)),
+      ),
+    }
+  }
+
   #[allow(dead_code)]
   pub(crate) fn get(&self, handle: MeshHandle) -> Option<&GpuMesh> {
     self.meshes.get(&handle)
@@ -62,6 +141,20 @@ impl AssetManager {
   pub fn is_empty(&self) -> bool {
     self.meshes.is_empty()
   }
+
+  /// Bytes of vertex, index and segment buffers held on the GPU.
+  pub fn gpu_bytes(&self) -> u64 {
+    let meshes = self
+      .meshes
+      .values()
+      .map(|m| m.vertex_buffer.size() + m.index_buffer.size());
+    let polylines = self
+      .polylines
+      .values()
+      .chain(self.previous_polylines.values())
+      .map(|p| p.instance_buffer.size());
+    meshes.chain(polylines).sum()
+  }
 }
 
 impl Default for AssetManager {
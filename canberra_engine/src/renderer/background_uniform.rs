@@ -0,0 +1,77 @@
+use glam::Vec3;
+
+use crate::background::Background;
+
+/// Mode values understood by `shader_background.wgsl`.
+const MODE_CLEAR: u32 = 0;
+const MODE_GRADIENT: u32 = 1;
+const MODE_SKYBOX: u32 = 2;
+const MODE_ATMOSPHERE: u32 = 3;
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct BackgroundUniform {
+  /// Inverse of the projection times the view rotation, mapping clip space to directions.
+  pub(crate) inv_view_proj: [[f32; 4]; 4],
+  pub(crate) camera_pos: [f32; 3],
+  pub(crate) mode: u32,
+  pub(crate) zenith: [f32; 4],
+  pub(crate) horizon: [f32; 4],
+  pub(crate) ground: [f32; 4],
+  pub(crate) sun_direction: [f32; 3],
+  pub(crate) sun_intensity: f32,
+  pub(crate) rayleigh_scattering: [f32; 3],
+  pub(crate) mie_scattering: f32,
+  pub(crate) planet_radius: f32,
+  pub(crate) atmosphere_radius: f32,
+  pub(crate) rayleigh_scale_height: f32,
+  pub(crate) mie_scale_height: f32,
+  pub(crate) mie_anisotropy: f32,
+  pub(crate) skybox_intensity: f32,
+  pub(crate) _pad: [f32; 2],
+}
+
+impl BackgroundUniform {
+  pub(crate) fn new(
+    background: &Background,
+    inv_view_proj: [[f32; 4]; 4],
+    camera_pos: Vec3,
+  ) -> Self {
+    let mut uniform = Self {
+      inv_view_proj,
+      camera_pos: camera_pos.into(),
+      ..bytemuck::Zeroable::zeroed()
+    };
+    match background {
+      Background::Clear => uniform.mode = MODE_CLEAR,
+      Background::Gradient(gradient) => {
+        let rgb = |[r, g, b]: [f32; 3]| [r, g, b, 1.0];
+        uniform.mode = MODE_GRADIENT;
+        uniform.zenith = rgb(gradient.zenith);
+        uniform.horizon = rgb(gradient.horizon);
+        uniform.ground = rgb(gradient.ground);
+      }
+      Background::Skybox(skybox) => {
+        uniform.mode = MODE_SKYBOX;
+        uniform.skybox_intensity = skybox.intensity;
+      }
+      Background::Atmosphere(atmosphere) => {
+        uniform.mode = MODE_ATMOSPHERE;
+        uniform.sun_direction = atmosphere.sun_direction.normalize_or(Vec3::Y).into();
+        uniform.sun_intensity = atmosphere.sun_intensity;
+        uniform.rayleigh_scattering = atmosphere.rayleigh_scattering;
+        uniform.mie_scattering = atmosphere.mie_scattering;
+        uniform.planet_radius = atmosphere.planet_radius;
+        uniform.atmosphere_radius = atmosphere.planet_radius + atmosphere.atmosphere_height;
+        uniform.rayleigh_scale_height = atmosphere.rayleigh_scale_height;
+        uniform.mie_scale_height = atmosphere.mie_scale_height;
+        uniform.mie_anisotropy = atmosphere.mie_anisotropy;
+      }
+    }
+    uniform
+  }
+
+  pub(crate) const fn size() -> u64 {
+    size_of::<Self>() as u64
+  }
+}
@@ -3,15 +3,24 @@
 pub(crate) struct CameraUniform {
   pub(crate) view_proj: [[f32; 4]; 4],
   pub(crate) time: f32,
-  _pad: [f32; 3],
+  /// `1 / tan(fov_y / 2)`: converts world lengths at depth `w` to NDC lengths.
+  pub(crate) proj_scale: f32,
+  /// Render target size in physical pixels.
+  pub(crate) viewport: [f32; 2],
 }
 
 impl CameraUniform {
-  pub(crate) fn new(view_proj: [[f32; 4]; 4], time: f32) -> Self {
+  pub(crate) fn new(
+    view_proj: [[f32; 4]; 4],
+    time: f32,
+    proj_scale: f32,
+    viewport: [f32; 2],
+  ) -> Self {
     Self {
       view_proj,
       time,
-      _pad: [0.0; 3],
+      proj_scale,
+      viewport,
     }
   }
 
@@ -0,0 +1,245 @@
+use glam::{DVec3, Mat4, Vec3};
+
+use super::asset_manager::MeshSource;
+use crate::{
+  Aabb, EntityView, Frustum, Scene,
+  components::{
+    GeoPosition, LineUnit, Lod, Marker, Mesh, Polyline, RenderLayers, Terrain, Transform,
+    Visibility,
+  },
+  geo::{self, LocalProjection},
+};
+
+/// Renderables submitted and skipped by culling in the last frame, and the work the
+/// scene pass issued for them.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RenderStats {
+  pub drawn: usize,
+  /// Outside the view frustum or behind the globe.
+  pub culled: usize,
+  pub draw_calls: usize,
+  pub triangles: usize,
+}
+
+pub(crate) struct CollectContext {
+  pub(crate) frustum: Frustum,
+  pub(crate) camera_pos: Vec3,
+  /// `1 / tan(fov_y / 2)` of the active camera.
+  pub(crate) proj_scale: f32,
+  /// Height of the viewport in pixels.
+  pub(crate) viewport_height: f32,
+  pub(crate) horizon_eye: Option<DVec3>,
+  /// Frame for placing geo-anchored markers, when not in globe mode.
+  pub(crate) geo_reference: Option<LocalProjection>,
+  /// Layers of the active camera.
+  pub(crate) layers: RenderLayers,
+}
+
+impl CollectContext {
+  /// Grows world-space `bounds` by `pixels` on screen, measured at their far side so
+  /// geometry drawn at a constant screen size is covered wherever it lies in the box.
+  fn inflate_pixels(&self, bounds: Aabb, pixels: f32) -> Aabb {
+    let distance = bounds.center().distance(self.camera_pos) + bounds.radius();
+    let metres_per_pixel =
+      2.0 * distance / (self.proj_scale * self.viewport_height).max(f32::EPSILON);
+    bounds.inflate(pixels * metres_per_pixel)
+  }
+}
+
+#[derive(Default)]
+pub(crate) struct DrawLists<'a> {
+  /// World matrix, owning entity, mesh, cross-fade (see `ObjectUniformData::fade`) and
+  /// the component the mesh comes from.
+  pub(crate) meshes: Vec<(Mat4, EntityView<'a>, &'a Mesh, f32, MeshSource)>,
+  /// World matrix, polyline and the component it comes from.
+  pub(crate) polylines: Vec<(Mat4, &'a Polyline, MeshSource)>,
+  /// World position of each marker.
+  pub(crate) markers: Vec<(Vec3, &'a Marker)>,
+  pub(crate) stats: RenderStats,
+}
+
+/// One entity from the bounds pass, stored in pre-order.
+struct Node {
+  world: Mat4,
+  /// World bounds of the entity's own renderables.
+  own: Option<Aabb>,
+  /// World bounds of the entity and all its descendants.
+  subtree: Option<Aabb>,
+  /// Nodes in the subtree, this one included.
+  len: usize,
+  own_count: usize,
+  subtree_count: usize,
+}
+
+impl<'a> DrawLists<'a> {
+  /// Gathers everything visible from `context`, skipping whole subtrees whose bounds fall
+  /// outside the frustum. Hidden entities and those off the camera's layers are neither
+  /// drawn nor counted.
+  pub(crate) fn collect(scene: &'a Scene, context: &CollectContext) -> Self {
+    let mut nodes = Vec::new();
+    for root in scene.roots() {
+      measure(root, Mat4::IDENTITY, context, &mut nodes);
+    }
+    let mut lists = Self::default();
+    let mut cursor = 0;
+    for root in scene.roots() {
+      lists.visit(root, &nodes, &mut cursor, context, true);
+    }
+    lists
+  }
+
+  fn visit(
+    &mut self,
+    entity: EntityView<'a>,
+    nodes: &[Node],
+    cursor: &mut usize,
+    context: &CollectContext,
+    parent_visible: bool,
+  ) {
+    let node = &nodes[*cursor];
+    let outside =
+      |bounds: Option<Aabb>| bounds.is_some_and(|b| !context.frustum.intersects_aabb(&b));
+    if outside(node.subtree) {
+      self.stats.culled += node.subtree_count;
+      *cursor += node.len;
+      return;
+    }
+    *cursor += 1;
+
+    let visible = Visibility::of(entity).resolve(parent_visible);
+    let shown = visible && context.layers.intersects(RenderLayers::of(entity));
+    let world = node.world;
+    let geo_position = entity.get_component::<GeoPosition>();
+    let occluded = match (context.horizon_eye, geo_position) {
+      (Some(eye), Some(&position)) => geo::below_horizon(eye, geo::geodetic_to_ecef(position)),
+      _ => false,
+    };
+    // Occluded entities are hidden behind the globe; children may carry their own anchors.
+    // Children of hidden entities may still be shown, so they are visited either way.
+    if !shown {
+      // Neither drawn nor culled.
+    } else if occluded || outside(node.own) {
+      self.stats.culled += node.own_count;
+    } else {
+      self.stats.drawn += node.own_count;
+      if let Some(terrain) = entity.get_component::<Terrain>() {
+        let local_camera = world.inverse().transform_point3(context.camera_pos);
+        let lod = terrain.lod_for_distance(terrain.distance_to(local_camera));
+        let source = MeshSource::new::<Terrain>(entity, lod);
+        self
+          .meshes
+          .push((world, entity, terrain.mesh(lod), 1.0, source));
+      } else if let Some(lod) = entity.get_component::<Lod>() {
+        let coverage = node.own.map_or(f32::INFINITY, |b| {
+          let distance = b.center().distance(context.camera_pos);
+          b.radius() * context.proj_scale / distance.max(f32::EPSILON)
+        });
+        for (i, (level, weight)) in lod.select(coverage).enumerate() {
+          // The coarser of two fading levels takes the complementary dither pattern.
+          let fade = if i == 0 { weight } else { -weight };
+          let source = MeshSource::new::<Lod>(entity, level);
+          self
+            .meshes
+            .push((world, entity, &lod.levels[level].mesh, fade, source));
+        }
+      } else if let Some(mesh) = entity.get_component::<Mesh>() {
+        let source = MeshSource::new::<Mesh>(entity, 0);
+        self.meshes.push((world, entity, mesh, 1.0, source));
+      }
+      if let Some(polyline) = entity.get_component::<Polyline>() {
+        let source = MeshSource::new::<Polyline>(entity, 0);
+        self.polylines.push((world, polyline, source));
+      }
+      if let Some(marker) = entity.get_component::<Marker>() {
+        self
+          .markers
+          .push((marker_position(entity, world, context), marker));
+      }
+    }
+
+    for child in entity.children() {
+      self.visit(child, nodes, cursor, context, visible);
+    }
+  }
+}
+
+/// Bounds pass: pushes the entity's subtree onto `nodes` and returns its world bounds
+/// and renderable count.
+fn measure(
+  entity: EntityView<'_>,
+  parent_world: Mat4,
+  context: &CollectContext,
+  nodes: &mut Vec<Node>,
+) -> (Option<Aabb>, usize) {
+  let local = entity
+    .get_component::<Transform>()
+    .map(|t| t.matrix())
+    .unwrap_or(Mat4::IDENTITY);
+  let world = parent_world * local;
+
+  let mut own = None;
+  let mut own_count = 0;
+  let mut add = |bounds: Option<Aabb>| {
+    own_count += 1;
+    own = union(own, bounds);
+  };
+  if let Some(terrain) = entity.get_component::<Terrain>() {
+    add(Some(terrain.bounds().transform(world)));
+  } else if let Some(lod) = entity.get_component::<Lod>() {
+    add(lod.bounds().map(|b| b.transform(world)));
+  } else if let Some(mesh) = entity.get_component::<Mesh>() {
+    add(mesh.bounds().map(|b| b.transform(world)));
+  }
+  if let Some(polyline) = entity.get_component::<Polyline>() {
+    // Joins reach out at most `miter_limit` half widths from the points.
+    let reach = polyline.width * 0.5 * polyline.miter_limit.max(1.0);
+    add(polyline.bounds().map(|b| match polyline.unit {
+      LineUnit::Pixels => context.inflate_pixels(b.transform(world), reach),
+      LineUnit::Metres => b.transform(world).inflate(reach),
+    }));
+  }
+  if let Some(marker) = entity.get_component::<Marker>() {
+    let position = marker_position(entity, world, context);
+    add(Some(context.inflate_pixels(
+      Aabb::new(position, position),
+      marker.size * 0.5,
+    )));
+  }
+
+  let index = nodes.len();
+  nodes.push(Node {
+    world,
+    own,
+    subtree: None,
+    len: 1,
+    own_count,
+    subtree_count: 0,
+  });
+  let mut subtree = own;
+  let mut subtree_count = own_count;
+  for child in entity.children() {
+    let (bounds, count) = measure(child, world, context, nodes);
+    subtree = union(subtree, bounds);
+    subtree_count += count;
+  }
+  let len = nodes.len() - index;
+  let node = &mut nodes[index];
+  node.subtree = subtree;
+  node.len = len;
+  node.subtree_count = subtree_count;
+  (subtree, subtree_count)
+}
+
+fn union(a: Option<Aabb>, b: Option<Aabb>) -> Option<Aabb> {
+  match (a, b) {
+    (Some(a), Some(b)) => Some(a.union(&b)),
+    (a, b) => a.or(b),
+  }
+}
+
+fn marker_position(entity: EntityView<'_>, world: Mat4, context: &CollectContext) -> Vec3 {
+  match (context.geo_reference, entity.get_component::<GeoPosition>()) {
+    (Some(frame), Some(&position)) => frame.project(position),
+    _ => world.transform_point3(Vec3::ZERO),
+  }
+}
@@ -0,0 +1,111 @@
+use super::OBJECT_STRIDE;
+
+/// Per-draw uniforms, one every [`OBJECT_STRIDE`] bytes of a single buffer bound with a
+/// dynamic offset. The buffer grows to fit the draws of a frame, up to the device's
+/// buffer size limit.
+pub(crate) struct DrawUniforms {
+  label: &'static str,
+  /// Bytes of each draw's uniform that the shaders read.
+  binding_size: u64,
+  layout: wgpu::BindGroupLayout,
+  buffer: wgpu::Buffer,
+  bind_group: wgpu::BindGroup,
+  /// Whether the last frame had more draws than fit, so the warning is not repeated.
+  truncated: bool,
+}
+
+impl DrawUniforms {
+  pub(crate) fn new(
+    device: &wgpu::Device,
+    label: &'static str,
+    layout: wgpu::BindGroupLayout,
+    binding_size: u64,
+    capacity: u64,
+  ) -> Self {
+    let (buffer, bind_group) = Self::make(device, label, &layout, binding_size, capacity);
+    Self {
+      label,
+      binding_size,
+      layout,
+      buffer,
+      bind_group,
+      truncated: false,
+    }
+  }
+
+  fn make(
+    device: &wgpu::Device,
+    label: &str,
+    layout: &wgpu::BindGroupLayout,
+    binding_size: u64,
+    capacity: u64,
+  ) -> (wgpu::Buffer, wgpu::BindGroup) {
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+      label: Some(label),
+      size: capacity.max(1) * OBJECT_STRIDE,
+      usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+      mapped_at_creation: false,
+    });
+    let bind_group = device.create_bind_group(&wgpu::BindGroupDescriptor {
+      label: Some(label),
+      layout,
+      entries: &[wgpu::BindGroupEntry {
+        binding: 0,
+        resource: wgpu::BindingResource::Buffer(wgpu::BufferBinding {
+          buffer: &buffer,
+          offset: 0,
+          size: wgpu::BufferSize::new(binding_size),
+        }),
+      }],
+    });
+    (buffer, bind_group)
+  }
+
+  /// Makes room for `count` draws, growing the buffer when needed, and returns how many
+  /// fit; draws past that must be skipped.
+  pub(crate) fn reserve(&mut self, device: &wgpu::Device, count: usize) -> usize {
+    let max = device.limits().max_buffer_size / OBJECT_STRIDE;
+    let fits = (count as u64).min(max);
+    if fits * OBJECT_STRIDE > self.buffer.size() {
+      let capacity = fits.next_power_of_two().min(max);
+      (self.buffer, self.bind_group) = Self::make(
+        device,
+        self.label,
+        &self.layout,
+        self.binding_size,
+        capacity,
+      );
+    }
+    let truncated = fits < count as u64;
+    if truncated && !self.truncated {
+      tracing::warn!(
+        "{count} draws need {} uniforms but only {fits} fit; skipping the rest",
+        self.label
+      );
+    }
+    self.truncated = truncated;
+    fits as usize
+  }
+
+  /// Writes `uniforms`, one per draw, each at its draw's dynamic offset.
+  pub(crate) fn write<T: bytemuck::Pod>(&self, queue: &wgpu::Queue, uniforms: &[T]) {
+    if uniforms.is_empty() {
+      return;
+    }
+    let mut data = vec![0u8; uniforms.len() * OBJECT_STRIDE as usize];
+    for (slot, uniform) in data.chunks_exact_mut(OBJECT_STRIDE as usize).zip(uniforms) {
+      let bytes = bytemuck::bytes_of(uniform);
+      slot[..bytes.len()].copy_from_slice(bytes);
+    }
+    queue.write_buffer(&self.buffer, 0, &data);
+  }
+
+  pub(crate) fn bind_group(&self) -> &wgpu::BindGroup {
+    &self.bind_group
+  }
+
+  /// Dynamic offset of draw `index`.
+  pub(crate) fn offset(index: usize) -> u32 {
+    (index as u64 * OBJECT_STRIDE) as u32
+  }
+}
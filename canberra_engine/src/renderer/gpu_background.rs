@@ -0,0 +1,180 @@
+use super::background_uniform::BackgroundUniform;
+use crate::background::{Background, Skybox};
+
+/// GPU side of the scene background: its uniform and the current skybox cubemap.
+pub(crate) struct GpuBackground {
+  pub(crate) layout: wgpu::BindGroupLayout,
+  pub(crate) bind_group: wgpu::BindGroup,
+  buffer: wgpu::Buffer,
+  sampler: wgpu::Sampler,
+  /// Id of the last [`Skybox`] uploaded, or tried; `None` while only the placeholder is bound.
+  skybox: Option<u64>,
+}
+
+impl GpuBackground {
+  pub(crate) fn new(device: &wgpu::Device) -> Self {
+    let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+      label: Some("background_bgl"),
+      entries: &[
+        wgpu::BindGroupLayoutEntry {
+          binding: 0,
+          visibility: wgpu::ShaderStages::FRAGMENT,
+          ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: wgpu::BufferSize::new(BackgroundUniform::size()),
+          },
+          count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+          binding: 1,
+          visibility: wgpu::ShaderStages::FRAGMENT,
+          ty: wgpu::BindingType::Texture {
+            sample_type: wgpu::TextureSampleType::Float { filterable: true },
+            view_dimension: wgpu::TextureViewDimension::Cube,
+            multisampled: false,
+          },
+          count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+          binding: 2,
+          visibility: wgpu::ShaderStages::FRAGMENT,
+          ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+          count: None,
+        },
+      ],
+    });
+    let buffer = device.create_buffer(&wgpu::BufferDescriptor {
+      label: Some("background_buffer"),
+      size: BackgroundUniform::size(),
+      usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+      mapped_at_creation: false,
+    });
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+      label: Some("background_sampler"),
+      address_mode_u: wgpu::AddressMode::ClampToEdge,
+      address_mode_v: wgpu::AddressMode::ClampToEdge,
+      address_mode_w: wgpu::AddressMode::ClampToEdge,
+      mag_filter: wgpu::FilterMode::Linear,
+      min_filter: wgpu::FilterMode::Linear,
+      ..Default::default()
+    });
+    // wgpu zero-initializes textures, so the placeholder samples as black.
+    let placeholder = cube_view(&make_cube(device, 1));
+    let bind_group = make_bind_group(device, &layout, &buffer, &placeholder, &sampler);
+    Self {
+      layout,
+      bind_group,
+      buffer,
+      sampler,
+      skybox: None,
+    }
+  }
+
+  /// Writes this frame's uniform and uploads the skybox if it changed since the last call.
+  pub(crate) fn update(
+    &mut self,
+    device: &wgpu::Device,
+    queue: &wgpu::Queue,
+    background: &Background,
+    uniform: &BackgroundUniform,
+  ) {
+    queue.write_buffer(&self.buffer, 0, bytemuck::bytes_of(uniform));
+    if let Background::Skybox(skybox) = background
+      && self.skybox != Some(skybox.id())
+    {
+      self.upload(device, queue, skybox);
+    }
+  }
+
+  /// Decodes and uploads `skybox`, binding the black placeholder if it cannot be.
+  fn upload(&mut self, device: &wgpu::Device, queue: &wgpu::Queue, skybox: &Skybox) {
+    let max = device.limits().max_texture_dimension_2d;
+    let cube = match skybox.load() {
+      Ok(image) if image.size() > max => {
+        tracing::warn!(
+          "skybox faces are {0}x{0}, larger than the {max}x{max} the device supports",
+          image.size()
+        );
+        make_cube(device, 1)
+      }
+      Ok(image) => {
+        let cube = make_cube(device, image.size());
+        queue.write_texture(
+          cube.as_image_copy(),
+          image.texels(),
+          wgpu::TexelCopyBufferLayout {
+            offset: 0,
+            bytes_per_row: Some(4 * image.size()),
+            rows_per_image: Some(image.size()),
+          },
+          cube.size(),
+        );
+        cube
+      }
+      Err(e) => {
+        tracing::warn!("cannot load skybox: {e}");
+        make_cube(device, 1)
+      }
+    };
+    self.bind_group = make_bind_group(
+      device,
+      &self.layout,
+      &self.buffer,
+      &cube_view(&cube),
+      &self.sampler,
+    );
+    self.skybox = Some(skybox.id());
+  }
+}
+
+fn make_cube(device: &wgpu::Device, size: u32) -> wgpu::Texture {
+  device.create_texture(&wgpu::TextureDescriptor {
+    label: Some("skybox_texture"),
+    size: wgpu::Extent3d {
+      width: size,
+      height: size,
+      depth_or_array_layers: 6,
+    },
+    mip_level_count: 1,
+    sample_count: 1,
+    dimension: wgpu::TextureDimension::D2,
+    format: wgpu::TextureFormat::Rgba8UnormSrgb,
+    usage: wgpu::TextureUsages::TEXTURE_BINDING | wgpu::TextureUsages::COPY_DST,
+    view_formats: &[],
+  })
+}
+
+fn cube_view(texture: &wgpu::Texture) -> wgpu::TextureView {
+  texture.create_view(&wgpu::TextureViewDescriptor {
+    dimension: Some(wgpu::TextureViewDimension::Cube),
+    ..Default::default()
+  })
+}
+
+fn make_bind_group(
+  device: &wgpu::Device,
+  layout: &wgpu::BindGroupLayout,
+  buffer: &wgpu::Buffer,
+  cube: &wgpu::TextureView,
+  sampler: &wgpu::Sampler,
+) -> wgpu::BindGroup {
+  device.create_bind_group(&wgpu::BindGroupDescriptor {
+    label: Some("background_bg"),
+    layout,
+    entries: &[
+      wgpu::BindGroupEntry {
+        binding: 0,
+        resource: buffer.as_entire_binding(),
+      },
+      wgpu::BindGroupEntry {
+        binding: 1,
+        resource: wgpu::BindingResource::TextureView(cube),
+      },
+      wgpu::BindGroupEntry {
+        binding: 2,
+        resource: wgpu::BindingResource::Sampler(sampler),
+      },
+    ],
+  })
+}
@@ -0,0 +1,92 @@
+use wgpu::util::DeviceExt;
+
+use crate::components::Polyline;
+
+pub(crate) const HAS_PREV: u32 = 1;
+pub(crate) const HAS_NEXT: u32 = 2;
+
+/// One instance of the line pipeline: a segment plus its neighbours for joins.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct LineSegment {
+  pub(crate) prev: [f32; 3],
+  pub(crate) start: [f32; 3],
+  pub(crate) end: [f32; 3],
+  pub(crate) next: [f32; 3],
+  /// Distance along the line at `start` and `end`, in local units.
+  pub(crate) distance: [f32; 2],
+  pub(crate) neighbours: u32,
+}
+
+impl LineSegment {
+  pub(crate) const ATTRIBUTES: [wgpu::VertexAttribute; 6] = wgpu::vertex_attr_array![
+    0 => Float32x3,
+    1 => Float32x3,
+    2 => Float32x3,
+    3 => Float32x3,
+    4 => Float32x2,
+    5 => Uint32,
+  ];
+
+  /// Segments of `points`, skipping zero-length ones so joins always have a direction.
+  pub(crate) fn from_points(points: &[glam::Vec3]) -> Vec<Self> {
+    let mut points = points.to_vec();
+    points.dedup();
+    let mut distance = 0.0;
+    let mut segments = Vec::with_capacity(points.len().saturating_sub(1));
+    for i in 0..points.len().saturating_sub(1) {
+      let (start, end) = (points[i], points[i + 1]);
+      let length = start.distance(end);
+      let mut neighbours = 0;
+      let prev = match i.checked_sub(1) {
+        Some(p) => {
+          neighbours |= HAS_PREV;
+          points[p]
+        }
+        None => start,
+      };
+      let next = match points.get(i + 2) {
+        Some(&n) => {
+          neighbours |= HAS_NEXT;
+          n
+        }
+        None => end,
+      };
+      segments.push(Self {
+        prev: prev.into(),
+        start: start.into(),
+        end: end.into(),
+        next: next.into(),
+        distance: [distance, distance + length],
+        neighbours,
+      });
+      distance += length;
+    }
+    segments
+  }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct GpuPolyline {
+  pub(crate) instance_buffer: wgpu::Buffer,
+  pub(crate) segment_count: u32,
+}
+
+impl GpuPolyline {
+  /// Vertices per segment instance: the body quad and the join wedge at its start.
+  pub(crate) const VERTICES: u32 = 12;
+
+  pub(crate) fn upload(device: &wgpu::Device, polyline: &Polyline) -> Self {
+    let segments = LineSegment::from_points(&polyline.points);
+    let instance_buffer = device.create_buffer_init(&wgpu::util::BufferInitDescriptor {
+      label: Some("Polyline Instance Buffer"),
+      contents: bytemuck::cast_slice(&segments),
+      usage: wgpu::BufferUsages::VERTEX,
+    });
+
+    Self {
+      instance_buffer,
+      segment_count: segments.len() as u32,
+    }
+  }
+}
@@ -0,0 +1,138 @@
+use std::sync::{
+  Arc,
+  atomic::{AtomicU8, Ordering},
+};
+
+/// Passes timed per frame; later ones go untimed.
+const MAX_PASSES: u32 = 32;
+/// Readbacks in flight; frames are skipped while all are waiting on the GPU.
+const READBACKS: usize = 3;
+
+const IDLE: u8 = 0;
+const PENDING: u8 = 1;
+const READY: u8 = 2;
+
+struct Readback {
+  buffer: wgpu::Buffer,
+  passes: Vec<&'static str>,
+  state: Arc<AtomicU8>,
+}
+
+/// Measures the GPU time of render graph passes with timestamp queries written around
+/// each one; results arrive a few frames late.
+pub(crate) struct GpuTimer {
+  query_set: wgpu::QuerySet,
+  resolve_buffer: wgpu::Buffer,
+  readbacks: Vec<Readback>,
+  /// Nanoseconds per timestamp tick.
+  period: f32,
+  /// Milliseconds per pass of the latest frame read back.
+  latest: Vec<(&'static str, f32)>,
+}
+
+impl GpuTimer {
+  /// Features the device needs for [`GpuTimer::new`].
+  pub(crate) const FEATURES: wgpu::Features =
+    wgpu::Features::TIMESTAMP_QUERY.union(wgpu::Features::TIMESTAMP_QUERY_INSIDE_ENCODERS);
+
+  /// `None` when the device lacks [`GpuTimer::FEATURES`].
+  pub(crate) fn new(device: &wgpu::Device, queue: &wgpu::Queue) -> Option<Self> {
+    if !device.features().contains(Self::FEATURES) {
+      return None;
+    }
+    let size = 2 * MAX_PASSES as u64 * wgpu::QUERY_SIZE as u64;
+    let readbacks = (0..READBACKS)
+      .map(|_| Readback {
+        buffer: device.create_buffer(&wgpu::BufferDescriptor {
+          label: Some("timestamp_readback"),
+          size,
+          usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+          mapped_at_creation: false,
+        }),
+        passes: Vec::new(),
+        state: Arc::new(AtomicU8::new(IDLE)),
+      })
+      .collect();
+    Some(Self {
+      query_set: device.create_query_set(&wgpu::QuerySetDescriptor {
+        label: Some("timestamps"),
+        ty: wgpu::QueryType::Timestamp,
+        count: 2 * MAX_PASSES,
+      }),
+      resolve_buffer: device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some("timestamp_resolve"),
+        size,
+        usage: wgpu::BufferUsages::QUERY_RESOLVE | wgpu::BufferUsages::COPY_SRC,
+        mapped_at_creation: false,
+      }),
+      readbacks,
+      period: queue.get_timestamp_period(),
+      latest: Vec::new(),
+    })
+  }
+
+  /// Reads back finished frames and picks a readback for this one; `None` skips timing.
+  pub(crate) fn begin_frame(&mut self) -> Option<usize> {
+    for readback in &mut self.readbacks {
+      if readback.state.load(Ordering::Acquire) != READY {
+        continue;
+      }
+      {
+        let data = readback.buffer.slice(..).get_mapped_range();
+        let ticks: &[u64] = bytemuck::cast_slice(&data);
+        self.latest = readback
+          .passes
+          .iter()
+          .enumerate()
+          .map(|(i, &name)| {
+            let elapsed = ticks[2 * i + 1].saturating_sub(ticks[2 * i]);
+            (name, elapsed as f32 * self.period / 1e6)
+          })
+          .collect();
+      }
+      readback.buffer.unmap();
+      readback.state.store(IDLE, Ordering::Release);
+    }
+    self
+      .readbacks
+      .iter()
+      .position(|r| r.state.load(Ordering::Acquire) == IDLE)
+  }
+
+  /// Timestamps pass number `index` of the frame, before or after it runs.
+  pub(crate) fn write(&self, encoder: &mut wgpu::CommandEncoder, index: usize, end: bool) {
+    if index < MAX_PASSES as usize {
+      encoder.write_timestamp(&self.query_set, 2 * index as u32 + u32::from(end));
+    }
+  }
+
+  /// Copies the frame's timestamps to `readback`, mapped once the GPU is done with them.
+  pub(crate) fn end_frame(
+    &mut self,
+    encoder: &mut wgpu::CommandEncoder,
+    readback: usize,
+    mut passes: Vec<&'static str>,
+  ) {
+    passes.truncate(MAX_PASSES as usize);
+    if passes.is_empty() {
+      return;
+    }
+    let queries = 2 * passes.len() as u32;
+    let readback = &mut self.readbacks[readback];
+    encoder.resolve_query_set(&self.query_set, 0..queries, &self.resolve_buffer, 0);
+    let size = queries as u64 * wgpu::QUERY_SIZE as u64;
+    encoder.copy_buffer_to_buffer(&self.resolve_buffer, 0, &readback.buffer, 0, size);
+
+    readback.passes = passes;
+    readback.state.store(PENDING, Ordering::Release);
+    let state = readback.state.clone();
+    encoder.map_buffer_on_submit(&readback.buffer, wgpu::MapMode::Read, .., move |result| {
+      state.store(if result.is_ok() { READY } else { IDLE }, Ordering::Release);
+    });
+  }
+
+  /// Milliseconds per pass of the latest measured frame.
+  pub(crate) fn latest(&self) -> &[(&'static str, f32)] {
+    &self.latest
+  }
+}
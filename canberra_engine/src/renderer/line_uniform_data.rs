@@ -0,0 +1,58 @@
+use crate::components::{LineCap, LineJoin, LineUnit, MAX_DASH_ENTRIES, Polyline};
+
+#[repr(C)]
+#[derive(Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct LineUniformData {
+  pub(crate) model: [[f32; 4]; 4],
+  pub(crate) color: [f32; 4],
+  pub(crate) dash: [f32; 4],
+  pub(crate) width: f32,
+  pub(crate) miter_limit: f32,
+  /// Bit 0: metres; bits 1-2: join; bits 3-4: cap. Must match `shader_line.wgsl`.
+  pub(crate) flags: u32,
+  pub(crate) dash_count: u32,
+}
+
+impl LineUniformData {
+  pub(crate) fn new(model: [[f32; 4]; 4], polyline: &Polyline) -> Self {
+    let unit = match polyline.unit {
+      LineUnit::Pixels => 0,
+      LineUnit::Metres => 1,
+    };
+    let join = match polyline.join {
+      LineJoin::Miter => 0,
+      LineJoin::Bevel => 1,
+      LineJoin::Round => 2,
+    };
+    let cap = match polyline.cap {
+      LineCap::Butt => 0,
+      LineCap::Square => 1,
+      LineCap::Round => 2,
+    };
+
+    let mut dash = [0.0; MAX_DASH_ENTRIES];
+    let dash_count = polyline.dash.len().min(MAX_DASH_ENTRIES);
+    dash[..dash_count].copy_from_slice(&polyline.dash[..dash_count]);
+    // An odd count or a pattern that draws nothing reads as a solid line.
+    let total: f32 = dash.iter().sum();
+    let dash_count = if dash_count.is_multiple_of(2) && total > 0.0 {
+      dash_count as u32
+    } else {
+      0
+    };
+
+    Self {
+      model,
+      color: polyline.color,
+      dash,
+      width: polyline.width,
+      miter_limit: polyline.miter_limit.max(1.0),
+      flags: unit | (join << 1) | (cap << 3),
+      dash_count,
+    }
+  }
+
+  pub(crate) const fn size() -> u64 {
+    size_of::<Self>() as u64
+  }
+}
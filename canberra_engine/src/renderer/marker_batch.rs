@@ -0,0 +1,140 @@
+use std::collections::HashMap;
+
+use glam::{Mat4, Vec2, Vec3, Vec4};
+
+use crate::components::{Marker, MarkerShape};
+
+/// Smallest cluster badge diameter that keeps its count legible, in pixels.
+const MIN_BADGE_SIZE: f32 = 24.0;
+/// Badge growth per tenfold increase in clustered markers, relative to the marker size.
+const BADGE_GROWTH: f32 = 0.35;
+
+/// One instance of the marker pipeline: a single marker or a cluster badge.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+pub(crate) struct MarkerInstance {
+  pub(crate) position: [f32; 3],
+  /// Diameter in pixels.
+  pub(crate) size: f32,
+  pub(crate) color: [f32; 4],
+  pub(crate) shape: u32,
+  /// Markers this instance stands for; above 1 it is drawn as a badge with the count.
+  pub(crate) count: u32,
+}
+
+impl MarkerInstance {
+  pub(crate) const ATTRIBUTES: [wgpu::VertexAttribute; 5] = wgpu::vertex_attr_array![
+    0 => Float32x3,
+    1 => Float32,
+    2 => Float32x4,
+    3 => Uint32,
+    4 => Uint32,
+  ];
+
+  fn new(position: Vec3, marker: &Marker) -> Self {
+    let shape = match marker.shape {
+      MarkerShape::Circle => 0,
+      MarkerShape::Square => 1,
+      MarkerShape::Diamond => 2,
+      MarkerShape::Triangle => 3,
+    };
+    Self {
+      position: position.into(),
+      size: marker.size,
+      color: marker.color,
+      shape,
+      count: 1,
+    }
+  }
+}
+
+/// Markers sharing one screen cell.
+struct Cluster {
+  color_sum: Vec4,
+  max_size: f32,
+  count: u32,
+  /// Member nearest the cell center, which anchors the badge on an actual marker.
+  anchor: MarkerInstance,
+  anchor_distance: f32,
+}
+
+impl Cluster {
+  fn add(&mut self, instance: MarkerInstance, distance: f32) {
+    self.color_sum += Vec4::from(instance.color);
+    self.max_size = self.max_size.max(instance.size);
+    self.count += 1;
+    if distance < self.anchor_distance {
+      self.anchor = instance;
+      self.anchor_distance = distance;
+    }
+  }
+
+  fn instance(&self) -> MarkerInstance {
+    if self.count == 1 {
+      return self.anchor;
+    }
+    let growth = 1.0 + BADGE_GROWTH * (self.count as f32).log10();
+    MarkerInstance {
+      size: (self.max_size * growth).max(MIN_BADGE_SIZE),
+      color: (self.color_sum / self.count as f32).into(),
+      shape: 0,
+      count: self.count,
+      ..self.anchor
+    }
+  }
+}
+
+/// Instances for one frame: off-screen markers are dropped and clustering markers that
+/// land in the same screen cell are merged into a badge.
+pub(crate) fn build_markers(
+  markers: &[(Vec3, &Marker)],
+  view_proj: Mat4,
+  viewport: Vec2,
+) -> Vec<MarkerInstance> {
+  let to_ndc = |position: Vec3| {
+    let clip = view_proj * position.extend(1.0);
+    (clip.w > 0.0).then(|| Vec2::new(clip.x, clip.y) / clip.w)
+  };
+  // Measure cells from the projected world origin so they travel with the map while
+  // panning instead of re-bucketing markers every frame.
+  let anchor = to_ndc(Vec3::ZERO).unwrap_or_default() * 0.5 * viewport;
+
+  let mut instances = Vec::with_capacity(markers.len());
+  let mut cells: HashMap<(u32, i32, i32), Cluster> = HashMap::new();
+  for &(position, marker) in markers {
+    let Some(ndc) = to_ndc(position) else {
+      continue;
+    };
+    let margin = 2.0 * (marker.size * 0.5).max(marker.cluster_radius) / viewport;
+    if ndc.abs().cmpgt(Vec2::ONE + margin).any() {
+      continue;
+    }
+
+    let instance = MarkerInstance::new(position, marker);
+    if marker.cluster_radius <= 0.0 {
+      instances.push(instance);
+      continue;
+    }
+    let screen = (ndc * 0.5 * viewport - anchor) / marker.cluster_radius;
+    let cell = screen.floor();
+    let distance = screen.distance(cell + 0.5);
+    let key = (
+      marker.cluster_radius.to_bits(),
+      cell.x as i32,
+      cell.y as i32,
+    );
+    cells
+      .entry(key)
+      .and_modify(|c| c.add(instance, distance))
+      .or_insert_with(|| Cluster {
+        color_sum: Vec4::from(instance.color),
+        max_size: instance.size,
+        count: 1,
+        anchor: instance,
+        anchor_distance: distance,
+      });
+  }
+
+  instances.extend(cells.values().map(Cluster::instance));
+  instances
+}
@@ -3,6 +3,10 @@
 pub(crate) struct ObjectUniformData {
   pub(crate) model: [[f32; 4]; 4],
   pub(crate) color: [f32; 4],
+  /// Dithered cross-fade: keep the fraction `fade` of pixels, or when negative the
+  /// complementary fraction `-fade`. 1 draws everything.
+  pub(crate) fade: f32,
+  pub(crate) _pad: [f32; 3],
 }
 
 impl ObjectUniformData {
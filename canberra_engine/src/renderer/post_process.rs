@@ -0,0 +1,534 @@
+use std::{collections::HashMap, sync::Mutex};
+
+use super::{
+  GLOBAL_SHADER_REGISTRY, PostPassHandle, PostSettings, ShaderRegistry, ToneMapping,
+  render_graph::{FrameGraph, GraphResource, PassContext, TextureDesc, TextureSize},
+};
+
+/// Format of the offscreen target the scene is drawn into.
+pub(crate) const HDR_FORMAT: wgpu::TextureFormat = wgpu::TextureFormat::Rgba16Float;
+/// Most levels in the bloom chain; each halves the resolution.
+const BLOOM_MIPS: u32 = 6;
+
+const PRELUDE: &str = include_str!("../post_common.wgsl");
+
+/// Mirrors `Post` in `post_common.wgsl`.
+#[repr(C)]
+#[derive(Debug, Copy, Clone, bytemuck::Pod, bytemuck::Zeroable)]
+struct PostUniform {
+  time: f32,
+  exposure: f32,
+  contrast: f32,
+  saturation: f32,
+  bloom_threshold: f32,
+  bloom_knee: f32,
+  bloom_intensity: f32,
+  tone_mapping: u32,
+}
+
+impl PostUniform {
+  fn new(settings: &PostSettings, time: f32) -> Self {
+    Self {
+      time,
+      exposure: settings.exposure,
+      contrast: settings.contrast,
+      saturation: settings.saturation,
+      bloom_threshold: settings.bloom_threshold,
+      bloom_knee: settings.bloom_knee,
+      bloom_intensity: settings.bloom_intensity,
+      tone_mapping: match settings.tone_mapping {
+        ToneMapping::None => 0,
+        ToneMapping::Aces => 1,
+        ToneMapping::Filmic => 2,
+      },
+    }
+  }
+}
+
+/// What every post bind group shares.
+struct PostBindings {
+  layout: wgpu::BindGroupLayout,
+  sampler: wgpu::Sampler,
+  uniform_buffer: wgpu::Buffer,
+  /// Bound where a pass has no bloom input, so no texture is read while written.
+  black: wgpu::TextureView,
+  cache: Mutex<BindGroupCache>,
+}
+
+/// Bind groups by input and bloom view, built against one generation of the graph's
+/// texture pool.
+#[derive(Default)]
+struct BindGroupCache {
+  generation: u64,
+  groups: HashMap<(wgpu::TextureView, wgpu::TextureView), wgpu::BindGroup>,
+}
+
+/// Runs the full-screen effect chain from the HDR scene target to the surface.
+pub(crate) struct PostProcess {
+  bindings: PostBindings,
+  prefilter: wgpu::RenderPipeline,
+  downsample: wgpu::RenderPipeline,
+  upsample: wgpu::RenderPipeline,
+  tonemap: wgpu::RenderPipeline,
+  fxaa: wgpu::RenderPipeline,
+  pipeline_layout: wgpu::PipelineLayout,
+  /// Registered post passes, as of the shader registry generation they were built at.
+  custom: Vec<(PostPassHandle, wgpu::RenderPipeline)>,
+  generation: u64,
+  surface_format: wgpu::TextureFormat,
+}
+
+impl PostProcess {
+  pub(crate) fn new(device: &wgpu::Device, surface_format: wgpu::TextureFormat) -> Self {
+    let texture_entry = |binding| wgpu::BindGroupLayoutEntry {
+      binding,
+      visibility: wgpu::ShaderStages::FRAGMENT,
+      ty: wgpu::BindingType::Texture {
+        sample_type: wgpu::TextureSampleType::Float { filterable: true },
+        view_dimension: wgpu::TextureViewDimension::D2,
+        multisampled: false,
+      },
+      count: None,
+    };
+    let layout = device.create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+      label: Some("post_bgl"),
+      entries: &[
+        texture_entry(0),
+        wgpu::BindGroupLayoutEntry {
+          binding: 1,
+          visibility: wgpu::ShaderStages::FRAGMENT,
+          ty: wgpu::BindingType::Sampler(wgpu::SamplerBindingType::Filtering),
+          count: None,
+        },
+        wgpu::BindGroupLayoutEntry {
+          binding: 2,
+          visibility: wgpu::ShaderStages::FRAGMENT,
+          ty: wgpu::BindingType::Buffer {
+            ty: wgpu::BufferBindingType::Uniform,
+            has_dynamic_offset: false,
+            min_binding_size: wgpu::BufferSize::new(size_of::<PostUniform>() as u64),
+          },
+          count: None,
+        },
+        texture_entry(3),
+      ],
+    });
+    let pipeline_layout = device.create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+      label: Some("post_pipeline_layout"),
+      bind_group_layouts: &[Some(&layout)],
+      immediate_size: 0,
+    });
+
+    let sampler = device.create_sampler(&wgpu::SamplerDescriptor {
+      label: Some("post_sampler"),
+      address_mode_u: wgpu::AddressMode::ClampToEdge,
+      address_mode_v: wgpu::AddressMode::ClampToEdge,
+      mag_filter: wgpu::FilterMode::Linear,
+      min_filter: wgpu::FilterMode::Linear,
+      ..Default::default()
+    });
+    let uniform_buffer = device.create_buffer(&wgpu::BufferDescriptor {
+      label: Some("post_uniform_buffer"),
+      size: size_of::<PostUniform>() as u64,
+      usage: wgpu::BufferUsages::UNIFORM | wgpu::BufferUsages::COPY_DST,
+      mapped_at_creation: false,
+    });
+    let black = make_target(device, "post_black", HDR_FORMAT, 1, 1, 1)
+      .create_view(&wgpu::TextureViewDescriptor::default());
+
+    let module = |label: &str, source: &str| post_module(device, label, source);
+    let pipeline = |label: &str, module: &wgpu::ShaderModule, entry_point, format, blend| {
+      make_post_pipeline(
+        device,
+        label,
+        module,
+        entry_point,
+        &pipeline_layout,
+        format,
+        blend,
+      )
+    };
+    let bloom = module("Bloom", include_str!("../post_bloom.wgsl"));
+    let additive = wgpu::BlendState {
+      color: wgpu::BlendComponent {
+        src_factor: wgpu::BlendFactor::One,
+        dst_factor: wgpu::BlendFactor::One,
+        operation: wgpu::BlendOperation::Add,
+      },
+      alpha: wgpu::BlendComponent::REPLACE,
+    };
+    let prefilter = pipeline(
+      "bloom_prefilter",
+      &bloom,
+      "fs_prefilter",
+      HDR_FORMAT,
+      wgpu::BlendState::REPLACE,
+    );
+    let downsample = pipeline(
+      "bloom_downsample",
+      &bloom,
+      "fs_downsample",
+      HDR_FORMAT,
+      wgpu::BlendState::REPLACE,
+    );
+    let upsample = pipeline(
+      "bloom_upsample",
+      &bloom,
+      "fs_upsample",
+      HDR_FORMAT,
+      additive,
+    );
+    let tonemap = pipeline(
+      "tonemap",
+      &module("Tone mapping", include_str!("../post_tonemap.wgsl")),
+      "fs_main",
+      surface_format,
+      wgpu::BlendState::REPLACE,
+    );
+    let fxaa = pipeline(
+      "fxaa",
+      &module("FXAA", include_str!("../post_fxaa.wgsl")),
+      "fs_main",
+      surface_format,
+      wgpu::BlendState::REPLACE,
+    );
+
+    let registry = GLOBAL_SHADER_REGISTRY.load();
+    let bindings = PostBindings {
+      layout,
+      sampler,
+      uniform_buffer,
+      black,
+      cache: Mutex::default(),
+    };
+    Self {
+      bindings,
+      prefilter,
+      downsample,
+      upsample,
+      tonemap,
+      fxaa,
+      custom: build_custom(device, &pipeline_layout, &registry),
+      generation: registry.generation(),
+      pipeline_layout,
+      surface_format,
+    }
+  }
+
+  /// Rebuilds the registered passes if the shader registry changed since they were built.
+  pub(crate) fn refresh(&mut self, device: &wgpu::Device) {
+    let registry = GLOBAL_SHADER_REGISTRY.load();
+    if registry.generation() != self.generation {
+      self.custom = build_custom(device, &self.pipeline_layout, &registry);
+      self.generation = registry.generation();
+    }
+  }
+
+  /// Adds the chain to `frame` as one pass per step, from the scene image in `hdr` to the
+  /// frame's output. Every intermediate image is a transient of the graph.
+  pub(crate) fn add_passes<'a>(
+    &'a self,
+    frame: &mut FrameGraph<'a>,
+    queue: &wgpu::Queue,
+    hdr: GraphResource,
+    settings: &PostSettings,
+    time: f32,
+  ) {
+    let uniform = PostUniform::new(settings, time);
+    queue.write_buffer(
+      &self.bindings.uniform_buffer,
+      0,
+      bytemuck::bytes_of(&uniform),
+    );
+    let bindings = &self.bindings;
+    let clear = wgpu::LoadOp::Clear(wgpu::Color::BLACK);
+    let sampled = |label, format| {
+      TextureDesc::new(label, format).with_usage(wgpu::TextureUsages::TEXTURE_BINDING)
+    };
+
+    let bloom = settings.bloom.then(|| {
+      let (width, height) = frame.size();
+      let smallest = (width / 2).max(1).min((height / 2).max(1));
+      let mips = BLOOM_MIPS.min(u32::BITS - smallest.leading_zeros());
+      let levels: Vec<_> = (0..mips)
+        .map(|level| {
+          let scale = 0.5f32.powi(level as i32 + 1);
+          frame.create_texture(sampled("bloom", HDR_FORMAT).with_size(TextureSize::Relative(scale)))
+        })
+        .collect();
+
+      let first = levels[0];
+      frame.add_pass("bloom_prefilter", &[hdr], &[first], move |ctx| {
+        let bind_group = bindings.bind_group(ctx, ctx.view(hdr), &bindings.black);
+        run_pass(
+          ctx.encoder,
+          "Bloom Prefilter",
+          &self.prefilter,
+          &bind_group,
+          ctx.view(first),
+          clear,
+        );
+      });
+      for pair in levels.windows(2) {
+        let (input, target) = (pair[0], pair[1]);
+        frame.add_pass("bloom_downsample", &[input], &[target], move |ctx| {
+          let bind_group = bindings.bind_group(ctx, ctx.view(input), &bindings.black);
+          run_pass(
+            ctx.encoder,
+            "Bloom Downsample",
+            &self.downsample,
+            &bind_group,
+            ctx.view(target),
+            clear,
+          );
+        });
+      }
+      // Each level is added onto the next larger one, which keeps its downsampled image.
+      for pair in levels.windows(2).rev() {
+        let (target, input) = (pair[0], pair[1]);
+        frame.add_pass("bloom_upsample", &[input], &[target], move |ctx| {
+          let bind_group = bindings.bind_group(ctx, ctx.view(input), &bindings.black);
+          run_pass(
+            ctx.encoder,
+            "Bloom Upsample",
+            &self.upsample,
+            &bind_group,
+            ctx.view(target),
+            wgpu::LoadOp::Load,
+          );
+        });
+      }
+      first
+    });
+    // Reads of `input`, plus the bloom result where there is one.
+    let reads = |input| [input].into_iter().chain(bloom).collect::<Vec<_>>();
+
+    let mut current = hdr;
+    let enabled = self
+      .custom
+      .iter()
+      .filter(|(handle, _)| !settings.disabled_passes.contains(handle));
+    for (_, pipeline) in enabled {
+      let (input, target) = (
+        current,
+        frame.create_texture(sampled("post_hdr", HDR_FORMAT)),
+      );
+      frame.add_pass("post_pass", &reads(input), &[target], move |ctx| {
+        let bloom = bloom.map_or(&bindings.black, |bloom| ctx.view(bloom));
+        let bind_group = bindings.bind_group(ctx, ctx.view(input), bloom);
+        run_pass(
+          ctx.encoder,
+          "Post Pass",
+          pipeline,
+          &bind_group,
+          ctx.view(target),
+          clear,
+        );
+      });
+      current = target;
+    }
+
+    let output = frame.output();
+    let ldr = settings
+      .fxaa
+      .then(|| frame.create_texture(sampled("ldr", self.surface_format)));
+    let (input, target) = (current, ldr.unwrap_or(output));
+    frame.add_pass("tone_mapping", &reads(input), &[target], move |ctx| {
+      let bloom = bloom.map_or(&bindings.black, |bloom| ctx.view(bloom));
+      let bind_group = bindings.bind_group(ctx, ctx.view(input), bloom);
+      run_pass(
+        ctx.encoder,
+        "Tone Mapping",
+        &self.tonemap,
+        &bind_group,
+        ctx.view(target),
+        clear,
+      );
+    });
+    if let Some(ldr) = ldr {
+      frame.add_pass("fxaa", &[ldr], &[output], move |ctx| {
+        let bind_group = bindings.bind_group(ctx, ctx.view(ldr), &bindings.black);
+        run_pass(
+          ctx.encoder,
+          "FXAA",
+          &self.fxaa,
+          &bind_group,
+          ctx.view(output),
+          clear,
+        );
+      });
+    }
+  }
+}
+
+impl PostBindings {
+  /// Bind group sampling `input` and `bloom`, reused until the graph's pool changes.
+  fn bind_group(
+    &self,
+    ctx: &PassContext<'_>,
+    input: &wgpu::TextureView,
+    bloom: &wgpu::TextureView,
+  ) -> wgpu::BindGroup {
+    let mut cache = self.cache.lock().unwrap();
+    if cache.generation != ctx.generation {
+      cache.groups.clear();
+      cache.generation = ctx.generation;
+    }
+    cache
+      .groups
+      .entry((input.clone(), bloom.clone()))
+      .or_insert_with(|| self.create_bind_group(ctx.device, input, bloom))
+      .clone()
+  }
+
+  fn create_bind_group(
+    &self,
+    device: &wgpu::Device,
+    input: &wgpu::TextureView,
+    bloom: &wgpu::TextureView,
+  ) -> wgpu::BindGroup {
+    device.create_bind_group(&wgpu::BindGroupDescriptor {
+      label: Some("post_bg"),
+      layout: &self.layout,
+      entries: &[
+        wgpu::BindGroupEntry {
+          binding: 0,
+          resource: wgpu::BindingResource::TextureView(input),
+        },
+        wgpu::BindGroupEntry {
+          binding: 1,
+          resource: wgpu::BindingResource::Sampler(&self.sampler),
+        },
+        wgpu::BindGroupEntry {
+          binding: 2,
+          resource: self.uniform_buffer.as_entire_binding(),
+        },
+        wgpu::BindGroupEntry {
+          binding: 3,
+          resource: wgpu::BindingResource::TextureView(bloom),
+        },
+      ],
+    })
+  }
+}
+
+fn post_module(device: &wgpu::Device, label: &str, source: &str) -> wgpu::ShaderModule {
+  device.create_shader_module(wgpu::ShaderModuleDescriptor {
+    label: Some(label),
+    source: wgpu::ShaderSource::Wgsl(format!("{PRELUDE}\n{source}").into()),
+  })
+}
+
+fn build_custom(
+  device: &wgpu::Device,
+  pipeline_layout: &wgpu::PipelineLayout,
+  registry: &ShaderRegistry,
+) -> Vec<(PostPassHandle, wgpu::RenderPipeline)> {
+  registry
+    .post_passes
+    .iter()
+    .map(|(handle, shader)| {
+      let module = post_module(device, &shader.name, &shader.wgsl);
+      let pipeline = make_post_pipeline(
+        device,
+        &shader.name,
+        &module,
+        "fs_main",
+        pipeline_layout,
+        HDR_FORMAT,
+        wgpu::BlendState::REPLACE,
+      );
+      (*handle, pipeline)
+    })
+    .collect()
+}
+
+fn make_target(
+  device: &wgpu::Device,
+  label: &str,
+  format: wgpu::TextureFormat,
+  width: u32,
+  height: u32,
+  mip_level_count: u32,
+) -> wgpu::Texture {
+  device.create_texture(&wgpu::TextureDescriptor {
+    label: Some(label),
+    size: wgpu::Extent3d {
+      width,
+      height,
+      depth_or_array_layers: 1,
+    },
+    mip_level_count,
+    sample_count: 1,
+    dimension: wgpu::TextureDimension::D2,
+    format,
+    usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::TEXTURE_BINDING,
+    view_formats: &[],
+  })
+}
+
+/// Draws one full-screen triangle into `target`.
+fn run_pass(
+  encoder: &mut wgpu::CommandEncoder,
+  label: &str,
+  pipeline: &wgpu::RenderPipeline,
+  bind_group: &wgpu::BindGroup,
+  target: &wgpu::TextureView,
+  load: wgpu::LoadOp<wgpu::Color>,
+) {
+  let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+    label: Some(label),
+    color_attachments: &[Some(wgpu::RenderPassColorAttachment {
+      view: target,
+      resolve_target: None,
+      depth_slice: None,
+      ops: wgpu::Operations {
+        load,
+        store: wgpu::StoreOp::Store,
+      },
+    })],
+    depth_stencil_attachment: None,
+    occlusion_query_set: None,
+    timestamp_writes: None,
+    multiview_mask: None,
+  });
+  pass.set_pipeline(pipeline);
+  pass.set_bind_group(0, bind_group, &[]);
+  pass.draw(0..3, 0..1);
+}
+
+fn make_post_pipeline(
+  device: &wgpu::Device,
+  label: &str,
+  shader: &wgpu::ShaderModule,
+  entry_point: &str,
+  pipeline_layout: &wgpu::PipelineLayout,
+  format: wgpu::TextureFormat,
+  blend: wgpu::BlendState,
+) -> wgpu::RenderPipeline {
+  device.create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+    label: Some(label),
+    layout: Some(pipeline_layout),
+    vertex: wgpu::VertexState {
+      module: shader,
+      entry_point: Some("vs_main"),
+      buffers: &[],
+      compilation_options: Default::default(),
+    },
+    fragment: Some(wgpu::FragmentState {
+      module: shader,
+      entry_point: Some(entry_point),
+      targets: &[Some(wgpu::ColorTargetState {
+        format,
+        blend: Some(blend),
+        write_mask: wgpu::ColorWrites::ALL,
+      })],
+      compilation_options: Default::default(),
+    }),
+    primitive: wgpu::PrimitiveState::default(),
+    depth_stencil: None,
+    multisample: wgpu::MultisampleState::default(),
+    multiview_mask: None,
+    cache: None,
+  })
+}
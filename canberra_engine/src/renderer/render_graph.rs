@@ -0,0 +1,515 @@
+use super::gpu_timer::GpuTimer;
+use crate::{Error, Result};
+
+/// Extent of a graph texture.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum TextureSize {
+  /// Fraction of the render target, at least one texel per side.
+  Relative(f32),
+  Absolute(u32, u32),
+}
+
+impl Default for TextureSize {
+  fn default() -> Self {
+    Self::Relative(1.0)
+  }
+}
+
+/// Texture that lives for a single frame of a [`FrameGraph`]; the graph allocates it,
+/// reuses it across frames and shares it between passes whose uses do not overlap.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TextureDesc {
+  pub label: &'static str,
+  pub format: wgpu::TextureFormat,
+  pub size: TextureSize,
+  pub sample_count: u32,
+  pub mip_level_count: u32,
+  pub usage: wgpu::TextureUsages,
+}
+
+impl TextureDesc {
+  /// Full-size, single-sampled render attachment.
+  pub fn new(label: &'static str, format: wgpu::TextureFormat) -> Self {
+    Self {
+      label,
+      format,
+      size: TextureSize::default(),
+      sample_count: 1,
+      mip_level_count: 1,
+      usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+    }
+  }
+
+  pub fn with_size(mut self, size: TextureSize) -> Self {
+    self.size = size;
+    self
+  }
+
+  pub fn with_sample_count(mut self, sample_count: u32) -> Self {
+    self.sample_count = sample_count;
+    self
+  }
+
+  pub fn with_mip_level_count(mut self, mip_level_count: u32) -> Self {
+    self.mip_level_count = mip_level_count;
+    self
+  }
+
+  /// Usages on top of [`wgpu::TextureUsages::RENDER_ATTACHMENT`].
+  pub fn with_usage(mut self, usage: wgpu::TextureUsages) -> Self {
+    self.usage = usage | wgpu::TextureUsages::RENDER_ATTACHMENT;
+    self
+  }
+
+  fn extent(&self, (width, height): (u32, u32)) -> (u32, u32) {
+    match self.size {
+      TextureSize::Relative(scale) => (
+        ((width as f32 * scale) as u32).max(1),
+        ((height as f32 * scale) as u32).max(1),
+      ),
+      TextureSize::Absolute(width, height) => (width.max(1), height.max(1)),
+    }
+  }
+}
+
+/// Texture a pass of a [`FrameGraph`] reads or writes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct GraphResource(usize);
+
+/// What a pass sees while it records: the device, the frame's encoder and the views of
+/// the resources it declared.
+pub struct PassContext<'r> {
+  pub device: &'r wgpu::Device,
+  pub encoder: &'r mut wgpu::CommandEncoder,
+  /// Render target size in physical pixels.
+  pub size: (u32, u32),
+  /// Changes whenever a pooled texture is created or dropped, so anything built from the
+  /// views can be kept until it does.
+  pub generation: u64,
+  views: Vec<Option<&'r wgpu::TextureView>>,
+}
+
+impl<'r> PassContext<'r> {
+  /// View of a resource the pass declared as read or written.
+  pub fn view(&self, resource: GraphResource) -> &'r wgpu::TextureView {
+    self.views[resource.0].expect("Resource not declared by this pass")
+  }
+}
+
+struct PooledTexture {
+  desc: TextureDesc,
+  extent: (u32, u32),
+  view: wgpu::TextureView,
+}
+
+/// Persistent state behind each frame's [`FrameGraph`]: the render target size, the
+/// pool of transient textures and the optional pass timer.
+pub struct RenderGraph {
+  size: (u32, u32),
+  pool: Vec<PooledTexture>,
+  /// Bumped on every change to `pool`.
+  generation: u64,
+  timer: Option<GpuTimer>,
+}
+
+impl RenderGraph {
+  pub fn new(width: u32, height: u32) -> Self {
+    Self {
+      size: (width.max(1), height.max(1)),
+      pool: Vec::new(),
+      generation: 0,
+      timer: None,
+    }
+  }
+
+  /// Starts timing every pass on the GPU; returns `false`, leaving timing off, when the
+  /// device lacks timestamp queries inside encoders.
+  pub fn enable_timing(&mut self, device: &wgpu::Device, queue: &wgpu::Queue) -> bool {
+    self.timer = GpuTimer::new(device, queue);
+    self.timer.is_some()
+  }
+
+  /// GPU milliseconds per pass of a recent frame, or `None` without timing.
+  pub fn pass_timings(&self) -> Option<&[(&'static str, f32)]> {
+    self.timer.as_ref().map(GpuTimer::latest)
+  }
+
+  /// Relative textures are reallocated at the new size on the next frame.
+  pub fn resize(&mut self, width: u32, height: u32) {
+    self.size = (width.max(1), height.max(1));
+  }
+
+  /// Starts a frame that draws into `output`.
+  pub fn begin_frame<'a>(&'a mut self, output: &'a wgpu::TextureView) -> FrameGraph<'a> {
+    FrameGraph {
+      graph: self,
+      resources: vec![Resource::Imported("output", 0)],
+      imports: vec![output],
+      passes: Vec::new(),
+    }
+  }
+
+  /// Index of a free pool texture matching `desc`, creating one if there is none.
+  fn acquire(&mut self, device: &wgpu::Device, desc: &TextureDesc, busy: &[bool]) -> usize {
+    let extent = desc.extent(self.size);
+    let free = self.pool.iter().enumerate().position(|(i, t)| {
+      !busy.get(i).copied().unwrap_or(false) && t.desc == *desc && t.extent == extent
+    });
+    free.unwrap_or_else(|| {
+      let view = device
+        .create_texture(&wgpu::TextureDescriptor {
+          label: Some(desc.label),
+          size: wgpu::Extent3d {
+            width: extent.0,
+            height: extent.1,
+            depth_or_array_layers: 1,
+          },
+          mip_level_count: desc.mip_level_count,
+          sample_count: desc.sample_count,
+          dimension: wgpu::TextureDimension::D2,
+          format: desc.format,
+          usage: desc.usage,
+          view_formats: &[],
+        })
+        .create_view(&wgpu::TextureViewDescriptor::default());
+      self.pool.push(PooledTexture {
+        desc: desc.clone(),
+        extent,
+        view,
+      });
+      self.generation += 1;
+      self.pool.len() - 1
+    })
+  }
+}
+
+enum Resource {
+  /// Index into [`FrameGraph::imports`].
+  Imported(&'static str, usize),
+  Transient(TextureDesc),
+}
+
+impl Resource {
+  fn label(&self) -> &'static str {
+    match self {
+      Self::Imported(label, _) => label,
+      Self::Transient(desc) => desc.label,
+    }
+  }
+}
+
+type PassFn<'a> = Box<dyn FnOnce(&mut PassContext<'_>) + 'a>;
+
+struct Pass<'a> {
+  name: &'static str,
+  reads: Vec<GraphResource>,
+  writes: Vec<GraphResource>,
+  run: PassFn<'a>,
+}
+
+/// One frame's passes and the textures they exchange.
+///
+/// A pass reading a resource runs after the passes added before it that write the
+/// resource, or after all its writers if it was added first; passes writing the same
+/// resource keep the order they were added in. Passes whose results reach neither an
+/// imported texture nor another pass are skipped.
+pub struct FrameGraph<'a> {
+  graph: &'a mut RenderGraph,
+  resources: Vec<Resource>,
+  imports: Vec<&'a wgpu::TextureView>,
+  passes: Vec<Pass<'a>>,
+}
+
+impl<'a> FrameGraph<'a> {
+  /// The texture the frame is presented from.
+  pub fn output(&self) -> GraphResource {
+    GraphResource(0)
+  }
+
+  /// Render target size in physical pixels.
+  pub fn size(&self) -> (u32, u32) {
+    self.graph.size
+  }
+
+  /// Makes a texture owned elsewhere available to passes.
+  pub fn import(&mut self, label: &'static str, view: &'a wgpu::TextureView) -> GraphResource {
+    self
+      .resources
+      .push(Resource::Imported(label, self.imports.len()));
+    self.imports.push(view);
+    GraphResource(self.resources.len() - 1)
+  }
+
+  /// Declares a transient texture; see [`TextureDesc`].
+  pub fn create_texture(&mut self, desc: TextureDesc) -> GraphResource {
+    self.resources.push(Resource::Transient(desc));
+    GraphResource(self.resources.len() - 1)
+  }
+
+  pub fn add_pass(
+    &mut self,
+    name: &'static str,
+    reads: &[GraphResource],
+    writes: &[GraphResource],
+    run: impl FnOnce(&mut PassContext<'_>) + 'a,
+  ) {
+    self.passes.push(Pass {
+      name,
+      reads: reads.to_vec(),
+      writes: writes.to_vec(),
+      run: Box::new(run),
+    });
+  }
+
+  /// Schedules the passes, allocates their transient textures and records them into
+  /// `encoder`.
+  pub fn execute(self, device: &wgpu::Device, encoder: &mut wgpu::CommandEncoder) -> Result<()> {
+    let order = self.schedule()?;
+    let Self {
+      graph,
+      resources,
+      imports,
+      passes,
+    } = self;
+
+    // Each transient holds a pool texture from its first to its last use.
+    let mut last_use = vec![0; resources.len()];
+    for (step, &p) in order.iter().enumerate() {
+      for r in passes[p].reads.iter().chain(&passes[p].writes) {
+        last_use[r.0] = step;
+      }
+    }
+    let mut slots = vec![None; resources.len()];
+    let mut busy = Vec::new();
+    for (step, &p) in order.iter().enumerate() {
+      let used = passes[p].reads.iter().chain(&passes[p].writes);
+      for r in used.clone() {
+        if let (Resource::Transient(desc), None) = (&resources[r.0], slots[r.0]) {
+          let slot = graph.acquire(device, desc, &busy);
+          busy.resize(graph.pool.len(), false);
+          busy[slot] = true;
+          slots[r.0] = Some(slot);
+        }
+      }
+      for r in used {
+        if last_use[r.0] == step
+          && let Some(slot) = slots[r.0]
+        {
+          busy[slot] = false;
+        }
+      }
+    }
+
+    let (size, generation) = (graph.size, graph.generation);
+    let readback = graph.timer.as_mut().and_then(GpuTimer::begin_frame);
+    let timer = graph.timer.as_ref().filter(|_| readback.is_some());
+    let names: Vec<_> = order.iter().map(|&p| passes[p].name).collect();
+    let mut passes: Vec<_> = passes.into_iter().map(Some).collect();
+    for (step, p) in order.into_iter().enumerate() {
+      let pass = passes[p].take().unwrap();
+      let mut views = vec![None; resources.len()];
+      for r in pass.reads.iter().chain(&pass.writes) {
+        views[r.0] = Some(match &resources[r.0] {
+          Resource::Imported(_, i) => imports[*i],
+          Resource::Transient(_) => &graph.pool[slots[r.0].unwrap()].view,
+        });
+      }
+      if let Some(timer) = timer {
+        timer.write(encoder, step, false);
+      }
+      let mut context = PassContext {
+        device,
+        encoder: &mut *encoder,
+        size,
+        generation,
+        views,
+      };
+      (pass.run)(&mut context);
+      if let Some(timer) = timer {
+        timer.write(encoder, step, true);
+      }
+    }
+    if let (Some(timer), Some(readback)) = (&mut graph.timer, readback) {
+      timer.end_frame(encoder, readback, names);
+    }
+
+    // Drop textures this frame did not need, such as those of an old size.
+    let mut used = vec![false; graph.pool.len()];
+    slots
+      .into_iter()
+      .flatten()
+      .for_each(|slot| used[slot] = true);
+    if used.contains(&false) {
+      let mut used = used.into_iter();
+      graph.pool.retain(|_| used.next().unwrap());
+      graph.generation += 1;
+    }
+    Ok(())
+  }
+
+  /// Execution order of the passes that contribute to an imported texture.
+  fn schedule(&self) -> Result<Vec<usize>> {
+    let count = self.passes.len();
+    let writers =
+      |r: GraphResource| (0..count).filter(move |&p| self.passes[p].writes.contains(&r));
+
+    let mut deps = vec![Vec::new(); count];
+    for (p, pass) in self.passes.iter().enumerate() {
+      for r in &pass.reads {
+        if matches!(self.resources[r.0], Resource::Transient(_)) && writers(*r).next().is_none() {
+          return Err(Error::InvalidRenderGraph(format!(
+            "pass \"{}\" reads \"{}\", which no pass writes",
+            pass.name,
+            self.resources[r.0].label()
+          )));
+        }
+        // A read sees the writes added before it, or all of them if it was added first.
+        let earlier: Vec<_> = writers(*r).filter(|&w| w < p).collect();
+        if earlier.is_empty() {
+          deps[p].extend(writers(*r).filter(|&w| w != p));
+        } else {
+          deps[p].extend(earlier);
+        }
+      }
+      for r in &pass.writes {
+        deps[p].extend(writers(*r).filter(|&w| w < p));
+      }
+    }
+
+    let mut live = vec![false; count];
+    let mut stack: Vec<_> = (0..count)
+      .filter(|&p| {
+        let writes = &self.passes[p].writes;
+        writes
+          .iter()
+          .any(|r| matches!(self.resources[r.0], Resource::Imported(..)))
+      })
+      .collect();
+    while let Some(p) = stack.pop() {
+      if !std::mem::replace(&mut live[p], true) {
+        stack.extend(&deps[p]);
+      }
+    }
+
+    // Depth-first topological sort; ties keep the order passes were added in.
+    let mut order = Vec::with_capacity(count);
+    let mut state = vec![Visit::New; count];
+    for p in (0..count).filter(|&p| live[p]) {
+      self.visit(p, &deps, &mut state, &mut order)?;
+    }
+    Ok(order)
+  }
+
+  fn visit(
+    &self,
+    p: usize,
+    deps: &[Vec<usize>],
+    state: &mut [Visit],
+    order: &mut Vec<usize>,
+  ) -> Result<()> {
+    match state[p] {
+      Visit::Done => return Ok(()),
+      Visit::Active => {
+        return Err(Error::InvalidRenderGraph(format!(
+          "cycle through pass \"{}\"",
+          self.passes[p].name
+        )));
+      }
+      Visit::New => {}
+    }
+    state[p] = Visit::Active;
+    for &d in &deps[p] {
+      self.visit(d, deps, state, order)?;
+    }
+    state[p] = Visit::Done;
+    order.push(p);
+    Ok(())
+  }
+}
+
+#[derive(Clone, Copy)]
+enum Visit {
+  New,
+  Active,
+  Done,
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// A frame whose output has no view; enough to schedule, not to execute.
+  fn frame(graph: &mut RenderGraph) -> FrameGraph<'_> {
+    FrameGraph {
+      graph,
+      resources: vec![Resource::Imported("output", 0)],
+      imports: Vec::new(),
+      passes: Vec::new(),
+    }
+  }
+
+  fn texture(frame: &mut FrameGraph<'_>, label: &'static str) -> GraphResource {
+    frame.create_texture(TextureDesc::new(label, wgpu::TextureFormat::Rgba8Unorm))
+  }
+
+  fn names(frame: &FrameGraph<'_>) -> Vec<&'static str> {
+    let order = frame.schedule().unwrap();
+    order.into_iter().map(|p| frame.passes[p].name).collect()
+  }
+
+  #[test]
+  fn readers_follow_writers_added_later() {
+    let mut graph = RenderGraph::new(4, 4);
+    let mut frame = frame(&mut graph);
+    let (output, hdr) = (frame.output(), texture(&mut frame, "hdr"));
+    frame.add_pass("tone_mapping", &[hdr], &[output], |_| {});
+    frame.add_pass("scene", &[], &[hdr], |_| {});
+    assert_eq!(names(&frame), ["scene", "tone_mapping"]);
+  }
+
+  #[test]
+  fn writers_of_one_resource_keep_their_order() {
+    let mut graph = RenderGraph::new(4, 4);
+    let mut frame = frame(&mut graph);
+    let (output, hdr) = (frame.output(), texture(&mut frame, "hdr"));
+    frame.add_pass("scene", &[], &[hdr], |_| {});
+    frame.add_pass("tone_mapping", &[hdr], &[output], |_| {});
+    frame.add_pass("overlay", &[], &[output], |_| {});
+    assert_eq!(names(&frame), ["scene", "tone_mapping", "overlay"]);
+  }
+
+  #[test]
+  fn passes_not_reaching_an_import_are_culled() {
+    let mut graph = RenderGraph::new(4, 4);
+    let mut frame = frame(&mut graph);
+    let output = frame.output();
+    let (hdr, unused) = (texture(&mut frame, "hdr"), texture(&mut frame, "unused"));
+    frame.add_pass("scene", &[], &[hdr], |_| {});
+    frame.add_pass("debug", &[hdr], &[unused], |_| {});
+    frame.add_pass("tone_mapping", &[hdr], &[output], |_| {});
+    assert_eq!(names(&frame), ["scene", "tone_mapping"]);
+  }
+
+  #[test]
+  fn reading_an_unwritten_transient_fails() {
+    let mut graph = RenderGraph::new(4, 4);
+    let mut frame = frame(&mut graph);
+    let (output, hdr) = (frame.output(), texture(&mut frame, "hdr"));
+    frame.add_pass("tone_mapping", &[hdr], &[output], |_| {});
+    let error = frame.schedule().unwrap_err().to_string();
+    assert!(error.contains("\"tone_mapping\" reads \"hdr\""), "{error}");
+  }
+
+  #[test]
+  fn cycles_fail() {
+    let mut graph = RenderGraph::new(4, 4);
+    let mut frame = frame(&mut graph);
+    let output = frame.output();
+    let (a, b) = (texture(&mut frame, "a"), texture(&mut frame, "b"));
+    frame.add_pass("first", &[b], &[a], |_| {});
+    frame.add_pass("second", &[a], &[b, output], |_| {});
+    frame.add_pass("third", &[a], &[b], |_| {});
+    let error = frame.schedule().unwrap_err().to_string();
+    assert!(error.contains("cycle through pass"), "{error}");
+  }
+}
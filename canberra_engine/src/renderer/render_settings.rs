@@ -0,0 +1,141 @@
+use std::collections::BTreeSet;
+
+use super::PostPassHandle;
+
+/// Curve mapping the HDR image into the displayable range.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ToneMapping {
+  /// Clamps, leaving colors in 0..1 exactly as authored.
+  #[default]
+  None,
+  /// Fitted ACES curve: rich contrast, highlights desaturate toward white.
+  Aces,
+  /// Uncharted 2 filmic curve: softer toe and shoulder.
+  Filmic,
+}
+
+impl ToneMapping {
+  pub const ALL: [Self; 3] = [Self::None, Self::Aces, Self::Filmic];
+}
+
+/// Full-screen effects applied to the HDR scene image, in order: bloom, registered
+/// post passes, color grading with tone mapping, then FXAA.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PostSettings {
+  pub bloom: bool,
+  /// Brightness above which pixels glow; colors within 0..1 stay dark at 1.0.
+  pub bloom_threshold: f32,
+  /// Softens the threshold over this fraction of it below.
+  pub bloom_knee: f32,
+  pub bloom_intensity: f32,
+  pub tone_mapping: ToneMapping,
+  /// Linear scale applied before grading and tone mapping.
+  pub exposure: f32,
+  /// Contrast around middle grey; 1 leaves the image unchanged.
+  pub contrast: f32,
+  /// 0 is greyscale, 1 unchanged.
+  pub saturation: f32,
+  pub fxaa: bool,
+  /// Registered post passes to skip; see [`crate::ShaderRegistry::register_post_pass`].
+  pub disabled_passes: BTreeSet<PostPassHandle>,
+}
+
+impl Default for PostSettings {
+  fn default() -> Self {
+    Self {
+      bloom: true,
+      bloom_threshold: 1.0,
+      bloom_knee: 0.5,
+      bloom_intensity: 0.3,
+      tone_mapping: ToneMapping::None,
+      exposure: 1.0,
+      contrast: 1.0,
+      saturation: 1.0,
+      fxaa: false,
+      disabled_passes: BTreeSet::new(),
+    }
+  }
+}
+
+/// Renderer configuration, passed to [`crate::Application::run_with_settings`].
+///
+/// The sample count, clear color, present mode and post effects can be changed at
+/// runtime from the editor; the power preference and backends only take effect when the
+/// device is created.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RenderSettings {
+  /// MSAA samples per pixel; 1 disables multisampling. Unsupported counts fall back to
+  /// the largest supported one below.
+  pub msaa_samples: u32,
+  pub clear_color: [f32; 4],
+  /// Modes the surface does not support fall back to [`wgpu::PresentMode::Fifo`].
+  pub present_mode: wgpu::PresentMode,
+  pub power_preference: wgpu::PowerPreference,
+  pub backends: wgpu::Backends,
+  pub post: PostSettings,
+}
+
+impl Default for RenderSettings {
+  fn default() -> Self {
+    Self {
+      msaa_samples: 4,
+      clear_color: [0.1, 0.2, 0.3, 1.0],
+      present_mode: wgpu::PresentMode::AutoVsync,
+      power_preference: wgpu::PowerPreference::HighPerformance,
+      backends: wgpu::Backends::PRIMARY,
+      post: PostSettings::default(),
+    }
+  }
+}
+
+impl RenderSettings {
+  pub fn with_msaa_samples(mut self, msaa_samples: u32) -> Self {
+    self.msaa_samples = msaa_samples;
+    self
+  }
+
+  pub fn with_clear_color(mut self, clear_color: [f32; 4]) -> Self {
+    self.clear_color = clear_color;
+    self
+  }
+
+  pub fn with_present_mode(mut self, present_mode: wgpu::PresentMode) -> Self {
+    self.present_mode = present_mode;
+    self
+  }
+
+  pub fn with_power_preference(mut self, power_preference: wgpu::PowerPreference) -> Self {
+    self.power_preference = power_preference;
+    self
+  }
+
+  pub fn with_backends(mut self, backends: wgpu::Backends) -> Self {
+    self.backends = backends;
+    self
+  }
+
+  pub fn with_post(mut self, post: PostSettings) -> Self {
+    self.post = post;
+    self
+  }
+
+  /// Largest entry of `supported` not above the requested sample count.
+  pub(crate) fn resolve_msaa_samples(&self, supported: &[u32]) -> u32 {
+    supported
+      .iter()
+      .copied()
+      .filter(|&n| n <= self.msaa_samples)
+      .max()
+      .unwrap_or(1)
+  }
+
+  /// The requested present mode, or FIFO when `supported` lacks it.
+  pub(crate) fn resolve_present_mode(&self, supported: &[wgpu::PresentMode]) -> wgpu::PresentMode {
+    match self.present_mode {
+      // Auto modes pick a supported mode themselves.
+      wgpu::PresentMode::AutoVsync | wgpu::PresentMode::AutoNoVsync => self.present_mode,
+      mode if supported.contains(&mode) => mode,
+      _ => wgpu::PresentMode::Fifo,
+    }
+  }
+}
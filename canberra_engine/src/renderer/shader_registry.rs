@@ -1,7 +1,4 @@
-use std::{
-  collections::BTreeMap,
-  sync::{Arc, LazyLock},
-};
+use std::{collections::BTreeMap, sync::LazyLock};
 
 use arc_swap::ArcSwap;
 
@@ -23,6 +20,22 @@ use crate::Shader;
 )]
 pub struct ShaderHandle(pub(crate) u32);
 
+/// Opaque handle to a registered full-screen post pass.
+#[derive(
+  Debug,
+  Default,
+  Clone,
+  Copy,
+  PartialEq,
+  Eq,
+  PartialOrd,
+  Ord,
+  Hash,
+  serde::Serialize,
+  serde::Deserialize,
+)]
+pub struct PostPassHandle(pub(crate) u32);
+
 pub static GLOBAL_SHADER_REGISTRY: LazyLock<ArcSwap<ShaderRegistry>> =
   LazyLock::new(|| ArcSwap::from_pointee(ShaderRegistry::new()));
 
@@ -38,14 +51,18 @@ impl std::fmt::Display for ShaderHandle {
 #[derive(Debug, Clone)]
 pub struct ShaderRegistry {
   pub(crate) shaders: BTreeMap<ShaderHandle, Shader>,
+  pub(crate) post_passes: BTreeMap<PostPassHandle, Shader>,
   next_id: u32,
+  generation: u64,
 }
 
 impl ShaderRegistry {
   fn new() -> Self {
     Self {
       shaders: BTreeMap::new(),
+      post_passes: BTreeMap::new(),
       next_id: 0,
+      generation: 0,
     }
     .register_default()
   }
@@ -53,8 +70,8 @@ impl ShaderRegistry {
   /// Register a WGSL shader source and return its handle.
   /// The shader must expose `vs_main` and `fs_main` entry points and
   /// declare the same bind groups as the built-in shaders:
-  ///   group(0) binding(0) — camera uniform  (view_proj: mat4x4<f32>)
-  ///   group(1) binding(0) — object uniform  (model: mat4x4<f32>, color: vec4<f32>)
+  ///   group(0) binding(0) — camera uniform  (view_proj: mat4x4<f32>, time: f32, …)
+  ///   group(1) binding(0) — object uniform  (model: mat4x4<f32>, color: vec4<f32>, fade: f32)
   /// Vertex input: @location(0) position: vec3<f32>, @location(1) normal: vec3<f32>
   pub fn register(&mut self, shader: Shader) -> ShaderHandle {
     let handle = ShaderHandle(self.next_id);
@@ -68,6 +85,33 @@ impl ShaderRegistry {
     self.shaders.get(&handle)
   }
 
+  /// Register a full-screen post pass, run in registration order on the linear HDR
+  /// image after bloom and before tone mapping. Enabled unless listed in
+  /// [`crate::PostSettings::disabled_passes`].
+  /// The source is appended to a prelude that provides `vs_main`, the `PostOut` input
+  /// (`uv` in 0..1, y down) and these bindings, so it only needs an `fs_main`:
+  ///   group(0) binding(0) — `input`: texture_2d<f32>, the HDR image so far
+  ///   group(0) binding(1) — `input_sampler`: linear clamping sampler
+  ///   group(0) binding(2) — `post` uniform (time: f32, exposure: f32, …)
+  ///   group(0) binding(3) — `bloom`: texture_2d<f32>, the blurred highlights
+  pub fn register_post_pass(&mut self, shader: Shader) -> PostPassHandle {
+    let handle = PostPassHandle(self.next_id);
+    self.next_id += 1;
+    self.post_passes.insert(handle, shader);
+    handle
+  }
+
+  #[inline]
+  pub fn get_post_pass(&self, handle: PostPassHandle) -> Option<&Shader> {
+    self.post_passes.get(&handle)
+  }
+
+  /// Bumped by every [`register_shaders`] call; renderers rebuild their pipelines when it
+  /// differs from the one they were built at.
+  pub fn generation(&self) -> u64 {
+    self.generation
+  }
+
   fn register_default(mut self) -> Self {
     self.register(Shader::new(
       "Default Lit",
@@ -81,10 +125,18 @@ impl ShaderRegistry {
   }
 }
 
-pub fn register_shaders<F: FnOnce(&mut ShaderRegistry)>(ctx: F) {
-  let mut registry = (**GLOBAL_SHADER_REGISTRY.load()).clone();
-  ctx(&mut registry);
-  GLOBAL_SHADER_REGISTRY.store(Arc::new(registry));
+/// Edits [`GLOBAL_SHADER_REGISTRY`]. Shaders registered after a renderer was created are
+/// picked up at the start of its next frame.
+///
+/// `ctx` runs on a copy of the registry that replaces it atomically, so concurrent calls
+/// do not lose each other's shaders; it runs again on a fresh copy if another call won.
+pub fn register_shaders<F: FnMut(&mut ShaderRegistry)>(mut ctx: F) {
+  GLOBAL_SHADER_REGISTRY.rcu(|current| {
+    let mut registry = ShaderRegistry::clone(current);
+    ctx(&mut registry);
+    registry.generation += 1;
+    registry
+  });
 }
 
 impl Default for ShaderRegistry {
@@ -92,3 +144,34 @@ impl Default for ShaderRegistry {
     Self::new()
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn concurrent_registrations_are_kept() {
+    const THREADS: usize = 8;
+    let before = GLOBAL_SHADER_REGISTRY.load_full();
+    let handles: Vec<_> = std::thread::scope(|scope| {
+      let threads: Vec<_> = (0..THREADS)
+        .map(|i| {
+          scope.spawn(move || {
+            let mut handle = ShaderHandle::default();
+            register_shaders(|registry| {
+              handle = registry.register(Shader::new(&format!("Test {i}"), ""));
+            });
+            handle
+          })
+        })
+        .collect();
+      threads.into_iter().map(|t| t.join().unwrap()).collect()
+    });
+
+    let after = GLOBAL_SHADER_REGISTRY.load();
+    assert!(after.generation() >= before.generation() + THREADS as u64);
+    for (i, handle) in handles.into_iter().enumerate() {
+      assert_eq!(after.get(handle).unwrap().name, format!("Test {i}"));
+    }
+  }
+}
@@ -1,41 +1,520 @@
-use glam::Mat4;
+use std::collections::BTreeSet;
+
+use glam::{Mat4, Quat, Vec2, Vec3};
+use uuid::Uuid;
 
 use crate::{
-  Entity,
-  components::{Camera, Transform},
+  Background, DanglingRef, Entity, EntityRef, EntityView, EntityViewMut, Error, Input, Prefab,
+  Query, QueryFilter, Ray, ReadOnlyQuery, Result, SceneEvents,
+  components::{
+    Camera, GlobeCamera, MapCamera, PrefabInstance, PropertyOverride, Tags, Transform, Visibility,
+  },
+  geo::{self, GeoJsonOptions, LocalProjection},
+  hierarchy::{self, World},
+  picking::{self, Pick},
+  prefab,
 };
 
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct Scene {
-  pub entities: Vec<Entity>,
+  /// Written as the tree of root entities.
+  #[serde(rename = "entities")]
+  world: World,
+  /// Geographic anchor of the scene's local frame. Set by the first geo import if absent.
+  #[serde(default)]
+  pub geo_reference: Option<LocalProjection>,
+  #[serde(default)]
+  pub background: Background,
+  /// Templates the scene's [`PrefabInstance`]s were made from.
+  #[serde(default)]
+  prefabs: Vec<Prefab>,
+  /// Changes after this tick pass [`crate::Added`] and [`crate::Changed`] filters: the tick
+  /// the running system last ran at, or the start of the frame outside systems.
+  #[serde(skip)]
+  change_since: u64,
 }
 
 impl Scene {
   pub fn new() -> Self {
     Self {
-      entities: Vec::new(),
+      world: World::new(),
+      geo_reference: None,
+      background: Background::Clear,
+      prefabs: Vec::new(),
+      change_since: 0,
     }
   }
 
+  /// Adds a root entity, sending [`crate::SceneEvent::Spawned`] for it and its
+  /// descendants.
   pub fn add(&mut self, entity: Entity) {
-    self.entities.push(entity);
+    self.world.spawn(entity, None, usize::MAX);
+  }
+
+  /// Adds `entity` as the last child of `parent`, or as the last root when `parent` is not
+  /// in the scene.
+  pub fn add_child(&mut self, parent: Uuid, entity: Entity) {
+    self.insert_child(parent, usize::MAX, entity);
+  }
+
+  /// Like [`Scene::add_child`], at position `at` among the children of `parent`.
+  pub(crate) fn insert_child(&mut self, parent: Uuid, at: usize, entity: Entity) {
+    let parent = self.world.index(parent);
+    self.world.spawn(entity, parent, at);
+  }
+
+  /// Removes the entity `id`, root or not, along with its descendants, sending
+  /// [`crate::SceneEvent::Despawned`] for each. [`EntityRef`]s to them are left dangling;
+  /// see [`Scene::dangling_refs`].
+  pub fn despawn(&mut self, id: Uuid) -> Option<Entity> {
+    let index = self.world.index(id)?;
+    Some(self.world.despawn(index))
+  }
+
+  /// Adds `entity` right after `sibling`, under the same parent, or as the last root when
+  /// `sibling` is not in the scene.
+  pub fn insert_after(&mut self, sibling: Uuid, entity: Entity) {
+    let Some(sibling) = self.world.index(sibling) else {
+      self.add(entity);
+      return;
+    };
+    let parent = self.world.parent(sibling);
+    let position = match parent {
+      Some(parent) => self.world.children(parent).position(|c| c == sibling),
+      None => self.world.roots().iter().position(|&r| r == sibling),
+    };
+    let at = position.map_or(usize::MAX, |i| i + 1);
+    self.world.spawn(entity, parent, at);
+  }
+
+  /// Copies the entity `id` and its descendants with fresh ids, places the copy right after
+  /// it and returns the copy's id; see [`Entity::duplicate`].
+  pub fn duplicate(&mut self, id: Uuid) -> Result<Option<Uuid>> {
+    let Some(entity) = self.find(id) else {
+      return Ok(None);
+    };
+    let copy = entity.duplicate()?;
+    let copy_id = copy.id();
+    self.insert_after(id, copy);
+    Ok(Some(copy_id))
+  }
+
+  /// Root entities in order.
+  pub fn roots(&self) -> impl DoubleEndedIterator<Item = EntityView<'_>> {
+    let world = &self.world;
+    world
+      .roots()
+      .iter()
+      .map(move |&index| EntityView::new(world, index))
+  }
+
+  /// Every entity, roots and descendants depth-first.
+  pub fn iter(&self) -> impl Iterator<Item = EntityView<'_>> {
+    let world = &self.world;
+    world
+      .descendants()
+      .map(move |index| EntityView::new(world, index))
+  }
+
+  /// The entity `id`, root or not.
+  pub fn find(&self, id: Uuid) -> Option<EntityView<'_>> {
+    let index = self.world.index(id)?;
+    Some(EntityView::new(&self.world, index))
+  }
+
+  pub fn find_mut(&mut self, id: Uuid) -> Option<EntityViewMut<'_>> {
+    let index = self.world.index(id)?;
+    Some(EntityViewMut::new(&mut self.world, index))
+  }
+
+  /// The target of `entity_ref`, if it is in the scene.
+  pub fn resolve(&self, entity_ref: EntityRef) -> Option<EntityView<'_>> {
+    self.find(entity_ref.id()?)
+  }
+
+  pub fn resolve_mut(&mut self, entity_ref: EntityRef) -> Option<EntityViewMut<'_>> {
+    self.find_mut(entity_ref.id()?)
+  }
+
+  /// Every [`EntityRef`] whose target is not in the scene, e.g. after its target was
+  /// despawned. Serializes every component to find them, so call it after loading or
+  /// editing rather than every frame.
+  pub fn dangling_refs(&self) -> Vec<DanglingRef> {
+    let mut dangling = Vec::new();
+    for entity in self.iter() {
+      for component in entity.iter() {
+        for target in hierarchy::entity_refs(component) {
+          if self.world.index(target).is_none() {
+            dangling.push(DanglingRef {
+              entity: entity.id(),
+              component: component.name(),
+              target,
+            });
+          }
+        }
+      }
+    }
+    dangling
+  }
+
+  /// Reads a scene written by [`Scene::to_json`], warning about its
+  /// [`Scene::dangling_refs`].
+  pub fn from_json(src: &str) -> Result<Self> {
+    let scene: Self = serde_json::from_str(src)?;
+    for r in scene.dangling_refs() {
+      tracing::warn!(
+        "{} of entity {} refers to missing entity {}",
+        r.component,
+        r.entity,
+        r.target
+      );
+    }
+    Ok(scene)
+  }
+
+  pub fn to_json(&self) -> Result<String> {
+    Ok(serde_json::to_string_pretty(self)?)
+  }
+
+  /// Entities, roots and descendants depth-first, carrying `tag` in their [`Tags`].
+  pub fn tagged<'a>(&'a self, tag: &'a str) -> impl Iterator<Item = EntityView<'a>> {
+    self
+      .iter()
+      .filter(move |e| e.get_component::<Tags>().is_some_and(|t| t.contains(tag)))
+  }
+
+  /// Every tag in the scene, sorted.
+  pub fn tags(&self) -> BTreeSet<&str> {
+    self.query::<&Tags>().flat_map(|tags| tags.iter()).collect()
+  }
+
+  /// Gives every entity tagged `tag` `visibility`, e.g. to switch a map layer grouped under
+  /// tagged entities on or off.
+  pub fn set_tagged_visibility(&mut self, tag: &str, visibility: Visibility) {
+    let tagged: Vec<Uuid> = self.tagged(tag).map(|e| e.id()).collect();
+    for id in tagged {
+      if let Some(mut entity) = self.find_mut(id) {
+        entity.add_component(visibility);
+      }
+    }
+  }
+
+  /// Lifecycle events of this frame and the previous one; read them with an
+  /// [`crate::EventCursor`] kept by the reader.
+  pub fn events(&self) -> &SceneEvents {
+    self.world.events()
+  }
+
+  /// Tick that [`crate::Added`] and [`crate::Changed`] filters compare against.
+  pub fn change_since(&self) -> u64 {
+    self.change_since
+  }
+
+  pub(crate) fn set_change_since(&mut self, tick: u64) -> u64 {
+    std::mem::replace(&mut self.change_since, tick)
+  }
+
+  /// The tick component writes are currently stamped with. Each scene keeps its own.
+  pub fn change_tick(&self) -> u64 {
+    self.world.change_tick()
+  }
+
+  /// Moves writes after this call past every tick handed out before it.
+  pub(crate) fn advance_change_tick(&mut self) {
+    self.world.advance_change_tick();
+  }
+
+  /// Ends the frame: later filters outside systems only see changes made after this call,
+  /// and the previous frame's events are dropped.
+  pub fn end_frame(&mut self) {
+    self.change_since = self.change_tick();
+    self.advance_change_tick();
+    self.world.end_frame();
+  }
+
+  /// Stores `prefab`, replacing the one with the same id without touching its instances,
+  /// and returns its id.
+  pub fn add_prefab(&mut self, prefab: Prefab) -> Uuid {
+    let id = prefab.id();
+    match self.prefabs.iter_mut().find(|p| p.id() == id) {
+      Some(existing) => *existing = prefab,
+      None => self.prefabs.push(prefab),
+    }
+    id
+  }
+
+  pub fn prefab(&self, id: Uuid) -> Option<&Prefab> {
+    self.prefabs.iter().find(|p| p.id() == id)
+  }
+
+  pub fn prefabs(&self) -> &[Prefab] {
+    &self.prefabs
+  }
+
+  /// Adds a root instance of the stored prefab `id` and returns the instance's id.
+  pub fn instantiate(&mut self, id: Uuid) -> Result<Uuid> {
+    let instance = self
+      .prefab(id)
+      .ok_or(Error::UnknownPrefab(id))?
+      .instantiate()?;
+    let instance_id = instance.id();
+    self.add(instance);
+    Ok(instance_id)
+  }
+
+  /// Stores `prefab` and brings its instances up to date. Properties an instance changed
+  /// since it was last made from the prefab become [`PropertyOverride`]s and keep their
+  /// values; the rest, along with added and removed entities and components, follows
+  /// `prefab`.
+  pub fn update_prefab(&mut self, prefab: Prefab) -> Result<()> {
+    let Some(old) = self.prefab(prefab.id()).cloned() else {
+      self.add_prefab(prefab);
+      return Ok(());
+    };
+    let result = self.propagate(&old, &prefab, None);
+    self.add_prefab(prefab);
+    result
+  }
+
+  /// Edits the stored prefab `id` with `f` and updates its instances; see
+  /// [`Scene::update_prefab`].
+  pub fn edit_prefab(&mut self, id: Uuid, f: impl FnOnce(&mut Entity)) -> Result<()> {
+    let mut prefab = self.prefab(id).ok_or(Error::UnknownPrefab(id))?.clone();
+    let mut root = prefab.root()?;
+    f(&mut root);
+    prefab.set_root(&root)?;
+    self.update_prefab(prefab)
+  }
+
+  /// Drops `property` from the overrides of the prefab instance rooted at `instance` and
+  /// gives it the prefab's value again.
+  pub fn revert_override(&mut self, instance: Uuid, property: &PropertyOverride) -> Result<()> {
+    let Some(prefab) = self
+      .find(instance)
+      .and_then(|e| e.get_component::<PrefabInstance>())
+      .and_then(|info| self.prefab(info.prefab))
+      .cloned()
+    else {
+      return Ok(());
+    };
+    self.propagate(&prefab, &prefab, Some((instance, property)))
+  }
+
+  /// Updates the instances of `old` to `new`, reverting `revert` on one of them.
+  fn propagate(
+    &mut self,
+    old: &Prefab,
+    new: &Prefab,
+    revert: Option<(Uuid, &PropertyOverride)>,
+  ) -> Result<()> {
+    let instances: Vec<Uuid> = self
+      .query::<(Uuid, &PrefabInstance)>()
+      .filter(|(_, info)| info.prefab == old.id())
+      .map(|(id, _)| id)
+      .collect();
+    let mut result = Ok(());
+    for id in instances {
+      let reverted = match revert {
+        Some((instance, property)) if instance == id => std::slice::from_ref(property),
+        Some(_) => continue,
+        None => &[],
+      };
+      result = result.and(prefab::propagate(old, new, self, id, reverted));
+    }
+    result
+  }
+
+  /// Fetches `Q` from every entity that has all of its non-optional components, e.g.
+  /// `scene.query::<(&Transform, &Mesh)>()`, in storage order rather than hierarchy order.
+  pub fn query<Q: ReadOnlyQuery>(&self) -> impl Iterator<Item = Q::Item<'_>> {
+    self.query_filtered::<Q, ()>()
+  }
+
+  /// Like [`Scene::query`], skipping entities that fail `F`, e.g.
+  /// `scene.query_filtered::<&Mesh, Changed<Mesh>>()`.
+  pub fn query_filtered<Q: ReadOnlyQuery, F: QueryFilter>(
+    &self,
+  ) -> impl Iterator<Item = Q::Item<'_>> {
+    self.world.query::<Q, F>(self.change_since)
+  }
+
+  /// Like [`Scene::query`], but terms may borrow mutably: `scene.query_mut::<(&mut
+  /// Transform, &Velocity)>()`. `&mut` terms mark their components changed.
+  pub fn query_mut<Q: Query>(&mut self) -> impl Iterator<Item = Q::Item<'_>> {
+    self.query_filtered_mut::<Q, ()>()
+  }
+
+  /// Like [`Scene::query_mut`], skipping entities that fail `F`.
+  pub fn query_filtered_mut<Q: Query, F: QueryFilter>(
+    &mut self,
+  ) -> impl Iterator<Item = Q::Item<'_>> {
+    self.world.query_mut::<Q, F>(self.change_since)
+  }
+
+  /// Imports a GeoJSON document (feature collection, feature or bare geometry) as a new
+  /// root entity with one child per feature, and returns the root's id.
+  pub fn import_geojson(&mut self, src: &str, options: &GeoJsonOptions) -> Result<Uuid> {
+    geo::import_geojson(self, src, options)
+  }
+
+  /// Projection between geodetic positions and the current scene frame: the globe camera's
+  /// target-centered frame in globe mode, otherwise [`Scene::geo_reference`].
+  pub fn geo_frame(&self) -> Option<LocalProjection> {
+    self
+      .roots()
+      .find_map(|e| e.get_component::<GlobeCamera>().map(|g| g.frame()))
+      .or(self.geo_reference)
+  }
+
+  /// Ray from the active camera through `cursor`, in physical pixels.
+  pub fn screen_ray(&self, cursor: Vec2, viewport: Vec2) -> Ray {
+    let aspect = viewport.x / viewport.y.max(1.0);
+    Ray::from_screen(self.camera_view_proj(aspect).inverse(), cursor, viewport)
+  }
+
+  /// Closest mesh or terrain surface hit by `ray`, among the entities the active camera
+  /// draws.
+  pub fn pick(&self, ray: &Ray) -> Option<Pick> {
+    picking::pick(self, ray)
+  }
+
+  /// Ground distance covered by one pixel at the center of the view, in metres.
+  pub fn ground_resolution(&self, viewport: Vec2) -> Option<f32> {
+    if let Some(map) = self.roots().find_map(|e| e.get_component::<MapCamera>()) {
+      return Some(map.metres_per_pixel() as f32);
+    }
+    let center = viewport * 0.5;
+    let a = self.screen_ray(center, viewport).intersect_ground(0.0)?;
+    let b = self
+      .screen_ray(center + Vec2::X, viewport)
+      .intersect_ground(0.0)?;
+    Some(a.distance(b))
+  }
+
+  /// Compass bearing the active camera faces, in degrees clockwise from north.
+  pub fn camera_bearing(&self) -> f32 {
+    for entity in self.roots() {
+      if let Some(map) = entity.get_component::<MapCamera>() {
+        return map.bearing as f32;
+      }
+      if let Some(globe) = entity.get_component::<GlobeCamera>() {
+        return globe.heading as f32;
+      }
+    }
+    let Some((_, world)) = self.active_camera() else {
+      return 0.0;
+    };
+    // Looking straight down, the screen's up direction is what points ahead.
+    let forward = world.transform_vector3(Vec3::NEG_Z);
+    let ahead = if forward.with_y(0.0).length() > 1e-3 {
+      forward
+    } else {
+      world.transform_vector3(Vec3::Y)
+    };
+    ahead.x.atan2(-ahead.z).to_degrees().rem_euclid(360.0)
+  }
+
+  /// Turns the active camera to face north without moving it.
+  pub fn reset_north(&mut self) {
+    let bearing = self.camera_bearing().to_radians();
+    let Some(id) = self.roots().find(|e| e.has::<Camera>()).map(|e| e.id()) else {
+      return;
+    };
+    let Some(mut entity) = self.find_mut(id) else {
+      return;
+    };
+    if let Some(mut map) = entity.get_component_mut::<MapCamera>() {
+      map.bearing = 0.0;
+    } else if let Some(mut globe) = entity.get_component_mut::<GlobeCamera>() {
+      globe.heading = 0.0;
+    } else if let Some(mut transform) = entity.get_component_mut::<Transform>() {
+      transform.rotation = Quat::from_rotation_y(bearing) * transform.rotation;
+    }
+  }
+
+  /// Drives interactive cameras from one frame of input: [`MapCamera`]s derive their entity's
+  /// transform and clip planes, [`GlobeCamera`]s orbit and re-center the globe frame.
+  pub fn update_cameras(&mut self, input: &Input, dt: f32, viewport: Vec2) {
+    let map_center = self
+      .roots()
+      .find_map(|e| e.get_component::<MapCamera>().map(|map| map.center));
+    if self.geo_reference.is_none()
+      && let Some(center) = map_center
+    {
+      self.geo_reference = Some(LocalProjection::new(center));
+    }
+
+    let cameras: Vec<Uuid> = self
+      .roots()
+      .filter(|e| e.has::<Camera>())
+      .map(|e| e.id())
+      .collect();
+    let geo_reference = self.geo_reference;
+    for id in cameras {
+      let Some(mut entity) = self.find_mut(id) else {
+        continue;
+      };
+      let Some(camera) = entity.get_component::<Camera>().cloned() else {
+        continue;
+      };
+      if let Some(mut globe) = entity.get_component_mut::<GlobeCamera>() {
+        globe.handle_input(input, camera.fov_y, viewport.y);
+      }
+      let (Some(mut map), Some(projection)) = (
+        entity.get_component_mut::<MapCamera>(),
+        geo_reference.as_ref(),
+      ) else {
+        continue;
+      };
+      let transform = map.update(input, dt, &camera, viewport, projection);
+      let (near, far) = map.clip_range(camera.fov_y, viewport.y);
+      match entity.get_component_mut::<Transform>() {
+        Some(mut t) => *t = transform,
+        None => {
+          entity.add_component(transform);
+        }
+      }
+      if let Some(mut cam) = entity.get_component_mut::<Camera>() {
+        cam.near = near;
+        cam.far = far;
+      }
+    }
+
+    self.update_globe();
+  }
+
+  /// Places geo-anchored entities for the active [`GlobeCamera`], if any.
+  pub fn update_globe(&mut self) {
+    geo::update_globe(self);
+  }
+
+  /// Writes every entity carrying a `GeoShape` or `GeoPosition` as a GeoJSON feature collection.
+  pub fn export_geojson(&self) -> String {
+    geo::export_geojson(self)
+  }
+
+  /// First root entity with a `Camera`, along with its world matrix.
+  pub fn active_camera(&self) -> Option<(&Camera, Mat4)> {
+    self.roots().find_map(|entity| {
+      let camera = entity.get_component::<Camera>()?;
+      let world = entity
+        .get_component::<Transform>()
+        .cloned()
+        .unwrap_or_default()
+        .matrix();
+      Some((camera, world))
+    })
   }
 
   pub fn camera_view_proj(&self, aspect: f32) -> Mat4 {
-    for entity in &self.entities {
-      if let Some(camera) = entity.get_component::<Camera>() {
+    match self.active_camera() {
+      Some((camera, world)) => {
         let mut cam = camera.clone();
         cam.aspect = aspect;
-        let view = entity
-          .get_component::<Transform>()
-          .cloned()
-          .unwrap_or_default()
-          .matrix()
-          .inverse();
-        return cam.projection_matrix() * view;
+        cam.projection_matrix() * world.inverse()
       }
+      None => Mat4::IDENTITY,
     }
-    Mat4::IDENTITY
   }
 }
 
@@ -44,3 +523,39 @@ impl Default for Scene {
     Self::new()
   }
 }
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+  use crate::components::Tags;
+
+  fn tagged(scene: &mut Scene, name: &str, tags: &[&str]) -> Uuid {
+    let mut entity = Entity::new(name);
+    entity.add_component(Tags::from_iter(tags.iter().map(|t| t.to_string())));
+    let id = entity.id();
+    scene.add(entity);
+    id
+  }
+
+  #[test]
+  fn set_tagged_visibility_replaces_only_tagged_entities() {
+    let mut scene = Scene::new();
+    let road = tagged(&mut scene, "road", &["roads"]);
+    let bridge = tagged(&mut scene, "bridge", &["roads", "bridges"]);
+    let building = tagged(&mut scene, "building", &["buildings"]);
+    let visibility = |scene: &Scene, id| {
+      let entity = scene.find(id).unwrap();
+      (
+        Visibility::of(entity),
+        entity.get_components::<Visibility>().count(),
+      )
+    };
+
+    scene.set_tagged_visibility("roads", Visibility::Hidden);
+    scene.set_tagged_visibility("roads", Visibility::Visible);
+    scene.set_tagged_visibility("bridges", Visibility::Hidden);
+    assert_eq!(visibility(&scene, road), (Visibility::Visible, 1));
+    assert_eq!(visibility(&scene, bridge), (Visibility::Hidden, 1));
+    assert_eq!(visibility(&scene, building), (Visibility::Inherited, 0));
+  }
+}
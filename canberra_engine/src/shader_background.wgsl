@@ -0,0 +1,158 @@
+// Scene background, drawn as a full-screen triangle at the far plane before any geometry.
+
+const MODE_GRADIENT:   u32 = 1u;
+const MODE_SKYBOX:     u32 = 2u;
+const MODE_ATMOSPHERE: u32 = 3u;
+
+const PI: f32 = 3.14159265;
+const PRIMARY_STEPS: i32 = 16;
+const LIGHT_STEPS:   i32 = 8;
+// Cosine of the sun's angular radius (0.27°), and the disc's radiance relative to the
+// sun intensity.
+const SUN_DISC_COS: f32 = 0.99998889;
+const SUN_DISC_RADIANCE: f32 = 100.0;
+
+struct Background {
+  // Clip space to view directions: the camera's rotation only, so far-away cameras keep
+  // their precision.
+  inv_view_proj: mat4x4<f32>,
+  camera_pos: vec3<f32>,
+  mode: u32,
+  zenith: vec4<f32>,
+  horizon: vec4<f32>,
+  ground: vec4<f32>,
+  sun_direction: vec3<f32>,
+  sun_intensity: f32,
+  rayleigh_scattering: vec3<f32>,
+  mie_scattering: f32,
+  planet_radius: f32,
+  atmosphere_radius: f32,
+  rayleigh_scale_height: f32,
+  mie_scale_height: f32,
+  mie_anisotropy: f32,
+  skybox_intensity: f32,
+};
+
+@group(0) @binding(0) var<uniform> background: Background;
+@group(0) @binding(1) var skybox: texture_cube<f32>;
+@group(0) @binding(2) var skybox_sampler: sampler;
+
+struct VertexOut {
+  @builtin(position) position: vec4<f32>,
+  @location(0) ndc: vec2<f32>,
+};
+
+@vertex
+fn vs_main(@builtin(vertex_index) index: u32) -> VertexOut {
+  let ndc = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u)) * 2.0 - 1.0;
+  var out: VertexOut;
+  out.position = vec4<f32>(ndc, 1.0, 1.0);
+  out.ndc = ndc;
+  return out;
+}
+
+fn gradient(dir: vec3<f32>) -> vec3<f32> {
+  if dir.y >= 0.0 {
+    return mix(background.horizon.rgb, background.zenith.rgb, sqrt(dir.y));
+  }
+  return mix(background.horizon.rgb, background.ground.rgb, sqrt(-dir.y));
+}
+
+// Distances along the ray to where it enters and leaves the sphere at the origin; the
+// entry is past the exit on a miss.
+fn ray_sphere(origin: vec3<f32>, dir: vec3<f32>, radius: f32) -> vec2<f32> {
+  let b = dot(origin, dir);
+  let c = dot(origin, origin) - radius * radius;
+  let d = b * b - c;
+  if d < 0.0 {
+    return vec2<f32>(1e30, -1e30);
+  }
+  let s = sqrt(d);
+  return vec2<f32>(-b - s, -b + s);
+}
+
+// Rayleigh and Mie optical depths of the sample point's path toward the sun.
+fn sun_depth(origin: vec3<f32>) -> vec2<f32> {
+  let sun = background.sun_direction;
+  let step = ray_sphere(origin, sun, background.atmosphere_radius).y / f32(LIGHT_STEPS);
+  var depth = vec2<f32>(0.0);
+  for (var i = 0; i < LIGHT_STEPS; i++) {
+    let height = length(origin + sun * (f32(i) + 0.5) * step) - background.planet_radius;
+    depth += exp(-height / vec2<f32>(background.rayleigh_scale_height, background.mie_scale_height)) * step;
+  }
+  return depth;
+}
+
+fn extinction(depth: vec2<f32>) -> vec3<f32> {
+  // Mie extinction is about 1.1x its scattering.
+  return exp(-(background.rayleigh_scattering * depth.x + background.mie_scattering * 1.1 * depth.y));
+}
+
+// Single scattering along the view ray from a planet-centred origin.
+fn atmosphere(origin: vec3<f32>, dir: vec3<f32>) -> vec3<f32> {
+  let outer = ray_sphere(origin, dir, background.atmosphere_radius);
+  if outer.x > outer.y || outer.y < 0.0 {
+    return vec3<f32>(0.0);
+  }
+  let start = max(outer.x, 0.0);
+  var end = outer.y;
+  let planet = ray_sphere(origin, dir, background.planet_radius);
+  let hits_ground = planet.x < planet.y && planet.x > 0.0;
+  if hits_ground {
+    end = min(end, planet.x);
+  }
+
+  let step = (end - start) / f32(PRIMARY_STEPS);
+  var view_depth = vec2<f32>(0.0);
+  var rayleigh = vec3<f32>(0.0);
+  var mie = vec3<f32>(0.0);
+  for (var i = 0; i < PRIMARY_STEPS; i++) {
+    let point = origin + dir * (start + (f32(i) + 0.5) * step);
+    let height = length(point) - background.planet_radius;
+    let density = exp(-height / vec2<f32>(background.rayleigh_scale_height, background.mie_scale_height)) * step;
+    view_depth += density;
+    let transmittance = extinction(view_depth + sun_depth(point));
+    rayleigh += density.x * transmittance;
+    mie += density.y * transmittance;
+  }
+
+  let mu = dot(dir, background.sun_direction);
+  let g = background.mie_anisotropy;
+  let phase_rayleigh = 3.0 / (16.0 * PI) * (1.0 + mu * mu);
+  let phase_mie = 3.0 / (8.0 * PI) * ((1.0 - g * g) * (1.0 + mu * mu))
+    / ((2.0 + g * g) * pow(1.0 + g * g - 2.0 * g * mu, 1.5));
+  var color = background.sun_intensity * (
+    phase_rayleigh * background.rayleigh_scattering * rayleigh +
+    phase_mie * background.mie_scattering * mie
+  );
+
+  if !hits_ground && mu > SUN_DISC_COS {
+    color += background.sun_intensity * SUN_DISC_RADIANCE * extinction(view_depth);
+  }
+  return color;
+}
+
+@fragment
+fn fs_main(in: VertexOut) -> @location(0) vec4<f32> {
+  let far = background.inv_view_proj * vec4<f32>(in.ndc, 1.0, 1.0);
+  let dir = normalize(far.xyz / far.w);
+
+  var color: vec3<f32>;
+  switch background.mode {
+    case MODE_GRADIENT: {
+      color = gradient(dir);
+    }
+    case MODE_SKYBOX: {
+      color = textureSample(skybox, skybox_sampler, dir).rgb * background.skybox_intensity;
+    }
+    case MODE_ATMOSPHERE: {
+      // The scene's y = 0 plane is the planet's surface, just under the camera.
+      let origin = background.camera_pos + vec3<f32>(0.0, background.planet_radius, 0.0);
+      color = atmosphere(origin, dir);
+    }
+    default: {
+      color = background.zenith.rgb;
+    }
+  }
+  return vec4<f32>(color, 1.0);
+}
@@ -0,0 +1,201 @@
+struct Camera {
+  view_proj:  mat4x4<f32>,
+  time:       f32,
+  proj_scale: f32,
+  viewport:   vec2<f32>,
+}
+
+struct Polyline {
+  model:       mat4x4<f32>,
+  color:       vec4<f32>,
+  dash:        vec4<f32>,
+  width:       f32,
+  miter_limit: f32,
+  // Bit 0: width in metres; bits 1-2: join; bits 3-4: cap.
+  flags:       u32,
+  dash_count:  u32,
+}
+
+@group(0) @binding(0) var<uniform> camera: Camera;
+@group(1) @binding(0) var<uniform> polyline: Polyline;
+
+const HAS_PREV: u32 = 1u;
+const HAS_NEXT: u32 = 2u;
+
+const UNIT_METRES: u32 = 1u;
+const JOIN_MITER: u32 = 0u;
+const JOIN_ROUND: u32 = 2u;
+const CAP_BUTT: u32 = 0u;
+const CAP_ROUND: u32 = 2u;
+
+// Smallest clip-space w kept when a segment crosses behind the camera.
+const MIN_W: f32 = 1e-5;
+
+struct SegmentIn {
+  @location(0) prev:       vec3<f32>,
+  @location(1) start:      vec3<f32>,
+  @location(2) end:        vec3<f32>,
+  @location(3) next:       vec3<f32>,
+  @location(4) distance:   vec2<f32>,
+  @location(5) neighbours: u32,
+}
+
+struct VertOut {
+  @builtin(position) clip_pos: vec4<f32>,
+  // Pixels along and across the segment, measured from its start.
+  @location(0) @interpolate(linear) local: vec2<f32>,
+  // Segment length and half widths at its start and end, in pixels.
+  @location(1) @interpolate(flat) shape: vec3<f32>,
+  // Dash pattern position at the start, and pattern units per pixel along the segment.
+  @location(2) @interpolate(flat) dash: vec2<f32>,
+  // Bit 0: round start; bit 1: round end.
+  @location(3) @interpolate(flat) round_ends: u32,
+}
+
+fn to_screen(clip: vec4<f32>) -> vec2<f32> {
+  return clip.xy / clip.w * camera.viewport * 0.5;
+}
+
+// Fraction of the way from `a` to `b` where the segment comes in front of the camera.
+fn near_t(a: vec4<f32>, b: vec4<f32>) -> f32 {
+  if a.w >= MIN_W {
+    return 0.0;
+  }
+  return (MIN_W - a.w) / (b.w - a.w);
+}
+
+fn half_width(clip: vec4<f32>) -> f32 {
+  if (polyline.flags & UNIT_METRES) != 0u {
+    // A world length at depth w spans proj_scale / w of half the viewport height.
+    return 0.25 * polyline.width * camera.proj_scale * camera.viewport.y / clip.w;
+  }
+  return 0.5 * polyline.width;
+}
+
+@vertex
+fn vs_main(in: SegmentIn, @builtin(vertex_index) index: u32) -> VertOut {
+  var out: VertOut;
+  let mvp = camera.view_proj * polyline.model;
+  let raw0 = mvp * vec4<f32>(in.start, 1.0);
+  let raw1 = mvp * vec4<f32>(in.end, 1.0);
+  if raw0.w < MIN_W && raw1.w < MIN_W {
+    // Entirely behind the camera: collapse outside the clip volume.
+    out.clip_pos = vec4<f32>(0.0, 0.0, -1.0, 1.0);
+    return out;
+  }
+
+  let t0 = near_t(raw0, raw1);
+  let t1 = near_t(raw1, raw0);
+  let c0 = mix(raw0, raw1, t0);
+  let c1 = mix(raw1, raw0, t1);
+  let s0 = to_screen(c0);
+  let s1 = to_screen(c1);
+  let seg_length = distance(s0, s1);
+  let dir = select(vec2<f32>(1.0, 0.0), (s1 - s0) / seg_length, seg_length > 1e-6);
+  let normal = vec2<f32>(-dir.y, dir.x);
+  let h0 = half_width(c0);
+  let h1 = half_width(c1);
+
+  let join = (polyline.flags >> 1u) & 3u;
+  let cap = (polyline.flags >> 3u) & 3u;
+  let prev_clip = mvp * vec4<f32>(in.prev, 1.0);
+  let next_clip = mvp * vec4<f32>(in.next, 1.0);
+  // Joins need both neighbours in front of the camera; otherwise the end is treated as open.
+  let has_prev = (in.neighbours & HAS_PREV) != 0u && t0 == 0.0 && prev_clip.w >= MIN_W;
+  let has_next = (in.neighbours & HAS_NEXT) != 0u && t1 == 0.0 && next_clip.w >= MIN_W;
+  let round0 = select(cap == CAP_ROUND, join == JOIN_ROUND, has_prev);
+  let round1 = select(cap == CAP_ROUND, join == JOIN_ROUND, has_next);
+  // Square and round ends reach half the width past the point; miter and bevel joins
+  // end flush and are closed by the next segment's wedge.
+  let ext0 = select(0.0, h0, select(cap != CAP_BUTT, join == JOIN_ROUND, has_prev));
+  let ext1 = select(0.0, h1, select(cap != CAP_BUTT, join == JOIN_ROUND, has_next));
+
+  var screen = s0;
+  var clip = c0;
+  if index < 6u {
+    // Body quad: x selects the end, y the side.
+    var quad = array<vec2<f32>, 6>(
+      vec2<f32>(0.0, -1.0), vec2<f32>(0.0, 1.0), vec2<f32>(1.0, 1.0),
+      vec2<f32>(0.0, -1.0), vec2<f32>(1.0, 1.0), vec2<f32>(1.0, -1.0),
+    );
+    let corner = quad[index];
+    if corner.x == 0.0 {
+      screen = s0 - dir * ext0 + normal * corner.y * h0;
+    } else {
+      screen = s1 + dir * ext1 + normal * corner.y * h1;
+      clip = c1;
+    }
+    out.local = vec2<f32>(dot(screen - s0, dir), dot(screen - s0, normal));
+  } else {
+    // Wedge on the outer side of the join with the previous segment.
+    if has_prev && join != JOIN_ROUND {
+      let sp = to_screen(prev_clip);
+      let prev_length = distance(sp, s0);
+      let prev_dir = select(dir, (s0 - sp) / prev_length, prev_length > 1e-6);
+      let prev_normal = vec2<f32>(-prev_dir.y, prev_dir.x);
+      let turn = prev_dir.x * dir.y - prev_dir.y * dir.x;
+      let side = select(1.0, -1.0, turn > 0.0);
+      let a = s0 + prev_normal * side * h0;
+      let b = s0 + normal * side * h0;
+      var tip = (a + b) * 0.5;
+      let bisector = prev_normal + normal;
+      if join == JOIN_MITER && dot(bisector, bisector) > 1e-6 {
+        let miter = normalize(bisector);
+        let cos_half = dot(miter, normal);
+        if cos_half * polyline.miter_limit >= 1.0 {
+          tip = s0 + miter * side * h0 / cos_half;
+        }
+      }
+      var wedge = array<vec2<f32>, 6>(s0, a, tip, s0, tip, b);
+      screen = wedge[index - 6u];
+    }
+    out.local = vec2<f32>(0.0, 0.0);
+  }
+
+  out.clip_pos = vec4<f32>(screen / (camera.viewport * 0.5) * clip.w, clip.z, clip.w);
+  out.shape = vec3<f32>(seg_length, h0, h1);
+  out.round_ends = u32(round0) | (u32(round1) << 1u);
+
+  let start = mix(in.distance.x, in.distance.y, t0);
+  let end = mix(in.distance.y, in.distance.x, t1);
+  if (polyline.flags & UNIT_METRES) != 0u {
+    out.dash = vec2<f32>(start, (end - start) / max(seg_length, 1e-6));
+  } else {
+    // Pixel dashes assume the scale of this segment held for the whole line so far.
+    out.dash = vec2<f32>(start * seg_length / max(end - start, 1e-6), 1.0);
+  }
+  return out;
+}
+
+@fragment
+fn fs_main(in: VertOut) -> @location(0) vec4<f32> {
+  let u = in.local.x;
+  let seg_length = in.shape.x;
+  if u < 0.0 && (in.round_ends & 1u) != 0u && dot(in.local, in.local) > in.shape.y * in.shape.y {
+    discard;
+  }
+  if u > seg_length && (in.round_ends & 2u) != 0u
+    && distance(in.local, vec2<f32>(seg_length, 0.0)) > in.shape.z {
+    discard;
+  }
+
+  if polyline.dash_count > 0u {
+    var total = 0.0;
+    for (var i = 0u; i < polyline.dash_count; i++) {
+      total += polyline.dash[i];
+    }
+    let along = in.dash.x + clamp(u, 0.0, seg_length) * in.dash.y;
+    var phase = along - floor(along / total) * total;
+    for (var i = 0u; i < polyline.dash_count; i++) {
+      if phase < polyline.dash[i] {
+        if i % 2u == 1u {
+          discard;
+        }
+        break;
+      }
+      phase -= polyline.dash[i];
+    }
+  }
+
+  return polyline.color;
+}
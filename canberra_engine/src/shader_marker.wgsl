@@ -0,0 +1,141 @@
+struct Camera {
+  view_proj:  mat4x4<f32>,
+  time:       f32,
+  proj_scale: f32,
+  viewport:   vec2<f32>,
+}
+
+@group(0) @binding(0) var<uniform> camera: Camera;
+
+const SHAPE_SQUARE: u32 = 1u;
+const SHAPE_DIAMOND: u32 = 2u;
+const SHAPE_TRIANGLE: u32 = 3u;
+
+// Border thickness as a fraction of the radius.
+const OUTLINE: f32 = 0.2;
+
+// 3x5 pixel glyphs for 0-9 and "k", row-major from the top-left, one bit per pixel.
+const GLYPHS = array<u32, 11>(
+  0x7b6fu, 0x2c97u, 0x73e7u, 0x73cfu, 0x5bc9u, 0x79cfu, 0x79efu, 0x7249u, 0x7befu, 0x7bcfu,
+  0x4badu,
+);
+const GLYPH_K: u32 = 10u;
+
+struct MarkerIn {
+  @location(0) position: vec3<f32>,
+  @location(1) size:     f32,
+  @location(2) color:    vec4<f32>,
+  @location(3) shape:    u32,
+  @location(4) count:    u32,
+}
+
+struct VertOut {
+  @builtin(position) clip_pos: vec4<f32>,
+  // -1..1 across the icon, +y up.
+  @location(0) uv: vec2<f32>,
+  @location(1) @interpolate(flat) color: vec4<f32>,
+  @location(2) @interpolate(flat) shape: u32,
+  @location(3) @interpolate(flat) count: u32,
+}
+
+@vertex
+fn vs_main(in: MarkerIn, @builtin(vertex_index) index: u32) -> VertOut {
+  var corners = array<vec2<f32>, 6>(
+    vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, -1.0), vec2<f32>(1.0, 1.0),
+    vec2<f32>(-1.0, -1.0), vec2<f32>(1.0, 1.0), vec2<f32>(-1.0, 1.0),
+  );
+  let corner = corners[index];
+
+  var out: VertOut;
+  let center = camera.view_proj * vec4<f32>(in.position, 1.0);
+  // Offset in NDC, scaled by w so the icon keeps its pixel size at any depth.
+  let offset = corner * in.size / camera.viewport * center.w;
+  out.clip_pos = vec4<f32>(center.xy + offset, center.zw);
+  out.uv = corner;
+  out.color = in.color;
+  out.shape = in.shape;
+  out.count = in.count;
+  return out;
+}
+
+// Normalized distance from the center: 1 on the outline of the shape.
+fn shape_distance(shape: u32, uv: vec2<f32>) -> f32 {
+  let a = abs(uv);
+  switch shape {
+    case SHAPE_SQUARE: {
+      return max(a.x, a.y) / 0.85;
+    }
+    case SHAPE_DIAMOND: {
+      return a.x + a.y;
+    }
+    case SHAPE_TRIANGLE: {
+      // Equilateral, pointing up, circumscribed by the unit circle.
+      return max(-uv.y, dot(vec2<f32>(0.866, 0.5), vec2<f32>(a.x, uv.y))) / 0.5;
+    }
+    default: {
+      return length(uv);
+    }
+  }
+}
+
+fn glyph_pixel(glyph: u32, col: u32, row: u32) -> bool {
+  var glyphs = GLYPHS;
+  return ((glyphs[glyph] >> (14u - (row * 3u + col))) & 1u) != 0u;
+}
+
+// Whether `uv` falls on the count text; thousands are shortened to "12k".
+fn count_text(count: u32, uv: vec2<f32>) -> bool {
+  var value = count;
+  var suffix = 0u;
+  if count >= 1000u {
+    value = min(count / 1000u, 999u);
+    suffix = 1u;
+  }
+  let digits = select(select(1u, 2u, value >= 10u), 3u, value >= 100u);
+  let glyph_count = digits + suffix;
+  // Glyphs are 3 pixels wide with one pixel of spacing.
+  let text_width = f32(glyph_count * 4u - 1u);
+  let pixel = min(1.3 / text_width, 0.22);
+  let p = vec2<f32>(uv.x / pixel + text_width * 0.5, 2.5 - uv.y / pixel);
+  if p.x < 0.0 || p.y < 0.0 || p.x >= text_width || p.y >= 5.0 {
+    return false;
+  }
+
+  let col = u32(p.x);
+  let slot = col / 4u;
+  if col % 4u == 3u {
+    return false;
+  }
+  var glyph = GLYPH_K;
+  if slot < digits {
+    var divisor = 1u;
+    for (var i = slot + 1u; i < digits; i++) {
+      divisor *= 10u;
+    }
+    glyph = (value / divisor) % 10u;
+  }
+  return glyph_pixel(glyph, col % 4u, u32(p.y));
+}
+
+@fragment
+fn fs_main(in: VertOut) -> @location(0) vec4<f32> {
+  if in.count > 1u {
+    let d = length(in.uv);
+    if d > 1.0 {
+      discard;
+    }
+    if d > 1.0 - OUTLINE * 0.5 || count_text(in.count, in.uv) {
+      return vec4<f32>(1.0, 1.0, 1.0, 1.0);
+    }
+    return in.color;
+  }
+
+  let d = shape_distance(in.shape, in.uv);
+  if d > 1.0 {
+    discard;
+  }
+  if d > 1.0 - OUTLINE {
+    return vec4<f32>(in.color.rgb * 0.5, in.color.a);
+  }
+  return in.color;
+}
@@ -6,11 +6,31 @@ struct Camera {
 struct Object {
   model: mat4x4<f32>,
   color: vec4<f32>,
+  fade:  f32,
 }
 
 @group(0) @binding(0) var<uniform> camera: Camera;
 @group(1) @binding(0) var<uniform> object: Object;
 
+// 4x4 ordered-dither thresholds for LOD cross-fades.
+const BAYER = array<f32, 16>(
+   0.0,  8.0,  2.0, 10.0,
+  12.0,  4.0, 14.0,  6.0,
+   3.0, 11.0,  1.0,  9.0,
+  15.0,  7.0, 13.0,  5.0,
+);
+
+// Keeps the fraction `fade` of pixels, or the complementary fraction `-fade` when negative.
+fn faded_out(frag: vec4<f32>) -> bool {
+  var bayer = BAYER;
+  let p = vec2<u32>(frag.xy) % 4u;
+  let threshold = (bayer[p.y * 4u + p.x] + 0.5) / 16.0;
+  if object.fade >= 0.0 {
+    return threshold >= object.fade;
+  }
+  return threshold < 1.0 + object.fade;
+}
+
 struct VertIn {
   @location(0) position: vec3<f32>,
   @location(1) normal:   vec3<f32>,
@@ -31,5 +51,8 @@ fn vs_main(in: VertIn) -> VertOut {
 
 @fragment
 fn fs_main(in: VertOut) -> @location(0) vec4<f32> {
+  if faded_out(in.clip_pos) {
+    discard;
+  }
   return in.color;
 }
@@ -0,0 +1,426 @@
+//! Quadric error metric mesh simplification (Garland & Heckbert).
+
+use std::{
+  cmp::Ordering,
+  collections::{BinaryHeap, HashMap, HashSet},
+};
+
+use glam::{DMat3, DVec3, Vec3};
+
+use crate::{Error, Result, Vertex, components::Mesh};
+
+/// Extra weight of the planes that pin open borders, so silhouettes and footprints survive.
+const BOUNDARY_WEIGHT: f64 = 100.0;
+/// Collapses turning any remaining face further than this (cosine) are rejected as flips.
+const MIN_FACE_COS: f64 = 0.2;
+/// Neighbouring faces bent further than this (cosine) get separate normals, keeping hard edges.
+const CREASE_COS: f32 = 0.5;
+
+/// Symmetric 4x4 error quadric, upper triangle: a², ab, ac, ad, b², bc, bd, c², cd, d².
+#[derive(Debug, Clone, Copy, Default)]
+struct Quadric([f64; 10]);
+
+impl Quadric {
+  /// Squared distance to the plane `normal · p + d = 0`, scaled by `weight`.
+  fn plane(normal: DVec3, d: f64, weight: f64) -> Self {
+    let DVec3 { x: a, y: b, z: c } = normal;
+    Self(
+      [
+        a * a,
+        a * b,
+        a * c,
+        a * d,
+        b * b,
+        b * c,
+        b * d,
+        c * c,
+        c * d,
+        d * d,
+      ]
+      .map(|v| v * weight),
+    )
+  }
+
+  fn add(&mut self, other: &Self) {
+    for (a, b) in self.0.iter_mut().zip(other.0) {
+      *a += b;
+    }
+  }
+
+  fn error(&self, p: DVec3) -> f64 {
+    let [a2, ab, ac, ad, b2, bc, bd, c2, cd, d2] = self.0;
+    let DVec3 { x, y, z } = p;
+    a2 * x * x
+      + 2.0 * ab * x * y
+      + 2.0 * ac * x * z
+      + 2.0 * ad * x
+      + b2 * y * y
+      + 2.0 * bc * y * z
+      + 2.0 * bd * y
+      + c2 * z * z
+      + 2.0 * cd * z
+      + d2
+  }
+
+  /// Point of least error, when the quadric is well conditioned.
+  fn optimum(&self) -> Option<DVec3> {
+    let [a2, ab, ac, ad, b2, bc, bd, c2, cd, _] = self.0;
+    let m = DMat3::from_cols(
+      DVec3::new(a2, ab, ac),
+      DVec3::new(ab, b2, bc),
+      DVec3::new(ac, bc, c2),
+    );
+    (m.determinant().abs() > 1e-12).then(|| m.inverse() * -DVec3::new(ad, bd, cd))
+  }
+}
+
+/// A candidate collapse of edge `(a, b)` into `target`, valid while both stamps match.
+struct Candidate {
+  cost: f64,
+  a: u32,
+  b: u32,
+  target: DVec3,
+  stamps: (u32, u32),
+}
+
+impl PartialEq for Candidate {
+  fn eq(&self, other: &Self) -> bool {
+    self.cost == other.cost
+  }
+}
+
+impl Eq for Candidate {}
+
+impl PartialOrd for Candidate {
+  fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+    Some(self.cmp(other))
+  }
+}
+
+impl Ord for Candidate {
+  // Reversed so the max-heap pops the cheapest collapse first.
+  fn cmp(&self, other: &Self) -> Ordering {
+    other.cost.total_cmp(&self.cost)
+  }
+}
+
+struct Simplifier {
+  positions: Vec<DVec3>,
+  quadrics: Vec<Quadric>,
+  triangles: Vec<[u32; 3]>,
+  alive: Vec<bool>,
+  /// Triangles touching each vertex; may hold dead entries.
+  vertex_triangles: Vec<Vec<u32>>,
+  removed: Vec<bool>,
+  stamps: Vec<u32>,
+  heap: BinaryHeap<Candidate>,
+}
+
+/// Collapses edges of `mesh` until at most `target_triangles` remain or no collapse is
+/// possible without folding the surface over. Normals are rebuilt, keeping hard edges.
+pub(crate) fn simplify(mesh: &Mesh, target_triangles: usize) -> Result<Mesh> {
+  // Weld split vertices so the surface is connected across hard edges.
+  let mut welded = HashMap::new();
+  let mut positions = Vec::new();
+  let remap: Vec<u32> = mesh
+    .vertices()
+    .iter()
+    .map(|v| {
+      *welded
+        .entry(v.position.map(f32::to_bits))
+        .or_insert_with(|| {
+          positions.push(Vec3::from(v.position).as_dvec3());
+          positions.len() as u32 - 1
+        })
+    })
+    .collect();
+  let triangles: Vec<[u32; 3]> = mesh
+    .indices()
+    .chunks_exact(3)
+    .map(|t| {
+      [
+        remap[t[0] as usize],
+        remap[t[1] as usize],
+        remap[t[2] as usize],
+      ]
+    })
+    .filter(|[a, b, c]| a != b && b != c && a != c)
+    .collect();
+
+  let mut simplifier = Simplifier::new(positions, triangles);
+  simplifier.run(target_triangles);
+  simplifier.build()
+}
+
+impl Simplifier {
+  fn new(positions: Vec<DVec3>, triangles: Vec<[u32; 3]>) -> Self {
+    let n = positions.len();
+    let mut quadrics = vec![Quadric::default(); n];
+    let mut vertex_triangles = vec![Vec::new(); n];
+    let mut edges: HashMap<(u32, u32), (u32, u32)> = HashMap::new();
+
+    for (t, tri) in triangles.iter().enumerate() {
+      let [p0, p1, p2] = tri.map(|v| positions[v as usize]);
+      let cross = (p1 - p0).cross(p2 - p0);
+      let area = cross.length() * 0.5;
+      if area > 0.0 {
+        let normal = cross.normalize();
+        let q = Quadric::plane(normal, -normal.dot(p0), area);
+        for &v in tri {
+          quadrics[v as usize].add(&q);
+        }
+      }
+      for i in 0..3 {
+        vertex_triangles[tri[i] as usize].push(t as u32);
+        let (a, b) = (tri[i], tri[(i + 1) % 3]);
+        edges.entry((a.min(b), a.max(b))).or_insert((0, t as u32)).0 += 1;
+      }
+    }
+
+    // Border edges get a plane perpendicular to their face, pinning the outline in place.
+    for (&(a, b), &(count, t)) in &edges {
+      if count != 1 {
+        continue;
+      }
+      let [p0, p1, p2] = triangles[t as usize].map(|v| positions[v as usize]);
+      let face = (p1 - p0).cross(p2 - p0).normalize_or_zero();
+      let (pa, pb) = (positions[a as usize], positions[b as usize]);
+      let normal = (pb - pa).cross(face).normalize_or_zero();
+      if normal == DVec3::ZERO {
+        continue;
+      }
+      let q = Quadric::plane(
+        normal,
+        -normal.dot(pa),
+        BOUNDARY_WEIGHT * pa.distance_squared(pb),
+      );
+      quadrics[a as usize].add(&q);
+      quadrics[b as usize].add(&q);
+    }
+
+    let mut simplifier = Self {
+      alive: vec![true; triangles.len()],
+      removed: vec![false; n],
+      stamps: vec![0; n],
+      heap: BinaryHeap::with_capacity(edges.len()),
+      positions,
+      quadrics,
+      triangles,
+      vertex_triangles,
+    };
+    for &(a, b) in edges.keys() {
+      simplifier.push(a, b);
+    }
+    simplifier
+  }
+
+  fn push(&mut self, a: u32, b: u32) {
+    let mut q = self.quadrics[a as usize];
+    q.add(&self.quadrics[b as usize]);
+    let (pa, pb) = (self.positions[a as usize], self.positions[b as usize]);
+    let target = q
+      .optimum()
+      .into_iter()
+      .chain([pa, pb, (pa + pb) * 0.5])
+      .min_by(|x, y| q.error(*x).total_cmp(&q.error(*y)))
+      .unwrap();
+    self.heap.push(Candidate {
+      cost: q.error(target),
+      a,
+      b,
+      target,
+      stamps: (self.stamps[a as usize], self.stamps[b as usize]),
+    });
+  }
+
+  fn run(&mut self, target_triangles: usize) {
+    let mut live = self.triangles.len();
+    while live > target_triangles {
+      let Some(c) = self.heap.pop() else {
+        break;
+      };
+      let (a, b) = (c.a as usize, c.b as usize);
+      if self.removed[a]
+        || self.removed[b]
+        || c.stamps != (self.stamps[a], self.stamps[b])
+        || self.folds(c.a, c.b, c.target)
+      {
+        continue;
+      }
+
+      self.positions[a] = c.target;
+      let q = self.quadrics[b];
+      self.quadrics[a].add(&q);
+      self.removed[b] = true;
+      for t in std::mem::take(&mut self.vertex_triangles[b]) {
+        if !self.alive[t as usize] {
+          continue;
+        }
+        let tri = &mut self.triangles[t as usize];
+        if tri.contains(&c.a) {
+          self.alive[t as usize] = false;
+          live -= 1;
+        } else {
+          tri.iter_mut().filter(|v| **v == c.b).for_each(|v| *v = c.a);
+          self.vertex_triangles[a].push(t);
+        }
+      }
+      let alive = &self.alive;
+      self.vertex_triangles[a].retain(|&t| alive[t as usize]);
+      self.stamps[a] += 1;
+
+      let neighbours: HashSet<u32> = self.vertex_triangles[a]
+        .iter()
+        .flat_map(|&t| self.triangles[t as usize])
+        .filter(|&v| v != c.a)
+        .collect();
+      for n in neighbours {
+        self.push(c.a, n);
+      }
+    }
+  }
+
+  /// Whether moving `a` and `b` to `target` would flip or flatten a surviving face.
+  fn folds(&self, a: u32, b: u32, target: DVec3) -> bool {
+    let faces = self.vertex_triangles[a as usize]
+      .iter()
+      .chain(&self.vertex_triangles[b as usize]);
+    for &t in faces {
+      let tri = self.triangles[t as usize];
+      if !self.alive[t as usize] || (tri.contains(&a) && tri.contains(&b)) {
+        continue;
+      }
+      let before = tri.map(|v| self.positions[v as usize]);
+      let after = tri.map(|v| {
+        if v == a || v == b {
+          target
+        } else {
+          self.positions[v as usize]
+        }
+      });
+      let normal = |[p0, p1, p2]: [DVec3; 3]| (p1 - p0).cross(p2 - p0);
+      let (n0, n1) = (normal(before), normal(after));
+      if n1.length_squared() <= f64::EPSILON * n0.length_squared()
+        || n0.normalize_or_zero().dot(n1.normalize_or_zero()) < MIN_FACE_COS
+      {
+        return true;
+      }
+    }
+    false
+  }
+
+  fn build(self) -> Result<Mesh> {
+    let triangles: Vec<[u32; 3]> = self
+      .triangles
+      .iter()
+      .zip(&self.alive)
+      .filter_map(|(tri, &alive)| alive.then_some(*tri))
+      .collect();
+    let positions: Vec<Vec3> = self.positions.iter().map(|p| p.as_vec3()).collect();
+    // Area-weighted face normals (length is twice the area).
+    let faces: Vec<Vec3> = triangles
+      .iter()
+      .map(|t| {
+        let [p0, p1, p2] = t.map(|v| positions[v as usize]);
+        (p1 - p0).cross(p2 - p0)
+      })
+      .collect();
+    let mut around = vec![Vec::new(); positions.len()];
+    for (t, tri) in triangles.iter().enumerate() {
+      for &v in tri {
+        around[v as usize].push(t);
+      }
+    }
+
+    let mut vertices = Vec::new();
+    let mut indices = Vec::with_capacity(triangles.len() * 3);
+    let mut lookup: HashMap<(u32, [u32; 3]), u16> = HashMap::new();
+    for (t, tri) in triangles.iter().enumerate() {
+      let own = faces[t].normalize_or_zero();
+      for &v in tri {
+        let normal = around[v as usize]
+          .iter()
+          .map(|&s| faces[s])
+          .filter(|n| n.normalize_or_zero().dot(own) > CREASE_COS)
+          .sum::<Vec3>()
+          .normalize_or(own);
+        let key = (v, normal.to_array().map(|c| (c * 1024.0).round().to_bits()));
+        let index = match lookup.get(&key) {
+          Some(&i) => i,
+          None => {
+            let i =
+              u16::try_from(vertices.len()).map_err(|_| Error::MeshTooLarge(vertices.len() + 1))?;
+            vertices.push(Vertex {
+              position: positions[v as usize].into(),
+              normal: normal.into(),
+            });
+            lookup.insert(key, i);
+            i
+          }
+        };
+        indices.push(index);
+      }
+    }
+    Ok(Mesh::new(vertices, indices))
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Flat `n` x `n` grid of quads on the XZ plane, facing up.
+  fn grid(n: u16) -> Mesh {
+    let mut vertices = Vec::new();
+    for z in 0..=n {
+      for x in 0..=n {
+        vertices.push(Vertex {
+          position: [x as f32, 0.0, z as f32],
+          normal: [0.0, 1.0, 0.0],
+        });
+      }
+    }
+    let mut indices = Vec::new();
+    for z in 0..n {
+      for x in 0..n {
+        let i = z * (n + 1) + x;
+        indices.extend([i, i + n + 1, i + 1, i + 1, i + n + 1, i + n + 2]);
+      }
+    }
+    Mesh::new(vertices, indices)
+  }
+
+  #[test]
+  fn flat_grid_collapses_to_its_outline() {
+    let mesh = grid(8);
+    let simplified = simplify(&mesh, 2).unwrap();
+    let triangles = simplified.indices().len() / 3;
+    assert!(
+      triangles < mesh.indices().len() / 3 / 4,
+      "{triangles} triangles left"
+    );
+    assert_eq!(simplified.bounds(), mesh.bounds());
+    for vertex in simplified.vertices() {
+      assert_eq!(vertex.position[1], 0.0);
+      assert_eq!(vertex.normal, [0.0, 1.0, 0.0]);
+    }
+  }
+
+  #[test]
+  fn target_above_triangle_count_keeps_everything() {
+    let mesh = grid(2);
+    let simplified = simplify(&mesh, 100).unwrap();
+    assert_eq!(simplified.indices().len(), mesh.indices().len());
+  }
+
+  #[test]
+  fn cube_keeps_hard_edges() {
+    let cube = Mesh::cube();
+    let simplified = simplify(&cube, 12).unwrap();
+    assert_eq!(simplified.bounds(), cube.bounds());
+    for vertex in simplified.vertices() {
+      let normal = Vec3::from(vertex.normal);
+      assert!((normal.abs().max_element() - 1.0).abs() < 1e-5, "{normal}");
+    }
+  }
+}
@@ -1,4 +1,7 @@
+mod aabb;
+mod frustum;
+mod ray;
 mod shader;
 mod vertex;
 
-pub use self::{shader::Shader, vertex::Vertex};
+pub use self::{aabb::Aabb, frustum::Frustum, ray::Ray, shader::Shader, vertex::Vertex};
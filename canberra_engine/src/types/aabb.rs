@@ -0,0 +1,55 @@
+use glam::{Mat3, Mat4, Vec3};
+
+/// Axis-aligned bounding box.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Aabb {
+  pub min: Vec3,
+  pub max: Vec3,
+}
+
+impl Aabb {
+  pub fn new(min: Vec3, max: Vec3) -> Self {
+    Self { min, max }
+  }
+
+  /// Smallest box containing every point, or `None` when there are none.
+  pub fn from_points(points: impl IntoIterator<Item = Vec3>) -> Option<Self> {
+    points.into_iter().fold(None, |acc: Option<Self>, p| {
+      Some(acc.map_or(Self::new(p, p), |b| Self::new(b.min.min(p), b.max.max(p))))
+    })
+  }
+
+  pub fn center(&self) -> Vec3 {
+    (self.min + self.max) * 0.5
+  }
+
+  pub fn half_extents(&self) -> Vec3 {
+    (self.max - self.min) * 0.5
+  }
+
+  /// Radius of the bounding sphere around [`Aabb::center`].
+  pub fn radius(&self) -> f32 {
+    self.half_extents().length()
+  }
+
+  pub fn union(&self, other: &Self) -> Self {
+    Self::new(self.min.min(other.min), self.max.max(other.max))
+  }
+
+  /// Grows the box by `margin` on every side.
+  pub fn inflate(&self, margin: f32) -> Self {
+    Self::new(self.min - margin, self.max + margin)
+  }
+
+  /// Box enclosing this one after transformation by `m`.
+  pub fn transform(&self, m: Mat4) -> Self {
+    let center = m.transform_point3(self.center());
+    let abs = Mat3::from_cols(
+      m.x_axis.truncate().abs(),
+      m.y_axis.truncate().abs(),
+      m.z_axis.truncate().abs(),
+    );
+    let extent = abs * self.half_extents();
+    Self::new(center - extent, center + extent)
+  }
+}
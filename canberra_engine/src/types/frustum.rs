@@ -0,0 +1,81 @@
+use glam::{Mat4, Vec3, Vec4};
+
+use super::Aabb;
+
+/// The six clip planes of a view-projection matrix, normals pointing inward.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Frustum {
+  planes: [Vec4; 6],
+}
+
+impl Frustum {
+  /// Extracts the planes for wgpu's clip volume (depth in `0..=1`).
+  pub fn from_view_proj(m: Mat4) -> Self {
+    let (r0, r1, r2, r3) = (m.row(0), m.row(1), m.row(2), m.row(3));
+    let planes = [r3 + r0, r3 - r0, r3 + r1, r3 - r1, r2, r3 - r2]
+      .map(|p| p / p.truncate().length().max(f32::MIN_POSITIVE));
+    Self { planes }
+  }
+
+  /// Conservative test: may report boxes just outside a frustum corner as intersecting.
+  pub fn intersects_aabb(&self, aabb: &Aabb) -> bool {
+    self.planes.iter().all(|p| {
+      let normal = p.truncate();
+      // Corner furthest along the plane normal.
+      let corner = Vec3::select(normal.cmpge(Vec3::ZERO), aabb.max, aabb.min);
+      normal.dot(corner) + p.w >= 0.0
+    })
+  }
+
+  pub fn intersects_sphere(&self, center: Vec3, radius: f32) -> bool {
+    self
+      .planes
+      .iter()
+      .all(|p| p.truncate().dot(center) + p.w >= -radius)
+  }
+
+  pub fn contains_point(&self, point: Vec3) -> bool {
+    self.intersects_sphere(point, 0.0)
+  }
+}
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  /// Camera at the origin looking down -Z, 90° vertical field of view, near 1, far 100.
+  fn frustum() -> Frustum {
+    let proj = Mat4::perspective_rh(std::f32::consts::FRAC_PI_2, 1.0, 1.0, 100.0);
+    Frustum::from_view_proj(proj * Mat4::look_to_rh(Vec3::ZERO, Vec3::NEG_Z, Vec3::Y))
+  }
+
+  #[test]
+  fn points_inside_and_outside() {
+    let frustum = frustum();
+    assert!(frustum.contains_point(Vec3::new(0.0, 0.0, -10.0)));
+    assert!(frustum.contains_point(Vec3::new(9.0, -9.0, -10.0)));
+    assert!(!frustum.contains_point(Vec3::new(11.0, 0.0, -10.0)));
+    assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, 10.0)));
+    assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -0.5)));
+    assert!(!frustum.contains_point(Vec3::new(0.0, 0.0, -101.0)));
+  }
+
+  #[test]
+  fn boxes_straddling_a_plane_intersect() {
+    let frustum = frustum();
+    let straddling = Aabb::new(Vec3::new(9.0, -1.0, -11.0), Vec3::new(12.0, 1.0, -9.0));
+    assert!(frustum.intersects_aabb(&straddling));
+    let behind = Aabb::new(Vec3::new(-1.0, -1.0, 1.0), Vec3::new(1.0, 1.0, 3.0));
+    assert!(!frustum.intersects_aabb(&behind));
+    let beyond_far = Aabb::new(Vec3::new(-1.0, -1.0, -200.0), Vec3::new(1.0, 1.0, -150.0));
+    assert!(!frustum.intersects_aabb(&beyond_far));
+  }
+
+  #[test]
+  fn spheres_count_their_radius() {
+    let frustum = frustum();
+    let center = Vec3::new(12.0, 0.0, -10.0);
+    assert!(!frustum.intersects_sphere(center, 1.0));
+    assert!(frustum.intersects_sphere(center, 2.0));
+  }
+}
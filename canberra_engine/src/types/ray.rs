@@ -0,0 +1,50 @@
+use glam::{Mat4, Vec2, Vec3};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Ray {
+  pub origin: Vec3,
+  /// Unit length.
+  pub direction: Vec3,
+}
+
+impl Ray {
+  pub fn new(origin: Vec3, direction: Vec3) -> Self {
+    Self {
+      origin,
+      direction: direction.normalize(),
+    }
+  }
+
+  /// Ray through `cursor` (physical pixels, origin top-left) for the camera whose
+  /// inverse view-projection matrix is `inv_view_proj`.
+  pub fn from_screen(inv_view_proj: Mat4, cursor: Vec2, viewport: Vec2) -> Self {
+    let ndc = Vec2::new(
+      cursor.x / viewport.x * 2.0 - 1.0,
+      1.0 - cursor.y / viewport.y * 2.0,
+    );
+    let near = inv_view_proj.project_point3(ndc.extend(0.0));
+    let far = inv_view_proj.project_point3(ndc.extend(1.0));
+    Self::new(near, far - near)
+  }
+
+  pub fn at(&self, t: f32) -> Vec3 {
+    self.origin + self.direction * t
+  }
+
+  /// Distance along the ray to the plane through `point` with `normal`, if hit in front.
+  pub fn intersect_plane(&self, point: Vec3, normal: Vec3) -> Option<f32> {
+    let denom = self.direction.dot(normal);
+    if denom.abs() < f32::EPSILON {
+      return None;
+    }
+    let t = (point - self.origin).dot(normal) / denom;
+    (t >= 0.0).then_some(t)
+  }
+
+  /// Hit point on the horizontal plane `y = height`.
+  pub fn intersect_ground(&self, height: f32) -> Option<Vec3> {
+    self
+      .intersect_plane(Vec3::new(0.0, height, 0.0), Vec3::Y)
+      .map(|t| self.at(t))
+  }
+}